@@ -0,0 +1,382 @@
+use crate::{
+    merger::{Placement, Rect},
+    MergeError,
+};
+
+use serde_json::{json, Map, Value};
+use std::fmt::Write;
+
+/// A single named image on an [Atlas](Atlas).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AtlasEntry {
+    /// The name of the image, its index unless it was renamed.
+    pub name: String,
+    /// Where the image was placed on the canvas.
+    pub placement: Placement,
+}
+
+/// A placement map of every image on a merged canvas, which can be serialized to formats understood by other tools so
+/// the canvas can be used as a sprite atlas. An atlas is created by a merger's `get_atlas` method, with every image named
+/// after its index, and can be renamed with `with_names`.
+///
+/// # Example
+/// ```
+/// use image_merger::{KnownSizeMerger, Merger, Image, Rgb};
+///
+/// let mut merger: KnownSizeMerger<Rgb<u8>, _> = KnownSizeMerger::new((100, 100), 2, 2, None);
+/// let image = Image::new(100, 100);
+/// merger.bulk_push(&[&image, &image]);
+///
+/// let atlas = merger.get_atlas().with_names(["grass", "stone"]);
+/// assert_eq!(atlas.get("stone").unwrap().placement.rect.x, 100);
+///
+/// let json = atlas.to_texture_packer_hash("atlas.png");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Atlas {
+    /// The width of the canvas.
+    pub width: u32,
+    /// The height of the canvas.
+    pub height: u32,
+    /// Every image on the canvas, in the order they were pushed.
+    pub entries: Vec<AtlasEntry>,
+}
+
+impl Atlas {
+    /// Constructs a new Atlas, naming every image after its index.
+    /// # Arguments
+    /// * `dimensions` - The dimensions, (width, height), of the canvas.
+    /// * `placements` - Where every image was placed on the canvas.
+    pub fn new(dimensions: (u32, u32), placements: &[Placement]) -> Self {
+        Self {
+            width: dimensions.0,
+            height: dimensions.1,
+            entries: placements
+                .iter()
+                .enumerate()
+                .map(|(index, placement)| AtlasEntry {
+                    name: index.to_string(),
+                    placement: *placement,
+                })
+                .collect(),
+        }
+    }
+
+    /// Renames the images on the atlas, in order. If fewer names than images are given, the remaining images keep their
+    /// current names; extra names are ignored.
+    /// # Arguments
+    /// * `names` - The new names of the images, usually the file names they were loaded from.
+    pub fn with_names<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        for (entry, name) in self.entries.iter_mut().zip(names) {
+            entry.name = name.into();
+        }
+
+        self
+    }
+
+    /// Returns the first image with the given name, if there is one.
+    pub fn get(&self, name: &str) -> Option<&AtlasEntry> {
+        self.entries.iter().find(|entry| entry.name == name)
+    }
+
+    /// Serializes the atlas to this crate's own JSON format:
+    /// `{"width", "height", "images": [{"name", "x", "y", "width", "height", "rotated"}]}`. The `width` and `height` of
+    /// each image are the dimensions of the area it covers on the canvas, so they are swapped for rotated images.
+    pub fn to_json(&self) -> String {
+        let images: Vec<Value> = self
+            .entries
+            .iter()
+            .map(|entry| {
+                let rect = entry.placement.rect;
+                json!({
+                    "name": entry.name,
+                    "x": rect.x,
+                    "y": rect.y,
+                    "width": rect.width,
+                    "height": rect.height,
+                    "rotated": entry.placement.rotated,
+                })
+            })
+            .collect();
+
+        let atlas = json!({
+            "width": self.width,
+            "height": self.height,
+            "images": images,
+        });
+
+        to_pretty_string(&atlas)
+    }
+
+    /// Parses an atlas from JSON, in any of the formats this crate serializes to: its own format, or the TexturePacker
+    /// JSON Hash and Array formats.
+    /// # Arguments
+    /// * `json` - The contents of the atlas placement file.
+    /// # Errors
+    /// * [MergeError::InvalidAtlas](crate::MergeError::InvalidAtlas) - If the JSON is malformed or is not an atlas.
+    pub fn from_json(json: &str) -> Result<Self, MergeError> {
+        let value: Value =
+            serde_json::from_str(json).map_err(|err| MergeError::InvalidAtlas(err.to_string()))?;
+
+        if let Some(images) = value.get("images").and_then(Value::as_array) {
+            let entries = images
+                .iter()
+                .map(|image| {
+                    let rect = Rect {
+                        x: field(image, "x")?,
+                        y: field(image, "y")?,
+                        width: field(image, "width")?,
+                        height: field(image, "height")?,
+                    };
+
+                    Ok(AtlasEntry {
+                        name: name(image, "name")?,
+                        placement: Placement {
+                            rect,
+                            rotated: image
+                                .get("rotated")
+                                .and_then(Value::as_bool)
+                                .unwrap_or(false),
+                        },
+                    })
+                })
+                .collect::<Result<_, MergeError>>()?;
+
+            return Ok(Self {
+                width: field(&value, "width")?,
+                height: field(&value, "height")?,
+                entries,
+            });
+        }
+
+        let size = value
+            .get("meta")
+            .and_then(|meta| meta.get("size"))
+            .ok_or_else(|| {
+                MergeError::InvalidAtlas("missing `images` or `meta.size`".to_string())
+            })?;
+
+        let entries = match value.get("frames") {
+            Some(Value::Object(frames)) => frames
+                .iter()
+                .map(|(name, frame)| texture_packer_entry(name.clone(), frame))
+                .collect::<Result<_, MergeError>>()?,
+            Some(Value::Array(frames)) => frames
+                .iter()
+                .map(|frame| texture_packer_entry(name(frame, "filename")?, frame))
+                .collect::<Result<_, MergeError>>()?,
+            _ => return Err(MergeError::InvalidAtlas("missing `frames`".to_string())),
+        };
+
+        Ok(Self {
+            width: field(size, "w")?,
+            height: field(size, "h")?,
+            entries,
+        })
+    }
+
+    /// Serializes the atlas to the TexturePacker JSON (Hash) format, where frames are keyed by name.
+    /// # Arguments
+    /// * `image` - The file name of the canvas, as written to the `meta.image` field.
+    pub fn to_texture_packer_hash(&self, image: &str) -> String {
+        let frames: Map<String, Value> = self
+            .entries
+            .iter()
+            .map(|entry| (entry.name.clone(), texture_packer_frame(entry)))
+            .collect();
+
+        to_pretty_string(&json!({
+            "frames": frames,
+            "meta": self.texture_packer_meta(image),
+        }))
+    }
+
+    /// Serializes the atlas to the TexturePacker JSON (Array) format, where frames are listed in order with a `filename`.
+    /// # Arguments
+    /// * `image` - The file name of the canvas, as written to the `meta.image` field.
+    pub fn to_texture_packer_array(&self, image: &str) -> String {
+        let frames: Vec<Value> = self
+            .entries
+            .iter()
+            .map(|entry| {
+                let mut frame = texture_packer_frame(entry);
+                if let Value::Object(fields) = &mut frame {
+                    fields.insert("filename".to_string(), json!(entry.name));
+                }
+                frame
+            })
+            .collect();
+
+        to_pretty_string(&json!({
+            "frames": frames,
+            "meta": self.texture_packer_meta(image),
+        }))
+    }
+
+    /// Serializes the atlas to a CSS sprite stylesheet. Every image gets a class named `{class_prefix}-{name}`, with any
+    /// character that is not allowed in a class name replaced by `-`, and all classes share the canvas as their
+    /// background. Rotated images cannot be displayed by CSS sprites, their class covers the rotated area of the canvas.
+    /// # Arguments
+    /// * `image_url` - The URL of the canvas, used in `background-image`. It is quoted and escaped, so it may hold any
+    ///   character.
+    /// * `class_prefix` - The prefix of every class name, and the name of the class shared by every image.
+    pub fn to_css(&self, image_url: &str, class_prefix: &str) -> String {
+        let prefix = css_identifier(class_prefix);
+
+        let mut css = String::new();
+        let _ = writeln!(
+            css,
+            ".{} {{\n  background-image: url({});\n  background-repeat: no-repeat;\n  display: inline-block;\n}}",
+            prefix,
+            css_string(image_url)
+        );
+
+        for entry in &self.entries {
+            let rect = entry.placement.rect;
+            let _ = writeln!(
+                css,
+                "\n.{}-{} {{\n  background-position: {} {};\n  width: {}px;\n  height: {}px;\n}}",
+                prefix,
+                css_identifier(&entry.name),
+                css_offset(rect.x),
+                css_offset(rect.y),
+                rect.width,
+                rect.height
+            );
+        }
+
+        css
+    }
+
+    fn texture_packer_meta(&self, image: &str) -> Value {
+        json!({
+            "app": env!("CARGO_PKG_REPOSITORY"),
+            "version": env!("CARGO_PKG_VERSION"),
+            "image": image,
+            "format": "RGBA8888",
+            "size": { "w": self.width, "h": self.height },
+            "scale": "1",
+        })
+    }
+}
+
+/// Builds a TexturePacker frame. TexturePacker stores the unrotated dimensions of rotated frames.
+fn texture_packer_frame(entry: &AtlasEntry) -> Value {
+    let rect = entry.placement.rect;
+    let (width, height) = if entry.placement.rotated {
+        (rect.height, rect.width)
+    } else {
+        (rect.width, rect.height)
+    };
+
+    json!({
+        "frame": { "x": rect.x, "y": rect.y, "w": width, "h": height },
+        "rotated": entry.placement.rotated,
+        "trimmed": false,
+        "spriteSourceSize": { "x": 0, "y": 0, "w": width, "h": height },
+        "sourceSize": { "w": width, "h": height },
+    })
+}
+
+/// Parses a TexturePacker frame, the inverse of `texture_packer_frame`.
+fn texture_packer_entry(name: String, frame: &Value) -> Result<AtlasEntry, MergeError> {
+    let area = frame
+        .get("frame")
+        .ok_or_else(|| MergeError::InvalidAtlas(format!("frame `{}` is missing `frame`", name)))?;
+    let rotated = frame
+        .get("rotated")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+
+    let (width, height) = (field(area, "w")?, field(area, "h")?);
+    let (width, height) = if rotated {
+        (height, width)
+    } else {
+        (width, height)
+    };
+
+    Ok(AtlasEntry {
+        placement: Placement {
+            rect: Rect {
+                x: field(area, "x")?,
+                y: field(area, "y")?,
+                width,
+                height,
+            },
+            rotated,
+        },
+        name,
+    })
+}
+
+/// Reads a `u32` field of a JSON object.
+fn field(value: &Value, key: &str) -> Result<u32, MergeError> {
+    value
+        .get(key)
+        .and_then(Value::as_u64)
+        .and_then(|field| u32::try_from(field).ok())
+        .ok_or_else(|| MergeError::InvalidAtlas(format!("missing or invalid `{}`", key)))
+}
+
+/// Reads a name field of a JSON object, which may be a string or a number.
+fn name(value: &Value, key: &str) -> Result<String, MergeError> {
+    match value.get(key) {
+        Some(Value::String(name)) => Ok(name.clone()),
+        Some(Value::Number(name)) => Ok(name.to_string()),
+        _ => Err(MergeError::InvalidAtlas(format!(
+            "missing or invalid `{}`",
+            key
+        ))),
+    }
+}
+
+fn to_pretty_string(value: &Value) -> String {
+    // Serializing a `Value` cannot fail, its keys are always strings.
+    serde_json::to_string_pretty(value).unwrap_or_default()
+}
+
+/// Replaces every character that is not allowed in a CSS class name with `-`.
+fn css_identifier(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+/// Quotes a value as a CSS string. Quotes and backslashes are escaped, and control characters, such as newlines, which
+/// cannot appear in a string even when escaped by a backslash, are written as hexadecimal escapes.
+fn css_string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('\'');
+    for c in value.chars() {
+        match c {
+            '\'' | '\\' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            c if c.is_control() => {
+                let _ = write!(quoted, "\\{:x} ", c as u32);
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('\'');
+    quoted
+}
+
+fn css_offset(offset: u32) -> String {
+    if offset == 0 {
+        "0".to_string()
+    } else {
+        format!("-{}px", offset)
+    }
+}
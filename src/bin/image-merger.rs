@@ -0,0 +1,540 @@
+//! The `image-merger` command-line tool, for merging folders of images into grids or packed atlases and splitting them
+//! back apart. Built with the `cli` feature: `cargo install image-merger --features cli`.
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use image::{DynamicImage, ImageFormat};
+use image_merger::{
+    load_image,
+    raw::{paste_composite, ImageCell},
+    Alignment, Atlas, BufferedImage, Composite, CompositeOp, FitMode, KnownSizeMerger, Merger,
+    MergerBuilder, PackingAlgorithm, PackingMerger, PackingOptions, Padding, Point, ResizeFilter,
+    Rgba, Spacing, Splitter, StreamOptions, StreamingMerger, TextStyle,
+};
+use rayon::prelude::*;
+use std::{
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+type Result<T> = std::result::Result<T, Box<dyn Error + Send + Sync>>;
+
+/// Merge images into grids or packed sprite atlases, and split them back apart.
+#[derive(Parser)]
+#[command(name = "image-merger", version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Merge images of a uniform size into a grid.
+    Grid(GridArgs),
+    /// Pack images of any size onto a compact canvas.
+    Pack(PackArgs),
+    /// Split a merged canvas back into individual images.
+    Split(SplitArgs),
+}
+
+#[derive(Args)]
+struct OutputArgs {
+    /// The file to write the canvas to.
+    #[arg(short, long)]
+    output: PathBuf,
+
+    /// The format of the output, guessed from the output's extension by default.
+    #[arg(long, value_parser = parse_format)]
+    format: Option<ImageFormat>,
+
+    /// The color of the canvas behind the images, as `RRGGBB` or `RRGGBBAA` hex.
+    #[arg(long, value_parser = parse_color)]
+    background: Option<Rgba<u8>>,
+}
+
+#[derive(Args)]
+struct GridArgs {
+    /// The images to merge: files, directories or glob patterns. Directories and patterns are sorted by path.
+    #[arg(required = true)]
+    inputs: Vec<String>,
+
+    /// The size of a cell, as `WIDTHxHEIGHT`. Defaults to the size of the first image.
+    #[arg(long, value_parser = parse_dimensions)]
+    cell: Option<(u32, u32)>,
+
+    /// The number of images per row. Defaults to a roughly square grid.
+    #[arg(long)]
+    per_row: Option<u32>,
+
+    /// The padding between cells, as `N` or `X,Y`.
+    #[arg(long, value_parser = parse_padding)]
+    padding: Option<Padding>,
+
+    /// The filter used to resize images that do not match the cell size.
+    #[arg(long, value_enum, default_value_t = Filter::Lanczos3)]
+    filter: Filter,
+
+    /// How images that do not match the cell size are fitted into it.
+    #[arg(long, value_enum, default_value_t = Fit::Stretch)]
+    fit: Fit,
+
+    /// Label every cell with its file name or its index, in a band below it.
+    #[arg(long, value_enum)]
+    caption: Option<Caption>,
+
+    /// A title drawn above the grid.
+    #[arg(long)]
+    title: Option<String>,
+
+    /// The color of the captions and the title, as `RRGGBB` or `RRGGBBAA` hex.
+    #[arg(long, value_parser = parse_color, default_value = "000000")]
+    text_color: Rgba<u8>,
+
+    /// How many times larger than its 8x8 pixels the font of the captions and the title is drawn.
+    #[arg(long, default_value_t = 1)]
+    text_scale: u32,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+#[derive(Args)]
+struct PackArgs {
+    /// The images to pack: files, directories or glob patterns. Directories and patterns are sorted by path.
+    #[arg(required = true)]
+    inputs: Vec<String>,
+
+    /// The packing algorithm.
+    #[arg(long, value_enum, default_value_t = Algorithm::MaxRects)]
+    algorithm: Algorithm,
+
+    /// The padding between images, as `N` or `X,Y`.
+    #[arg(long, value_parser = parse_padding)]
+    padding: Option<Padding>,
+
+    /// Allow images to be rotated by 90 degrees to pack them tighter.
+    #[arg(long)]
+    rotate: bool,
+
+    /// Write the placement of every image to this file.
+    #[arg(long)]
+    atlas: Option<PathBuf>,
+
+    /// The format of the atlas file.
+    #[arg(long, value_enum, default_value_t = AtlasFormat::Json)]
+    atlas_format: AtlasFormat,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+#[derive(Args)]
+struct SplitArgs {
+    /// The canvas to split.
+    input: PathBuf,
+
+    /// The directory to write the images to.
+    #[arg(short, long)]
+    output: PathBuf,
+
+    /// The format of the images, PNG by default.
+    #[arg(long, value_parser = parse_format)]
+    format: Option<ImageFormat>,
+
+    /// The size of a cell, as `WIDTHxHEIGHT`. Requires `--per-row` and `--count`.
+    #[arg(long, value_parser = parse_dimensions, requires_all = ["per_row", "count"], conflicts_with = "atlas")]
+    cell: Option<(u32, u32)>,
+
+    /// The number of images per row.
+    #[arg(long, requires = "cell")]
+    per_row: Option<u32>,
+
+    /// The number of images on the canvas.
+    #[arg(long, requires = "cell")]
+    count: Option<u32>,
+
+    /// The padding between cells, as `N` or `X,Y`.
+    #[arg(long, value_parser = parse_padding, requires = "cell")]
+    padding: Option<Padding>,
+
+    /// An atlas file, in any of the formats written by `pack`, describing where the images are.
+    #[arg(long)]
+    atlas: Option<PathBuf>,
+
+    /// The color of the padding used to detect the grid when neither `--cell` nor `--atlas` is given, as `RRGGBB` or
+    /// `RRGGBBAA` hex. Defaults to the color of the first uniform column or row.
+    #[arg(long, value_parser = parse_color)]
+    background: Option<Rgba<u8>>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Filter {
+    Nearest,
+    Bilinear,
+    CatmullRom,
+    Mitchell,
+    Lanczos3,
+    Box,
+}
+
+impl From<Filter> for ResizeFilter {
+    fn from(filter: Filter) -> Self {
+        match filter {
+            Filter::Nearest => ResizeFilter::Nearest,
+            Filter::Bilinear => ResizeFilter::Bilinear,
+            Filter::CatmullRom => ResizeFilter::CatmullRom,
+            Filter::Mitchell => ResizeFilter::Mitchell,
+            Filter::Lanczos3 => ResizeFilter::Lanczos3,
+            Filter::Box => ResizeFilter::Box,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Fit {
+    /// Stretch images to the cell size.
+    Stretch,
+    /// Scale images to fit inside the cell, filling the rest with the background.
+    Contain,
+    /// Scale images to cover the cell, cropping what does not fit.
+    Cover,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Caption {
+    /// The file name of the image.
+    Name,
+    /// The index of the image, starting at 0.
+    Index,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Algorithm {
+    MaxRects,
+    Skyline,
+    Guillotine,
+}
+
+impl From<Algorithm> for PackingAlgorithm {
+    fn from(algorithm: Algorithm) -> Self {
+        match algorithm {
+            Algorithm::MaxRects => PackingAlgorithm::MaxRects,
+            Algorithm::Skyline => PackingAlgorithm::Skyline,
+            Algorithm::Guillotine => PackingAlgorithm::Guillotine,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum AtlasFormat {
+    /// This crate's own JSON format.
+    Json,
+    /// TexturePacker JSON (Hash).
+    TexturePackerHash,
+    /// TexturePacker JSON (Array).
+    TexturePackerArray,
+    /// A CSS sprite stylesheet.
+    Css,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = match cli.command {
+        Command::Grid(args) => grid(args),
+        Command::Pack(args) => pack(args),
+        Command::Split(args) => split(args),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn grid(args: GridArgs) -> Result<()> {
+    let paths = expand_inputs(&args.inputs)?;
+
+    // Stream the images onto the canvas so only a few of them are decoded at once.
+    let cell = match args.cell {
+        Some(cell) => cell,
+        None => image::image_dimensions(&paths[0])?,
+    };
+    let total = paths.len() as u32;
+    let per_row = args
+        .per_row
+        .unwrap_or_else(|| (total as f64).sqrt().ceil() as u32);
+
+    let style = TextStyle::new(args.text_color).with_scale(args.text_scale);
+    let mut builder = KnownSizeMerger::builder(cell)
+        .with_images_per_row(per_row)
+        .with_total_images(total)
+        .with_spacing(Spacing::from(args.padding));
+    if args.caption.is_some() {
+        builder = builder.with_captions(style.clone());
+    }
+    if let Some(title) = &args.title {
+        builder = builder.with_title(title.as_str(), style);
+    }
+
+    let mut merger: KnownSizeMerger<Rgba<u8>, _> = builder.try_build()?;
+    merger.set_resize_filter(args.filter.into());
+    merger.set_fit_mode(match args.fit {
+        Fit::Stretch => FitMode::Stretch,
+        Fit::Contain => FitMode::Contain {
+            fill: args.output.background.unwrap_or(Rgba([0, 0, 0, 0])),
+            alignment: Alignment::Center,
+        },
+        Fit::Cover => FitMode::Cover {
+            gravity: Alignment::Center,
+        },
+    });
+
+    let captions: Option<Vec<String>> = args.caption.map(|caption| match caption {
+        Caption::Name => paths
+            .iter()
+            .map(|path| {
+                path.file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect(),
+        Caption::Index => (0..total).map(|index| index.to_string()).collect(),
+    });
+
+    merger.try_push_stream(paths, &StreamOptions::default())?;
+    for (index, caption) in captions.iter().flatten().enumerate() {
+        merger.try_set_caption(index as u32, caption)?;
+    }
+
+    save(merger.into_canvas(), &args.output)
+}
+
+fn pack(args: PackArgs) -> Result<()> {
+    let paths = expand_inputs(&args.inputs)?;
+    let images = load_images(&paths)?;
+    let images: Vec<&BufferedImage<Rgba<u8>>> = images.iter().collect();
+
+    let merger = PackingMerger::try_pack(
+        &images,
+        PackingOptions {
+            algorithm: args.algorithm.into(),
+            padding: args.padding,
+            allow_rotation: args.rotate,
+        },
+    )?;
+
+    if let Some(path) = &args.atlas {
+        let names = paths
+            .iter()
+            .map(|path| path.file_name().unwrap_or_default().to_string_lossy());
+        let atlas = merger.get_atlas().with_names(names);
+        let image = args
+            .output
+            .output
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy();
+
+        let contents = match args.atlas_format {
+            AtlasFormat::Json => atlas.to_json(),
+            AtlasFormat::TexturePackerHash => atlas.to_texture_packer_hash(&image),
+            AtlasFormat::TexturePackerArray => atlas.to_texture_packer_array(&image),
+            AtlasFormat::Css => atlas.to_css(&image, "sprite"),
+        };
+        fs::write(path, contents).map_err(|err| format!("{}: {}", path.display(), err))?;
+    }
+
+    save(merger.into_canvas(), &args.output)
+}
+
+fn split(args: SplitArgs) -> Result<()> {
+    let canvas = load_image(&args.input)?;
+
+    let (splitter, names): (Splitter, Vec<String>) = if let Some(path) = &args.atlas {
+        let json =
+            fs::read_to_string(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+        let atlas = Atlas::from_json(&json)?;
+        let names = atlas
+            .entries
+            .iter()
+            .map(|entry| {
+                // Atlas names are usually the file names the images were packed from.
+                let name = Path::new(&entry.name);
+                name.file_stem()
+                    .unwrap_or(name.as_os_str())
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect();
+
+        (Splitter::from_atlas(&atlas), names)
+    } else {
+        let splitter = match (args.cell, args.per_row, args.count) {
+            (Some(cell), Some(per_row), Some(count)) => {
+                Splitter::try_new(cell, per_row, count, args.padding)?
+            }
+            _ => Splitter::detect(&canvas, args.background)?,
+        };
+        let names = (0..splitter.get_num_images())
+            .map(|index| index.to_string())
+            .collect();
+
+        (splitter, names)
+    };
+
+    let format = args.format.unwrap_or(ImageFormat::Png);
+    let extension = format.extensions_str().first().copied().unwrap_or("png");
+    fs::create_dir_all(&args.output)
+        .map_err(|err| format!("{}: {}", args.output.display(), err))?;
+
+    splitter
+        .try_split(&canvas)?
+        .into_par_iter()
+        .zip(names)
+        .try_for_each(|(image, name): (BufferedImage<Rgba<u8>>, String)| {
+            let path = args.output.join(format!("{}.{}", name, extension));
+            write_image(image, &path, format)
+        })
+}
+
+/// Expands every input into the image files it refers to. Directories and glob patterns are sorted by path.
+fn expand_inputs(inputs: &[String]) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for input in inputs {
+        let path = Path::new(input);
+        let mut expanded: Vec<PathBuf> = if path.is_dir() {
+            fs::read_dir(path)
+                .map_err(|err| format!("{}: {}", input, err))?
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.is_file() && ImageFormat::from_path(path).is_ok())
+                .collect()
+        } else if path.exists() {
+            vec![path.to_path_buf()]
+        } else {
+            glob::glob(input)
+                .map_err(|err| format!("{}: {}", input, err))?
+                .filter_map(|path| path.ok())
+                .filter(|path| path.is_file())
+                .collect()
+        };
+
+        if expanded.is_empty() {
+            return Err(format!("{}: no images found", input).into());
+        }
+
+        expanded.sort();
+        paths.extend(expanded);
+    }
+
+    Ok(paths)
+}
+
+/// Decodes every image in parallel.
+fn load_images(paths: &[PathBuf]) -> Result<Vec<BufferedImage<Rgba<u8>>>> {
+    Ok(paths
+        .par_iter()
+        .map(load_image)
+        .collect::<std::result::Result<_, _>>()?)
+}
+
+/// Fills the background of the canvas, if asked to, and writes it to the output.
+fn save(canvas: BufferedImage<Rgba<u8>>, output: &OutputArgs) -> Result<()> {
+    let canvas = match output.background {
+        Some(background) => {
+            let (width, height) = canvas.dimensions();
+            let filled = ImageCell::new(BufferedImage::new_from_pixel(width, height, background));
+            // Safety: the filled canvas was just created, and nothing else reads or writes it.
+            unsafe {
+                paste_composite(
+                    &filled,
+                    &canvas,
+                    Point { x: 0, y: 0 },
+                    &Composite::new(CompositeOp::SourceOver),
+                )
+            };
+            filled.into_inner()
+        }
+        None => canvas,
+    };
+
+    let format = match output.format {
+        Some(format) => format,
+        None => ImageFormat::from_path(&output.output)
+            .map_err(|err| format!("{}: {}", output.output.display(), err))?,
+    };
+
+    write_image(canvas, &output.output, format)
+}
+
+fn write_image(image: BufferedImage<Rgba<u8>>, path: &Path, format: ImageFormat) -> Result<()> {
+    let image = DynamicImage::ImageRgba8(image.into_buffer());
+
+    // Not every format can store an alpha channel.
+    let image = match format {
+        ImageFormat::Jpeg | ImageFormat::Pnm | ImageFormat::Farbfeld | ImageFormat::Hdr => {
+            DynamicImage::ImageRgb8(image.into_rgb8())
+        }
+        _ => image,
+    };
+
+    image
+        .save_with_format(path, format)
+        .map_err(|err| format!("{}: {}", path.display(), err).into())
+}
+
+fn parse_dimensions(value: &str) -> std::result::Result<(u32, u32), String> {
+    let (width, height) = value
+        .split_once(['x', 'X'])
+        .ok_or_else(|| format!("expected WIDTHxHEIGHT, got `{}`", value))?;
+
+    Ok((
+        width.trim().parse().map_err(|err| format!("{}", err))?,
+        height.trim().parse().map_err(|err| format!("{}", err))?,
+    ))
+}
+
+fn parse_padding(value: &str) -> std::result::Result<Padding, String> {
+    let parse = |value: &str| {
+        value
+            .trim()
+            .parse::<u32>()
+            .map_err(|err| format!("{}", err))
+    };
+
+    match value.split_once(',') {
+        Some((x, y)) => Ok(Point {
+            x: parse(x)?,
+            y: parse(y)?,
+        }),
+        None => {
+            let padding = parse(value)?;
+            Ok(Point {
+                x: padding,
+                y: padding,
+            })
+        }
+    }
+}
+
+fn parse_color(value: &str) -> std::result::Result<Rgba<u8>, String> {
+    let hex = value.trim_start_matches('#');
+    if !(hex.len() == 6 || hex.len() == 8) || !hex.is_ascii() {
+        return Err(format!("expected RRGGBB or RRGGBBAA, got `{}`", value));
+    }
+
+    let channel = |index: usize| {
+        hex.get(index..index + 2)
+            .map_or(Ok(255), |channel| u8::from_str_radix(channel, 16))
+            .map_err(|err| format!("{}", err))
+    };
+
+    Ok(Rgba([channel(0)?, channel(2)?, channel(4)?, channel(6)?]))
+}
+
+fn parse_format(value: &str) -> std::result::Result<ImageFormat, String> {
+    ImageFormat::from_extension(value).ok_or_else(|| format!("unknown image format `{}`", value))
+}
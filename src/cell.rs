@@ -1,16 +1,57 @@
 use super::core::Image;
-use image::Pixel;
+use crate::{
+    claims::{Batch, Claim},
+    merger::Point,
+    merger::Rect,
+    MergeError,
+};
+use image::{ImageBuffer, Pixel};
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
 use std::{
-    cell::UnsafeCell,
-    marker::{Send, Sync},
-    ops::Deref,
+    marker::{PhantomData, Send, Sync},
+    mem::ManuallyDrop,
+    ops::{Deref, DerefMut},
+    ptr::NonNull,
 };
 
-/// A struct that allows multiple mutable references to an underlying image's data buffer. This is an
-/// unsafe struct and should only be used when no two items are trying to change the same place in the underlying
-/// image's data buffer. This struct is used to allow multiple threads to write to the same image at the same time.
+/// A struct that allows multiple threads to write to an underlying image's data buffer at the same time.
+///
+/// The safe way to write to the cell from many threads is to split it into non-overlapping [RegionMut](RegionMut)s with
+/// `regions_mut`, which checks that the regions lie within the image and do not overlap when they are created. The
+/// regions can then be sent to other threads and written to without any `unsafe` code.
+///
+/// The cell also hands out single pixels with the unsafe `request_handout`, and the unsafe paste functions of the
+/// [raw](crate::raw) module write to it directly, which is what the crate's own parallel pastes use. Every write goes
+/// through a raw pointer to the underlying subpixel buffer, so no two threads ever hold a mutable reference to the
+/// image at the same time, but it is up to the caller to keep those writes from overlapping.
+///
+/// # Example
+/// ```
+/// use image_merger::{raw::ImageCell, Image, Rect, Rgb};
+/// use rayon::prelude::*;
+///
+/// let mut cell = ImageCell::new(Image::<Rgb<u8>, _>::new(100, 100));
+/// let rects: Vec<Rect> = (0..4)
+///     .map(|index| Rect { x: index * 25, y: 0, width: 25, height: 100 })
+///     .collect();
+///
+/// // Fill every quarter of the image with its own color, in parallel.
+/// let mut regions = cell.regions_mut(&rects).unwrap();
+/// regions.par_iter_mut().enumerate().for_each(|(index, region)| {
+///     region.fill(Rgb([index as u8 * 60, 0, 0]));
+/// });
+///
+/// let image = cell.into_inner();
+/// assert_eq!(image.get_pixel(80, 50), &Rgb([180, 0, 0]));
+/// ```
 pub struct ImageCell<P: Pixel, U: image::GenericImage<Pixel = P>> {
-    underlying: UnsafeCell<Image<P, U>>,
+    image: NonNull<Image<P, U>>, // The image, boxed so the cell can move without invalidating `data`.
+    data: *mut P::Subpixel, // The subpixel buffer of the image, which every write goes through.
+    width: u32,
+    height: u32,
+    #[cfg(feature = "debug-checks")]
+    claims: std::sync::Mutex<crate::claims::Claims>, // Which pixels are being written to.
+    _owns: PhantomData<Image<P, U>>,
 }
 
 /// Represents a handout of an image cell. This struct is used to write to the image cell's underlying
@@ -19,27 +60,89 @@ pub struct Handout<'a, P: Pixel, U: image::GenericImage<Pixel = P>> {
     ic: &'a ImageCell<P, U>,
     x: u32,
     y: u32,
+    _claim: Claim<'a>,
 }
 
-impl<P: Pixel, U: image::GenericImage<Pixel = P>> ImageCell<P, U> {
-    pub fn new(image: Image<P, U>) -> Self {
+impl<P, Container> ImageCell<P, ImageBuffer<P, Container>>
+where
+    P: Pixel,
+    Container: DerefMut<Target = [P::Subpixel]>,
+{
+    pub fn new(image: Image<P, ImageBuffer<P, Container>>) -> Self {
+        let (width, height) = image.dimensions();
+        let image = Box::into_raw(Box::new(image));
+
+        // Safety: the box was just allocated, so this is the only reference to the image. The buffer pointer is taken
+        // once, here, so later writes never need a mutable reference to the image.
+        let data = unsafe {
+            let buffer: &mut [P::Subpixel] = &mut *image;
+            buffer.as_mut_ptr()
+        };
+
         Self {
-            underlying: UnsafeCell::new(image),
+            // Safety: `Box::into_raw` never returns null.
+            image: unsafe { NonNull::new_unchecked(image) },
+            data,
+            width,
+            height,
+            #[cfg(feature = "debug-checks")]
+            claims: crate::claims::Claims::new(width, height),
+            _owns: PhantomData,
         }
     }
 
-    /// Returns the underlying image.
-    pub fn into_inner(self) -> Image<P, U> {
-        self.underlying.into_inner()
+    /// Claims an area of the image for a write, which is checked against every other write with the `debug-checks`
+    /// feature. The claim must be held until the write finishes.
+    #[inline(always)]
+    pub(crate) fn claim(&self, rect: Rect, writer: &'static str) -> Claim<'_> {
+        #[cfg(feature = "debug-checks")]
+        return Claim::new(&self.claims, rect, writer);
+
+        #[cfg(not(feature = "debug-checks"))]
+        Claim::new(rect, writer)
     }
 
-    #[allow(clippy::mut_from_ref)]
-    pub(crate) fn get_image_mut(&self) -> &mut Image<P, U> {
-        unsafe { &mut *self.underlying.get() }
+    /// Opens a [Batch](crate::raw::Batch) of writes that may run at the same time, which lasts until the returned
+    /// guard is dropped. With the `debug-checks` feature enabled, every paste and handout made during the batch must
+    /// cover different pixels, or the write that overlaps an earlier one panics with a report of both writes.
+    /// Without the feature, this does nothing.
+    ///
+    /// # Example
+    /// ```
+    /// use image_merger::{raw::{paste, ImageCell}, BufferedImage, Image, Point, Rgb};
+    /// use rayon::prelude::*;
+    ///
+    /// let cell = ImageCell::new(Image::<Rgb<u8>, _>::new(200, 100));
+    /// let image: BufferedImage<Rgb<u8>> = Image::new(100, 100);
+    ///
+    /// let batch = cell.batch();
+    /// // Safety: the two pastes cover different halves of the canvas, and nothing reads it until the batch ends.
+    /// [0, 100].par_iter().for_each(|&x| unsafe { paste(&cell, &image, Point { x, y: 0 }) });
+    /// drop(batch);
+    /// ```
+    pub fn batch(&self) -> Batch<'_> {
+        #[cfg(feature = "debug-checks")]
+        return Batch::new(&self.claims);
+
+        #[cfg(not(feature = "debug-checks"))]
+        Batch::new()
+    }
+
+    /// Returns a pointer to the first subpixel of the underlying image's data buffer. Writes through this pointer must
+    /// not overlap with writes from other threads.
+    pub(crate) fn as_mut_ptr(&self) -> *mut P::Subpixel {
+        self.data
+    }
+
+    /// Returns the offset, in subpixels, of the pixel at the given coordinates.
+    #[inline(always)]
+    fn offset(&self, x: u32, y: u32) -> usize {
+        (y as usize * self.width as usize + x as usize) * <P as Pixel>::CHANNEL_COUNT as usize
     }
 
     /// Requests a handout at the given coordinates of the underlying image. Can be be used to write
     /// to an underlying image buffer across threads without a mutable reference to the underlying image.
+    /// Prefer `regions_mut`, which checks that no two threads write to the same place.
     /// # Safety
     /// This function is unsafe because it does not implement any thread safety via locks or anything else. It is up to the caller to ensure that
     /// no two threads are trying to write to the same place in the underlying image's data buffer.
@@ -59,8 +162,123 @@ impl<P: Pixel, U: image::GenericImage<Pixel = P>> ImageCell<P, U> {
     /// let mut handout = unsafe { cell.request_handout(0, 0) };
     /// handout.put_pixel(Rgb([255, 255, 255]));
     /// ```
-    pub unsafe fn request_handout(&self, x: u32, y: u32) -> Handout<P, U> {
-        Handout { ic: self, x, y }
+    pub unsafe fn request_handout(
+        &self,
+        x: u32,
+        y: u32,
+    ) -> Handout<'_, P, ImageBuffer<P, Container>> {
+        let rect = Rect {
+            x,
+            y,
+            width: 1,
+            height: 1,
+        };
+
+        Handout {
+            ic: self,
+            x,
+            y,
+            _claim: self.claim(rect, "handout"),
+        }
+    }
+
+    /// Borrows a single region of the underlying image for writing. See `regions_mut`.
+    /// # Errors
+    /// * [MergeError::RegionOutOfBounds](crate::MergeError::RegionOutOfBounds) - If the region does not lie within the image.
+    pub fn region_mut(&mut self, rect: Rect) -> Result<RegionMut<'_, P>, MergeError> {
+        self.check_bounds(rect)?;
+
+        Ok(self.region_unchecked(rect))
+    }
+
+    /// Splits the underlying image into non-overlapping regions that can be written to from different threads. The
+    /// regions borrow the cell mutably, so nothing else can touch the image until they are dropped.
+    /// # Arguments
+    /// * `rects` - The areas of the image to borrow. Regions with a zero width or height never overlap anything.
+    /// # Returns
+    /// The regions, in the order of `rects`.
+    /// # Errors
+    /// * [MergeError::RegionOutOfBounds](crate::MergeError::RegionOutOfBounds) - If a region does not lie within the image.
+    /// * [MergeError::RegionOverlap](crate::MergeError::RegionOverlap) - If two regions overlap.
+    pub fn regions_mut(&mut self, rects: &[Rect]) -> Result<Vec<RegionMut<'_, P>>, MergeError> {
+        for rect in rects {
+            self.check_bounds(*rect)?;
+        }
+
+        // Sweep the regions from left to right, only comparing regions whose horizontal spans overlap.
+        let mut order: Vec<usize> = (0..rects.len())
+            .filter(|&index| rects[index].width > 0 && rects[index].height > 0)
+            .collect();
+        order.sort_by_key(|&index| rects[index].x);
+
+        for (position, &index) in order.iter().enumerate() {
+            let rect = rects[index];
+            let right = rect.x as u64 + rect.width as u64;
+
+            for &other_index in &order[position + 1..] {
+                let other = rects[other_index];
+                if other.x as u64 >= right {
+                    break;
+                }
+
+                let overlaps_vertically = (other.y as u64) < rect.y as u64 + rect.height as u64
+                    && (rect.y as u64) < other.y as u64 + other.height as u64;
+                if overlaps_vertically {
+                    let (first, second) = if index < other_index {
+                        (rect, other)
+                    } else {
+                        (other, rect)
+                    };
+                    return Err(MergeError::RegionOverlap { first, second });
+                }
+            }
+        }
+
+        Ok(rects
+            .iter()
+            .map(|rect| self.region_unchecked(*rect))
+            .collect())
+    }
+
+    fn check_bounds(&self, rect: Rect) -> Result<(), MergeError> {
+        let inside = rect.x as u64 + rect.width as u64 <= self.width as u64
+            && rect.y as u64 + rect.height as u64 <= self.height as u64;
+
+        if inside {
+            Ok(())
+        } else {
+            Err(MergeError::RegionOutOfBounds {
+                region: rect,
+                dimensions: (self.width, self.height),
+            })
+        }
+    }
+
+    /// Creates a region without checking it. The region must lie within the image.
+    fn region_unchecked(&self, rect: Rect) -> RegionMut<'_, P> {
+        let data = if rect.width == 0 || rect.height == 0 {
+            self.data
+        } else {
+            // Safety: the region lies within the image, so its first pixel does too.
+            unsafe { self.data.add(self.offset(rect.x, rect.y)) }
+        };
+
+        RegionMut {
+            data,
+            rect,
+            stride: self.width as usize * <P as Pixel>::CHANNEL_COUNT as usize,
+            _borrow: PhantomData,
+        }
+    }
+}
+
+impl<P: Pixel, U: image::GenericImage<Pixel = P>> ImageCell<P, U> {
+    /// Returns the underlying image.
+    pub fn into_inner(self) -> Image<P, U> {
+        let cell = ManuallyDrop::new(self);
+
+        // Safety: the cell owns the box, and will not free it since it is never dropped.
+        *unsafe { Box::from_raw(cell.image.as_ptr()) }
     }
 }
 
@@ -68,20 +286,64 @@ impl<P: Pixel, U: image::GenericImage<Pixel = P>> Deref for ImageCell<P, U> {
     type Target = Image<P, U>;
 
     fn deref(&self) -> &Self::Target {
-        unsafe { &*self.underlying.get() }
+        // Safety: the image lives as long as the cell, and is only ever written through `data`.
+        unsafe { self.image.as_ref() }
+    }
+}
+
+impl<P: Pixel, U: image::GenericImage<Pixel = P>> Drop for ImageCell<P, U> {
+    fn drop(&mut self) {
+        // Safety: the cell owns the box.
+        unsafe { drop(Box::from_raw(self.image.as_ptr())) }
     }
 }
 
-unsafe impl<P: Pixel, U: image::GenericImage<Pixel = P>> Sync for ImageCell<P, U> {}
-unsafe impl<P: Pixel, U: image::GenericImage<Pixel = P>> Send for ImageCell<P, U> {}
+// A shared cell can be written to from every thread that holds it, so its subpixels must be sendable and shareable.
+unsafe impl<P, U> Sync for ImageCell<P, U>
+where
+    P: Pixel,
+    <P as Pixel>::Subpixel: Send + Sync,
+    U: image::GenericImage<Pixel = P> + Sync,
+{
+}
+unsafe impl<P, U> Send for ImageCell<P, U>
+where
+    P: Pixel,
+    U: image::GenericImage<Pixel = P> + Send,
+{
+}
+
+impl<'a, P, Container> Handout<'a, P, ImageBuffer<P, Container>>
+where
+    P: Pixel,
+    Container: DerefMut<Target = [P::Subpixel]>,
+{
+    /// Returns the pixel at the handout's coordinates.
+    /// # Panics
+    /// This function will panic if the coordinates lie outside of the image.
+    pub fn get_pixel(&self) -> P {
+        self.check_bounds();
+        unsafe { self.unsafe_get_pixel() }
+    }
+
+    /// Same as `get_pixel` but does not check bounds.
+    /// # Safety
+    /// This function is unsafe because it does not check bounds when reading the pixel.
+    pub unsafe fn unsafe_get_pixel(&self) -> P {
+        let channels = <P as Pixel>::CHANNEL_COUNT as usize;
+        let data = self.ic.data.add(self.ic.offset(self.x, self.y));
+
+        *<P as Pixel>::from_slice(std::slice::from_raw_parts(data, channels))
+    }
 
-impl<'a, P: Pixel, U: image::GenericImage<Pixel = P>> Handout<'a, P, U> {
     /// Puts a pixel at the handout's coordinates.
     /// # Arguments
     /// * `pixel` - The pixel to place.
+    /// # Panics
+    /// This function will panic if the coordinates lie outside of the image.
     pub fn put_pixel(&mut self, pixel: P) {
-        let image = self.ic.get_image_mut();
-        image.put_pixel(self.x, self.y, pixel);
+        self.check_bounds();
+        unsafe { self.unsafe_put_pixel(pixel) }
     }
 
     /// Same as `put_pixel` but does not check bounds.
@@ -90,7 +352,178 @@ impl<'a, P: Pixel, U: image::GenericImage<Pixel = P>> Handout<'a, P, U> {
     /// # Arguments
     /// * `pixel` - The pixel to place.
     pub unsafe fn unsafe_put_pixel(&mut self, pixel: P) {
-        let image = self.ic.get_image_mut();
-        image.unsafe_put_pixel(self.x, self.y, pixel);
+        let channels = pixel.channels();
+        let data = self.ic.data.add(self.ic.offset(self.x, self.y));
+
+        std::ptr::copy_nonoverlapping(channels.as_ptr(), data, channels.len());
+    }
+
+    fn check_bounds(&self) {
+        if self.x >= self.ic.width || self.y >= self.ic.height {
+            panic!(
+                "handout at ({}, {}) lies outside of the {}x{} image",
+                self.x, self.y, self.ic.width, self.ic.height
+            );
+        }
+    }
+}
+
+/// A mutable view of a rectangular region of an [ImageCell](ImageCell), created by `ImageCell::regions_mut`. Regions
+/// handed out together never overlap, so each one can be written to from its own thread. Coordinates are relative to
+/// the top left corner of the region.
+pub struct RegionMut<'a, P: Pixel> {
+    data: *mut P::Subpixel, // The top left subpixel of the region.
+    rect: Rect,             // The area of the image the region covers.
+    stride: usize,          // The number of subpixels in a row of the image.
+    _borrow: PhantomData<&'a mut [P::Subpixel]>,
+}
+
+// A region is an exclusive borrow of its subpixels, so it is sendable and shareable exactly like `&mut [P::Subpixel]`.
+unsafe impl<P> Send for RegionMut<'_, P>
+where
+    P: Pixel,
+    <P as Pixel>::Subpixel: Send,
+{
+}
+unsafe impl<P> Sync for RegionMut<'_, P>
+where
+    P: Pixel,
+    <P as Pixel>::Subpixel: Sync,
+{
+}
+
+impl<P: Pixel> RegionMut<'_, P> {
+    /// Returns the area of the image the region covers.
+    pub fn get_rect(&self) -> Rect {
+        self.rect
+    }
+
+    /// Returns the dimensions, (width, height), of the region.
+    pub fn dimensions(&self) -> (u32, u32) {
+        (self.rect.width, self.rect.height)
+    }
+
+    /// Returns a row of the region's subpixels.
+    /// # Panics
+    /// This function will panic if `y` lies outside of the region.
+    pub fn row(&self, y: u32) -> &[P::Subpixel] {
+        let (data, len) = self.row_parts(y);
+        unsafe { std::slice::from_raw_parts(data, len) }
+    }
+
+    /// Returns a mutable row of the region's subpixels.
+    /// # Panics
+    /// This function will panic if `y` lies outside of the region.
+    pub fn row_mut(&mut self, y: u32) -> &mut [P::Subpixel] {
+        let (data, len) = self.row_parts(y);
+        unsafe { std::slice::from_raw_parts_mut(data, len) }
+    }
+
+    /// Returns the pixel at the given coordinates of the region.
+    /// # Panics
+    /// This function will panic if the coordinates lie outside of the region.
+    pub fn get_pixel(&self, x: u32, y: u32) -> P {
+        let channels = <P as Pixel>::CHANNEL_COUNT as usize;
+        let start = self.column(x) * channels;
+
+        *<P as Pixel>::from_slice(&self.row(y)[start..start + channels])
+    }
+
+    /// Puts a pixel at the given coordinates of the region.
+    /// # Panics
+    /// This function will panic if the coordinates lie outside of the region.
+    pub fn put_pixel(&mut self, x: u32, y: u32, pixel: P) {
+        let channels = <P as Pixel>::CHANNEL_COUNT as usize;
+        let start = self.column(x) * channels;
+
+        self.row_mut(y)[start..start + channels].copy_from_slice(pixel.channels());
+    }
+
+    /// Fills the whole region with a pixel.
+    pub fn fill(&mut self, pixel: P) {
+        for y in 0..self.rect.height {
+            for chunk in self
+                .row_mut(y)
+                .chunks_exact_mut(<P as Pixel>::CHANNEL_COUNT as usize)
+            {
+                chunk.copy_from_slice(pixel.channels());
+            }
+        }
+    }
+
+    /// Fills the region with a pixel computed for every position, in parallel over its rows.
+    /// # Arguments
+    /// * `pixel` - Returns the pixel at the given coordinates of the region.
+    pub fn fill_with<F>(&mut self, pixel: F)
+    where
+        <P as Pixel>::Subpixel: Send + Sync,
+        F: Fn(u32, u32) -> P + Sync,
+    {
+        let channels = <P as Pixel>::CHANNEL_COUNT as usize;
+        let region = &*self;
+
+        (0..self.rect.height).into_par_iter().for_each(|y| {
+            // Safety: the region is borrowed mutably, and no two rows of it overlap.
+            let (data, len) = region.row_parts(y);
+            let row = unsafe { std::slice::from_raw_parts_mut(data, len) };
+
+            for (x, chunk) in row.chunks_exact_mut(channels).enumerate() {
+                chunk.copy_from_slice(pixel(x as u32, y).channels());
+            }
+        });
+    }
+
+    /// Pastes an image onto the region, copying it row by row. Any part of the image that lies outside of the region is
+    /// clipped.
+    /// # Arguments
+    /// * `image` - The image to paste.
+    /// * `loc` - The location to paste the image at, relative to the region.
+    pub fn paste<Container>(&mut self, image: &Image<P, ImageBuffer<P, Container>>, loc: Point)
+    where
+        Container: DerefMut<Target = [P::Subpixel]>,
+    {
+        let channels = <P as Pixel>::CHANNEL_COUNT as usize;
+        let width = image.width().min(self.rect.width.saturating_sub(loc.x)) as usize;
+        let height = image.height().min(self.rect.height.saturating_sub(loc.y));
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        let image_stride = image.width() as usize * channels;
+        let source: &[P::Subpixel] = image;
+        for y in 0..height {
+            let start = y as usize * image_stride;
+            let destination = loc.x as usize * channels;
+
+            self.row_mut(loc.y + y)[destination..destination + width * channels]
+                .copy_from_slice(&source[start..start + width * channels]);
+        }
+    }
+
+    fn column(&self, x: u32) -> usize {
+        if x >= self.rect.width {
+            panic!(
+                "column {} lies outside of the region, which is {} pixels wide",
+                x, self.rect.width
+            );
+        }
+
+        x as usize
+    }
+
+    fn row_parts(&self, y: u32) -> (*mut P::Subpixel, usize) {
+        if y >= self.rect.height {
+            panic!(
+                "row {} lies outside of the region, which is {} pixels high",
+                y, self.rect.height
+            );
+        }
+
+        // Safety: the row lies within the region, which lies within the image.
+        let data = unsafe { self.data.add(y as usize * self.stride) };
+        (
+            data,
+            self.rect.width as usize * <P as Pixel>::CHANNEL_COUNT as usize,
+        )
     }
 }
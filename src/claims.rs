@@ -0,0 +1,259 @@
+use crate::merger::Rect;
+
+use std::marker::PhantomData;
+
+#[cfg(feature = "debug-checks")]
+use std::{
+    sync::{Mutex, MutexGuard},
+    thread::{self, ThreadId},
+};
+
+/// Keeps track of which pixels of a [raw::ImageCell](crate::raw::ImageCell) are being written to, when the `debug-checks`
+/// feature is enabled. Every write claims the area it covers before writing, and a claim that overlaps another claim,
+/// or lies outside of the image, panics with a report of both writes.
+///
+/// Claims are released once their write finishes, unless a [Batch](Batch) is open: the claims made during a batch are
+/// kept until it ends, so writes that may run at the same time are checked against each other even when they happen not
+/// to.
+#[cfg(feature = "debug-checks")]
+pub(crate) struct Claims {
+    width: u32,
+    height: u32,
+    owners: Vec<u32>, // The claim covering every pixel, or 0 for none. Allocated on the first claim.
+    claims: Vec<ClaimInfo>, // Every claim that still covers pixels, claim `id` at index `id - 1`.
+    active: usize,    // The number of claims whose write has not finished.
+    batches: usize,   // The number of open batches.
+}
+
+#[cfg(feature = "debug-checks")]
+struct ClaimInfo {
+    rect: Rect,
+    writer: &'static str, // What is writing, such as `paste`.
+    thread: ThreadId,
+    released: bool,
+}
+
+#[cfg(feature = "debug-checks")]
+impl ClaimInfo {
+    fn describe(&self) -> String {
+        format!(
+            "{} of {}x{} at ({}, {}), on {:?}",
+            self.writer, self.rect.width, self.rect.height, self.rect.x, self.rect.y, self.thread
+        )
+    }
+}
+
+#[cfg(feature = "debug-checks")]
+impl Claims {
+    pub(crate) fn new(width: u32, height: u32) -> Mutex<Self> {
+        Mutex::new(Self {
+            width,
+            height,
+            owners: Vec::new(),
+            claims: Vec::new(),
+            active: 0,
+            batches: 0,
+        })
+    }
+
+    /// Claims an area for a write, returning the id of the claim, or a report of why it cannot be claimed.
+    fn claim(&mut self, rect: Rect, writer: &'static str) -> Result<u32, String> {
+        let info = ClaimInfo {
+            rect,
+            writer,
+            thread: thread::current().id(),
+            released: false,
+        };
+
+        let (right, bottom) = (
+            rect.x as u64 + rect.width as u64,
+            rect.y as u64 + rect.height as u64,
+        );
+        if right > self.width as u64 || bottom > self.height as u64 {
+            return Err(format!(
+                "debug-checks: out of bounds write to a {}x{} image\n  {}, reaches ({}, {})",
+                self.width,
+                self.height,
+                info.describe(),
+                right,
+                bottom
+            ));
+        }
+
+        if rect.width == 0 || rect.height == 0 {
+            return Ok(0);
+        }
+
+        if self.owners.is_empty() {
+            self.owners = vec![0; self.width as usize * self.height as usize];
+        }
+
+        let id = self.claims.len() as u32 + 1;
+        for y in rect.y..rect.y + rect.height {
+            let start = y as usize * self.width as usize + rect.x as usize;
+            let row = &mut self.owners[start..start + rect.width as usize];
+
+            if let Some(x) = row.iter().position(|&owner| owner != 0) {
+                let existing = &self.claims[row[x] as usize - 1];
+                let report = format!(
+                    "debug-checks: overlapping writes to a {}x{} image\n  new:      {}\n  existing: {}, {}\n  first shared pixel: ({}, {})",
+                    self.width,
+                    self.height,
+                    info.describe(),
+                    existing.describe(),
+                    if existing.released {
+                        "finished earlier in the same batch"
+                    } else {
+                        "still in progress"
+                    },
+                    rect.x as usize + x,
+                    y
+                );
+
+                // Undo the rows claimed so far, so the image can still be used after the panic is caught.
+                self.forget_rows(id, rect, y);
+                return Err(report);
+            }
+
+            row.fill(id);
+        }
+
+        self.claims.push(info);
+        self.active += 1;
+        Ok(id)
+    }
+
+    /// Marks a claim's write as finished, forgetting it unless a batch is open.
+    fn release(&mut self, id: u32) {
+        if id == 0 {
+            return;
+        }
+
+        self.claims[id as usize - 1].released = true;
+        self.active -= 1;
+        if self.batches == 0 {
+            let rect = self.claims[id as usize - 1].rect;
+            self.forget_rows(id, rect, rect.y + rect.height);
+            self.compact();
+        }
+    }
+
+    fn end_batch(&mut self) {
+        self.batches -= 1;
+        if self.batches > 0 {
+            return;
+        }
+
+        for index in 0..self.claims.len() {
+            let claim = &self.claims[index];
+            if claim.released {
+                let rect = claim.rect;
+                self.forget_rows(index as u32 + 1, rect, rect.y + rect.height);
+            }
+        }
+        self.compact();
+    }
+
+    /// Clears the pixels of the rows of a claim above `end`.
+    fn forget_rows(&mut self, id: u32, rect: Rect, end: u32) {
+        for y in rect.y..end {
+            let start = y as usize * self.width as usize + rect.x as usize;
+            for owner in &mut self.owners[start..start + rect.width as usize] {
+                if *owner == id {
+                    *owner = 0;
+                }
+            }
+        }
+    }
+
+    /// Drops the claims once none of them cover any pixels, so ids do not grow forever.
+    fn compact(&mut self) {
+        if self.active == 0 {
+            self.claims.clear();
+        }
+    }
+}
+
+/// Locks the claims of an image, even if a write panicked while holding them.
+#[cfg(feature = "debug-checks")]
+fn lock(claims: &Mutex<Claims>) -> MutexGuard<'_, Claims> {
+    claims
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// An area of an image claimed by a write, released when dropped. Does nothing unless the `debug-checks` feature is
+/// enabled.
+pub(crate) struct Claim<'a> {
+    #[cfg(feature = "debug-checks")]
+    claims: &'a Mutex<Claims>,
+    #[cfg(feature = "debug-checks")]
+    id: u32,
+    _image: PhantomData<&'a ()>,
+}
+
+impl<'a> Claim<'a> {
+    #[cfg(feature = "debug-checks")]
+    pub(crate) fn new(claims: &'a Mutex<Claims>, rect: Rect, writer: &'static str) -> Self {
+        let result = lock(claims).claim(rect, writer);
+        match result {
+            Ok(id) => Self {
+                claims,
+                id,
+                _image: PhantomData,
+            },
+            Err(report) => panic!("{}", report),
+        }
+    }
+
+    #[cfg(not(feature = "debug-checks"))]
+    #[inline(always)]
+    pub(crate) fn new(_rect: Rect, _writer: &'static str) -> Self {
+        Self {
+            _image: PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "debug-checks")]
+impl Drop for Claim<'_> {
+    fn drop(&mut self) {
+        lock(self.claims).release(self.id);
+    }
+}
+
+/// Groups the writes to a [raw::ImageCell](crate::raw::ImageCell) that may run at the same time, such as the pastes of
+/// a bulk push, created by `ImageCell::batch`. With the `debug-checks` feature enabled, every write made while a batch
+/// is open is checked against every other write of the batch, not only the ones that happen to run at the same moment,
+/// so overlapping writes are caught deterministically. Without the feature, a batch does nothing.
+pub struct Batch<'a> {
+    #[cfg(feature = "debug-checks")]
+    claims: &'a Mutex<Claims>,
+    _image: PhantomData<&'a ()>,
+}
+
+impl<'a> Batch<'a> {
+    #[cfg(feature = "debug-checks")]
+    pub(crate) fn new(claims: &'a Mutex<Claims>) -> Self {
+        lock(claims).batches += 1;
+        Self {
+            claims,
+            _image: PhantomData,
+        }
+    }
+
+    #[cfg(not(feature = "debug-checks"))]
+    #[inline(always)]
+    pub(crate) fn new() -> Self {
+        Self {
+            _image: PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "debug-checks")]
+impl Drop for Batch<'_> {
+    fn drop(&mut self) {
+        lock(self.claims).end_batch();
+    }
+}
@@ -0,0 +1,369 @@
+use image::{Pixel, Primitive};
+use num_traits::NumCast;
+
+/// The Porter-Duff operator used to combine a pasted image (the source) with the canvas beneath it (the destination).
+/// Pixels without an alpha channel are treated as fully opaque.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CompositeOp {
+    /// Neither the source nor the destination is kept.
+    Clear,
+    /// The source replaces the destination. This is the default, and is the fastest operator as no blending is needed.
+    #[default]
+    Source,
+    /// The destination is kept and the source is discarded.
+    Destination,
+    /// The source is placed over the destination.
+    SourceOver,
+    /// The destination is placed over the source.
+    DestinationOver,
+    /// The part of the source lying inside of the destination replaces the destination.
+    SourceIn,
+    /// The part of the destination lying inside of the source replaces the destination.
+    DestinationIn,
+    /// The part of the source lying outside of the destination replaces the destination.
+    SourceOut,
+    /// The part of the destination lying outside of the source replaces the destination.
+    DestinationOut,
+    /// The part of the source lying inside of the destination is placed over the destination.
+    SourceAtop,
+    /// The part of the destination lying inside of the source is placed over the source.
+    DestinationAtop,
+    /// The parts of the source and destination that do not overlap are kept.
+    Xor,
+    /// The source and destination are added together.
+    Lighter,
+}
+
+/// The blend mode used to mix the colors of a pasted image (the source) with the canvas beneath it (the destination),
+/// before the result is composited with the [CompositeOp](CompositeOp). These match the blend modes found in most image
+/// editors. Where the destination is transparent, the source color is used as is.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BlendMode {
+    /// The source color is used as is. This is the default.
+    #[default]
+    Normal,
+    /// Multiplies the source and destination colors, which always results in a darker color.
+    Multiply,
+    /// Multiplies the complements of the source and destination colors, which always results in a lighter color.
+    Screen,
+    /// Multiplies or screens the colors depending on the destination color, preserving its highlights and shadows.
+    Overlay,
+    /// Keeps the darker of the source and destination colors.
+    Darken,
+    /// Keeps the lighter of the source and destination colors.
+    Lighten,
+    /// Subtracts the darker of the source and destination colors from the lighter one.
+    Difference,
+    /// Adds the source and destination colors together.
+    Additive,
+    /// Darkens or lightens the colors depending on the source color, like shining a diffused spotlight on the destination.
+    SoftLight,
+}
+
+impl BlendMode {
+    /// Blends a single destination and source color channel, both in the range `0.0..=1.0`.
+    fn blend(&self, destination: f32, source: f32) -> f32 {
+        match self {
+            BlendMode::Normal => source,
+            BlendMode::Multiply => destination * source,
+            BlendMode::Screen => destination + source - destination * source,
+            BlendMode::Overlay => {
+                if destination <= 0.5 {
+                    2.0 * destination * source
+                } else {
+                    let destination = 2.0 * destination - 1.0;
+                    destination + source - destination * source
+                }
+            }
+            BlendMode::Darken => destination.min(source),
+            BlendMode::Lighten => destination.max(source),
+            BlendMode::Difference => (destination - source).abs(),
+            BlendMode::Additive => (destination + source).min(1.0),
+            BlendMode::SoftLight => {
+                if source <= 0.5 {
+                    destination - (1.0 - 2.0 * source) * destination * (1.0 - destination)
+                } else {
+                    let d = if destination <= 0.25 {
+                        ((16.0 * destination - 12.0) * destination + 4.0) * destination
+                    } else {
+                        destination.sqrt()
+                    };
+
+                    destination + (2.0 * source - 1.0) * (d - destination)
+                }
+            }
+        }
+    }
+}
+
+/// Describes how a pasted image is composited onto the canvas: the [BlendMode](BlendMode) used to mix colors, the
+/// [CompositeOp](CompositeOp) to use and the opacity the pasted image is multiplied by.
+///
+/// # Example
+/// ```
+/// use image_merger::{Composite, CompositeOp, Rgba};
+///
+/// let composite = Composite::new(CompositeOp::SourceOver).with_opacity(0.5);
+/// let blended = composite.apply(&Rgba([0u8, 0, 255, 255]), &Rgba([255u8, 0, 0, 255]));
+/// assert_eq!(blended, Rgba([128, 0, 128, 255]));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Composite {
+    /// The Porter-Duff operator to use.
+    pub op: CompositeOp,
+    /// The blend mode used to mix colors before compositing.
+    pub blend: BlendMode,
+    /// The opacity of the pasted image, from `0.0` (invisible) to `1.0` (unchanged).
+    pub opacity: f32,
+}
+
+impl Default for Composite {
+    fn default() -> Self {
+        Self {
+            op: CompositeOp::default(),
+            blend: BlendMode::default(),
+            opacity: 1.0,
+        }
+    }
+}
+
+impl Composite {
+    /// Creates a new composite with the given operator, no blending and full opacity.
+    pub fn new(op: CompositeOp) -> Self {
+        Self {
+            op,
+            ..Self::default()
+        }
+    }
+
+    /// Creates a new composite that blends the pasted image over the canvas with the given blend mode, using the
+    /// [SourceOver](CompositeOp::SourceOver) operator.
+    pub fn blend(blend: BlendMode) -> Self {
+        Self {
+            op: CompositeOp::SourceOver,
+            blend,
+            opacity: 1.0,
+        }
+    }
+
+    /// Returns this composite with its blend mode set to the given value.
+    pub fn with_blend_mode(self, blend: BlendMode) -> Self {
+        Self { blend, ..self }
+    }
+
+    /// Returns this composite with its opacity set to the given value, clamped between `0.0` and `1.0`.
+    pub fn with_opacity(self, opacity: f32) -> Self {
+        Self {
+            opacity: opacity.clamp(0.0, 1.0),
+            ..self
+        }
+    }
+
+    /// Returns true if compositing is a plain copy of the source, which allows the caller to skip blending entirely.
+    pub(crate) fn is_copy(&self) -> bool {
+        self.op == CompositeOp::Source && self.blend == BlendMode::Normal && self.opacity >= 1.0
+    }
+
+    /// Returns the Porter-Duff fractions, (Fa, Fb), of the source and destination that make up the result.
+    fn fractions(&self, alpha_source: f32, alpha_destination: f32) -> (f32, f32) {
+        match self.op {
+            CompositeOp::Clear => (0.0, 0.0),
+            CompositeOp::Source => (1.0, 0.0),
+            CompositeOp::Destination => (0.0, 1.0),
+            CompositeOp::SourceOver => (1.0, 1.0 - alpha_source),
+            CompositeOp::DestinationOver => (1.0 - alpha_destination, 1.0),
+            CompositeOp::SourceIn => (alpha_destination, 0.0),
+            CompositeOp::DestinationIn => (0.0, alpha_source),
+            CompositeOp::SourceOut => (1.0 - alpha_destination, 0.0),
+            CompositeOp::DestinationOut => (0.0, 1.0 - alpha_source),
+            CompositeOp::SourceAtop => (alpha_destination, 1.0 - alpha_source),
+            CompositeOp::DestinationAtop => (1.0 - alpha_destination, alpha_source),
+            CompositeOp::Xor => (1.0 - alpha_destination, 1.0 - alpha_source),
+            CompositeOp::Lighter => (1.0, 1.0),
+        }
+    }
+
+    /// Composites the `top` pixel onto the `bottom` pixel, returning the result.
+    /// # Arguments
+    /// * `bottom` - The destination pixel, already on the canvas.
+    /// * `top` - The source pixel, being pasted.
+    pub fn apply<P: Pixel>(&self, bottom: &P, top: &P) -> P {
+        let mut result = *bottom;
+        let channels = result.channels_mut();
+        let top = top.channels();
+
+        let has_alpha = has_alpha::<P>();
+        let color_channels = if has_alpha {
+            channels.len() - 1
+        } else {
+            channels.len()
+        };
+
+        let alpha = |channels: &[P::Subpixel]| {
+            if has_alpha {
+                to_unit(channels[color_channels])
+            } else {
+                1.0
+            }
+        };
+
+        let alpha_source = alpha(top) * self.opacity;
+        let alpha_destination = alpha(channels);
+        let (fa, fb) = self.fractions(alpha_source, alpha_destination);
+
+        let alpha_out = (alpha_source * fa + alpha_destination * fb).min(1.0);
+
+        for (index, channel) in channels.iter_mut().take(color_channels).enumerate() {
+            let destination = to_unit(*channel);
+
+            // Mix in the blended color where the destination is opaque.
+            let source = to_unit(top[index]);
+            let source = (1.0 - alpha_destination) * source
+                + alpha_destination * self.blend.blend(destination, source);
+
+            // Premultiply, combine, then divide the result back out by its alpha.
+            let premultiplied = alpha_source * source * fa + alpha_destination * destination * fb;
+            let value = if alpha_out > 0.0 {
+                premultiplied / alpha_out
+            } else {
+                0.0
+            };
+
+            *channel = from_unit(value);
+        }
+
+        if has_alpha {
+            channels[color_channels] = from_unit(alpha_out);
+        }
+
+        result
+    }
+}
+
+/// Returns true if the last channel of the given pixel type is an alpha channel.
+#[inline(always)]
+pub(crate) fn has_alpha<P: Pixel>() -> bool {
+    P::COLOR_MODEL.ends_with('A')
+}
+
+/// Converts a subpixel into the range `0.0..=1.0`, where `1.0` is the subpixel's maximum value.
+#[inline(always)]
+pub(crate) fn to_unit<S: Primitive>(value: S) -> f32 {
+    let max: f32 = NumCast::from(S::DEFAULT_MAX_VALUE).unwrap();
+    let value: f32 = NumCast::from(value).unwrap();
+
+    value / max
+}
+
+/// Converts a value in the range `0.0..=1.0` back into a subpixel, rounding to the nearest value for integer subpixels.
+#[inline(always)]
+pub(crate) fn from_unit<S: Primitive>(value: f32) -> S {
+    let max: f32 = NumCast::from(S::DEFAULT_MAX_VALUE).unwrap();
+    let scaled = value.clamp(0.0, 1.0) * max;
+
+    // Floating point subpixels have a maximum of 1.0 and should not be rounded.
+    if max > 1.0 {
+        NumCast::from(scaled.round()).unwrap()
+    } else {
+        NumCast::from(scaled).unwrap()
+    }
+}
+
+/// Mixes two pixels channel by channel, where `t` of `0.0` gives `from` and `1.0` gives `to`.
+pub(crate) fn mix<P: Pixel>(from: &P, to: &P, t: f32) -> P {
+    if t <= 0.0 {
+        return *from;
+    }
+    if t >= 1.0 {
+        return *to;
+    }
+
+    from.map2(to, |a, b| {
+        let (a, b) = (to_unit(a), to_unit(b));
+        from_unit(a + (b - a) * t)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{LumaA, Rgb, Rgba};
+
+    #[test]
+    fn test_source_over_transparent_keeps_destination() {
+        let composite = Composite::new(CompositeOp::SourceOver);
+
+        let bottom = Rgba([10u8, 20, 30, 255]);
+        let top = Rgba([255u8, 255, 255, 0]);
+        assert_eq!(composite.apply(&bottom, &top), bottom);
+    }
+
+    #[test]
+    fn test_source_over_half_transparent() {
+        let composite = Composite::new(CompositeOp::SourceOver);
+
+        let bottom = Rgba([0u16, 0, 0, u16::MAX]);
+        let top = Rgba([u16::MAX, 0, 0, u16::MAX / 2 + 1]);
+        let result = composite.apply(&bottom, &top);
+        assert_eq!(result, Rgba([32768, 0, 0, u16::MAX]));
+    }
+
+    #[test]
+    fn test_destination_over() {
+        let composite = Composite::new(CompositeOp::DestinationOver);
+
+        let bottom = LumaA([0.25f32, 0.5]);
+        let top = LumaA([1.0f32, 1.0]);
+        let result = composite.apply(&bottom, &top);
+        assert!((result.0[0] - 0.625).abs() < 1e-6);
+        assert_eq!(result.0[1], 1.0);
+    }
+
+    #[test]
+    fn test_xor_of_opaque_pixels_is_clear() {
+        let composite = Composite::new(CompositeOp::Xor);
+
+        let result = composite.apply(&Rgba([1u8, 2, 3, 255]), &Rgba([4u8, 5, 6, 255]));
+        assert_eq!(result, Rgba([0, 0, 0, 0]));
+    }
+
+    #[test]
+    fn test_blend_modes() {
+        let bottom = Rgb([0.5f32, 0.25, 1.0]);
+        let top = Rgb([0.5f32, 1.0, 0.0]);
+
+        let cases = [
+            (BlendMode::Multiply, [0.25, 0.25, 0.0]),
+            (BlendMode::Screen, [0.75, 1.0, 1.0]),
+            (BlendMode::Overlay, [0.5, 0.5, 1.0]),
+            (BlendMode::Darken, [0.5, 0.25, 0.0]),
+            (BlendMode::Lighten, [0.5, 1.0, 1.0]),
+            (BlendMode::Difference, [0.0, 0.75, 1.0]),
+            (BlendMode::Additive, [1.0, 1.0, 1.0]),
+            (BlendMode::SoftLight, [0.5, 0.5, 1.0]),
+        ];
+
+        for (mode, expected) in cases {
+            let result = Composite::blend(mode).apply(&bottom, &top);
+            for (value, expected) in result.0.iter().zip(expected) {
+                assert!((value - expected).abs() < 1e-6, "{:?}: {:?}", mode, result);
+            }
+        }
+    }
+
+    #[test]
+    fn test_blend_over_transparent_destination() {
+        let composite = Composite::blend(BlendMode::Multiply);
+
+        let result = composite.apply(&Rgba([0u8, 0, 0, 0]), &Rgba([200u8, 100, 50, 255]));
+        assert_eq!(result, Rgba([200, 100, 50, 255]));
+    }
+
+    #[test]
+    fn test_opacity_without_alpha_channel() {
+        let composite = Composite::new(CompositeOp::SourceOver).with_opacity(0.25);
+
+        let result = composite.apply(&Rgb([0u8, 0, 0]), &Rgb([200u8, 100, 0]));
+        assert_eq!(result, Rgb([50, 25, 0]));
+    }
+}
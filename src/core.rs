@@ -2,6 +2,8 @@ use std::ops::{Deref, DerefMut};
 
 use image::{ImageBuffer, ImageFormat, Luma, LumaA, Pixel, Rgb, Rgba};
 
+use crate::MergeError;
+
 /// Represents an image that can be passed to the merger. This is a wrapper around an image crate's GenericImage
 /// and adds some additional functionality for the merger.
 /// # Type Parameters
@@ -24,7 +26,7 @@ pub struct Image<P: Pixel, U: image::GenericImage<Pixel = P>> {
 impl<P: Pixel, U: image::GenericImage<Pixel = P>> Image<P, U> {
     /// Returns the capacity of the underlying image's data buffer.
     pub fn capacity(&self) -> usize {
-        return self.underlying.pixels().count() * <P as Pixel>::CHANNEL_COUNT as usize;
+        self.underlying.pixels().count() * <P as Pixel>::CHANNEL_COUNT as usize
     }
 
     /// Consumes the image and returns the underlying image buffer.
@@ -139,7 +141,8 @@ where
     /// # Returns
     /// An [Image](Image) with the given pixel and buffer type.
     /// # Panics
-    /// This function will panic if the given container cannot be transformed into an image with the given format.
+    /// This function will panic if the given container cannot be transformed into an image with the given format. Use
+    /// [TryFromWithFormat](TryFromWithFormat) to handle the error instead.
     /// # Example
     /// ```no_run
     /// use image_merger::{FromWithFormat, Image, Rgba, BufferedImage};
@@ -151,6 +154,32 @@ where
     fn from_with_format(container: Container, format: ImageFormat) -> Self;
 }
 
+/// The fallible counterpart of [FromWithFormat](FromWithFormat). Allows the creation of an Image from a container of bytes
+/// using a specified image format, returning an error if the container cannot be decoded.
+/// # Type Parameters
+/// * `Container` - The container type. This must be dereferenceable to a slice of bytes.
+pub trait TryFromWithFormat<Container>: Sized
+where
+    Container: Deref<Target = [u8]>,
+{
+    /// Transforms the given container and image format into an Image.
+    /// # Arguments
+    /// * `container` - The container to transform into an Image.
+    /// * `format` - The format of the image.
+    /// # Returns
+    /// An [Image](Image) with the given pixel and buffer type, or a [MergeError::DecodeError](crate::MergeError::DecodeError)
+    /// if the container could not be decoded.
+    /// # Example
+    /// ```
+    /// use image_merger::{TryFromWithFormat, Image, Rgba, BufferedImage};
+    ///
+    /// let container = vec![0, 0, 0, 255, 255, 255, 255, 255];
+    /// let image = BufferedImage::<Rgba<u8>>::try_from_with_format(container, image::ImageFormat::Png);
+    /// assert!(image.is_err());
+    /// ```
+    fn try_from_with_format(container: Container, format: ImageFormat) -> Result<Self, MergeError>;
+}
+
 macro_rules! impl_from_with_format {
     ($px_type:ident, $channel_type:ty, $to_fn:ident) => {
         #[doc = concat!(
@@ -175,10 +204,23 @@ macro_rules! impl_from_with_format {
             Container: Deref<Target = [u8]>,
         {
             fn from_with_format(container: Container, format: ImageFormat) -> Self {
-                let dyn_image = image::load_from_memory_with_format(&container, format).unwrap();
+                Self::try_from_with_format(container, format).unwrap_or_else(|err| panic!("{}", err))
+            }
+        }
+
+        impl<Container> TryFromWithFormat<Container>
+            for Image<
+                $px_type<$channel_type>,
+                ImageBuffer<$px_type<$channel_type>, Vec<$channel_type>>,
+            >
+        where
+            Container: Deref<Target = [u8]>,
+        {
+            fn try_from_with_format(container: Container, format: ImageFormat) -> Result<Self, MergeError> {
+                let dyn_image = image::load_from_memory_with_format(&container, format)?;
                 let img = dyn_image.$to_fn();
 
-                Self::from(img)
+                Ok(Self::from(img))
             }
         }
     };
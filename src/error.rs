@@ -0,0 +1,140 @@
+use crate::Rect;
+
+use std::{fmt, path::PathBuf};
+
+/// The error type returned by the fallible APIs of this crate, such as [Merger::try_push](crate::Merger::try_push) or
+/// [KnownSizeMerger::try_new](crate::KnownSizeMerger::try_new).
+#[derive(Debug)]
+pub enum MergeError {
+    /// The canvas does not have enough free slots left for the requested images.
+    CanvasFull {
+        /// The number of free slots left on the canvas.
+        remaining: u32,
+        /// The number of images that were requested to be pushed.
+        requested: u32,
+    },
+    /// The image being pushed does not match the dimensions the merger expects.
+    DimensionMismatch {
+        /// The dimensions, (x, y), the merger expects.
+        expected: (u32, u32),
+        /// The dimensions, (x, y), of the image that was pushed.
+        actual: (u32, u32),
+    },
+    /// An image could not be decoded.
+    DecodeError(image::ImageError),
+    /// An image file could not be read or decoded.
+    LoadError {
+        /// The path of the file.
+        path: PathBuf,
+        /// Why the file could not be loaded.
+        error: image::ImageError,
+    },
+    /// The requested canvas is too large to be represented, either because its dimensions overflow a `u32` or because
+    /// its buffer would not fit in memory.
+    CanvasTooLarge,
+    /// The container given to a `new_from_raw` constructor is too small to hold the canvas.
+    BufferTooSmall {
+        /// The number of subpixels the canvas needs.
+        required: usize,
+        /// The number of subpixels the container holds.
+        actual: usize,
+    },
+    /// The index of a cell does not lie on the canvas.
+    CellOutOfBounds {
+        /// The requested index.
+        index: u32,
+        /// The number of cells on the canvas.
+        capacity: u32,
+    },
+    /// The layout given to a merger is invalid, for example because it has no images per row.
+    InvalidLayout(&'static str),
+    /// An atlas placement file could not be parsed, or describes images outside of the canvas.
+    InvalidAtlas(String),
+    /// A font could not be loaded, such as a [BitmapFont](crate::BitmapFont) from a PSF file.
+    InvalidFont(&'static str),
+    /// A region requested from a [raw::ImageCell](crate::raw::ImageCell) does not lie within its image.
+    RegionOutOfBounds {
+        /// The requested region.
+        region: Rect,
+        /// The dimensions, (x, y), of the image.
+        dimensions: (u32, u32),
+    },
+    /// Two regions requested from a [raw::ImageCell](crate::raw::ImageCell) overlap.
+    RegionOverlap {
+        /// The region requested first.
+        first: Rect,
+        /// The region requested later, which overlaps the first one.
+        second: Rect,
+    },
+}
+
+impl fmt::Display for MergeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MergeError::CanvasFull {
+                remaining,
+                requested,
+            } => write!(
+                f,
+                "not enough space on the canvas: {} image(s) requested but only {} slot(s) remain",
+                requested, remaining
+            ),
+            MergeError::DimensionMismatch { expected, actual } => write!(
+                f,
+                "image dimensions {}x{} do not match the expected {}x{}",
+                actual.0, actual.1, expected.0, expected.1
+            ),
+            MergeError::DecodeError(err) => write!(f, "failed to decode image: {}", err),
+            MergeError::LoadError { path, error } => {
+                write!(f, "failed to load {}: {}", path.display(), error)
+            }
+            MergeError::CanvasTooLarge => write!(f, "the canvas is too large to be created"),
+            MergeError::BufferTooSmall { required, actual } => write!(
+                f,
+                "the container holds {} subpixels but the canvas requires {}",
+                actual, required
+            ),
+            MergeError::CellOutOfBounds { index, capacity } => write!(
+                f,
+                "cell {} does not lie on the canvas, which has {} cell(s)",
+                index, capacity
+            ),
+            MergeError::InvalidLayout(reason) => write!(f, "invalid layout: {}", reason),
+            MergeError::InvalidAtlas(reason) => write!(f, "invalid atlas: {}", reason),
+            MergeError::InvalidFont(reason) => write!(f, "invalid font: {}", reason),
+            MergeError::RegionOutOfBounds { region, dimensions } => write!(
+                f,
+                "the {}x{} region at ({}, {}) does not lie within the {}x{} image",
+                region.width, region.height, region.x, region.y, dimensions.0, dimensions.1
+            ),
+            MergeError::RegionOverlap { first, second } => write!(
+                f,
+                "the {}x{} region at ({}, {}) overlaps the {}x{} region at ({}, {})",
+                second.width,
+                second.height,
+                second.x,
+                second.y,
+                first.width,
+                first.height,
+                first.x,
+                first.y
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MergeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MergeError::DecodeError(err) => Some(err),
+            MergeError::LoadError { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<image::ImageError> for MergeError {
+    fn from(err: image::ImageError) -> Self {
+        MergeError::DecodeError(err)
+    }
+}
@@ -1,47 +1,678 @@
-use crate::{cell::ImageCell, core::Image, merger::Point, BufferedImage};
+use crate::{
+    cell::ImageCell,
+    composite::{from_unit, has_alpha, mix, to_unit, Composite, CompositeOp},
+    core::Image,
+    merger::{BlurFilter, CellDecoration, ConvertPixel, Point, Rect, ResizeFilter, Shadow},
+    view::{ImageView, Samples},
+    BufferedImage,
+};
 use image::Pixel;
 use rayon::{
-    iter::IntoParallelIterator,
-    prelude::{IndexedParallelIterator, ParallelIterator},
-    slice::ParallelSlice,
+    prelude::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator},
+    slice::ParallelSliceMut,
 };
 use std::{marker::Sync, ops::DerefMut};
 
 /// The library's underlying paste method. This is only used internally and should not be used by the user, but is exposed
 /// through the raw module for documentation purposes.
+///
+/// Every row of the top image is copied onto the canvas with a single `memcpy`, in parallel over rows, when the top image
+/// lies in a single buffer, and pixel by pixel otherwise. Any part of the top
+/// image that lies outside of the canvas is clipped.
 /// # Arguments
 /// * `bottom` - The image to paste onto.
-/// * `top` - The image to paste.
+/// * `top` - The image to paste, which can be any [ImageView](crate::ImageView).
 /// * `loc` - The location to paste the top image at.
-pub fn paste<P, Container>(
+///
+/// # Safety
+/// The canvas is written through a shared reference, so nothing else may touch the pixels the top image covers while
+/// this runs: no other paste or handout may write to them from another thread, and no reference to the canvas, such as
+/// one taken through `Deref`, may be read from at the same time. The `debug-checks` feature reports overlapping writes,
+/// but it is compiled out of release builds, so the caller must keep writes disjoint. Use
+/// [RegionMut::paste](crate::raw::RegionMut::paste) to paste from many threads without `unsafe` code.
+pub unsafe fn paste<P, Container, V>(
     bottom: &ImageCell<P, image::ImageBuffer<P, Container>>,
-    top: &Image<P, image::ImageBuffer<P, Container>>,
+    top: &V,
     loc: Point,
 ) where
     P: Pixel + Sync,
     <P as Pixel>::Subpixel: Sync,
     Container: DerefMut<Target = [P::Subpixel]>,
+    V: ImageView<Pixel = P>,
 {
-    // Go through each pixel in the image (at once), grab its relative location on the canvas,
-    // and alter the canvas underlying buffer to reflect the new pixel.
-    let image_width = top.width();
-    top.par_chunks_exact(<P as Pixel>::CHANNEL_COUNT as usize)
-        .enumerate()
-        .for_each(|(index, chunk)| {
-            let x = index as u32 % image_width;
-            let y = index as u32 / image_width;
+    paste_rows(bottom, top, loc, "paste", |canvas_row, row| {
+        canvas_row.copy_from_slice(row)
+    });
+}
+
+/// The library's underlying compositing paste method. Works like [paste](paste), but blends every pixel of the top image
+/// with the pixel beneath it using the given [Composite](crate::Composite). Falls back to [paste](paste) when the composite
+/// is a plain copy. This is only used internally and should not be used by the user, but is exposed through the raw module
+/// for documentation purposes.
+/// # Arguments
+/// * `bottom` - The image to paste onto.
+/// * `top` - The image to paste, which can be any [ImageView](crate::ImageView).
+/// * `loc` - The location to paste the top image at.
+/// * `composite` - How to combine the top image with the bottom image.
+///
+/// # Safety
+/// The same as for [paste](paste). The pixels beneath the top image are read as well as written.
+pub unsafe fn paste_composite<P, Container, V>(
+    bottom: &ImageCell<P, image::ImageBuffer<P, Container>>,
+    top: &V,
+    loc: Point,
+    composite: &Composite,
+) where
+    P: Pixel + Sync,
+    <P as Pixel>::Subpixel: Send + Sync,
+    Container: DerefMut<Target = [P::Subpixel]> + Sync,
+    V: ImageView<Pixel = P>,
+{
+    if composite.is_copy() {
+        // Safety: the top image covers the same pixels, which the caller keeps to itself.
+        return unsafe { paste(bottom, top, loc) };
+    }
+
+    paste_rows(bottom, top, loc, "paste_composite", |canvas_row, row| {
+        blend_row::<P>(canvas_row, row, composite)
+    });
+}
+
+/// The library's underlying converting paste method. Works like [paste_composite](paste_composite), but the top image may
+/// have any pixel type that converts into the pixel type of the canvas. Every row of the top image is converted as it is
+/// pasted, in parallel over rows, so the converted image is never held in memory. This is only used internally and
+/// should not be used by the user, but is exposed through the raw module for documentation purposes.
+/// # Arguments
+/// * `bottom` - The image to paste onto.
+/// * `top` - The image to convert and paste, which can be any [ImageView](crate::ImageView).
+/// * `loc` - The location to paste the top image at.
+/// * `composite` - How to combine the converted top image with the bottom image.
+///
+/// # Safety
+/// The same as for [paste](paste).
+pub unsafe fn paste_converted<P, Q, Container, V>(
+    bottom: &ImageCell<P, image::ImageBuffer<P, Container>>,
+    top: &V,
+    loc: Point,
+    composite: &Composite,
+) where
+    P: Pixel + Sync,
+    <P as Pixel>::Subpixel: Send + Sync,
+    Q: ConvertPixel<P> + Sync,
+    <Q as Pixel>::Subpixel: Sync,
+    Container: DerefMut<Target = [P::Subpixel]> + Sync,
+    V: ImageView<Pixel = Q>,
+{
+    let is_copy = composite.is_copy();
+    paste_rows(bottom, top, loc, "paste_converted", |canvas_row, row| {
+        if is_copy {
+            return Q::convert_row(row, canvas_row);
+        }
+
+        blend_converted_row::<P, Q>(canvas_row, row, composite);
+    });
+}
+
+/// The library's underlying decorating paste method. Works like [paste_composite](paste_composite), but masks the top
+/// image to the rounded corners of the given cell and draws its border, in the same pass over the rows of the cell that
+/// writes the image. The edges of rounded corners are anti-aliased against the canvas beneath them. Falls back to
+/// [paste_composite](paste_composite) when the decoration is plain. This is only used internally and should not be
+/// used by the user, but is exposed through the raw module for documentation purposes.
+/// # Arguments
+/// * `bottom` - The image to paste onto.
+/// * `top` - The image to paste, which can be any [ImageView](crate::ImageView).
+/// * `loc` - The location to paste the top image at, which must lie within the cell.
+/// * `cell` - The cell the top image is pasted into, which the decoration is drawn around.
+/// * `composite` - How to combine the top image with the bottom image.
+/// * `decoration` - The border and rounded corners to draw.
+///
+/// # Safety
+/// The same as for [paste](paste), but for the whole frame of the cell, which is larger than the cell with an outside
+/// border or a shadow.
+pub unsafe fn paste_decorated<P, Container, V>(
+    bottom: &ImageCell<P, image::ImageBuffer<P, Container>>,
+    top: &V,
+    loc: Point,
+    cell: Rect,
+    composite: &Composite,
+    decoration: &CellDecoration<P>,
+) where
+    P: Pixel + Sync,
+    <P as Pixel>::Subpixel: Send + Sync,
+    Container: DerefMut<Target = [P::Subpixel]> + Sync,
+    V: ImageView<Pixel = P>,
+{
+    if decoration.is_plain() {
+        // Safety: a plain decoration has no frame outside of the cell, so the paste covers no more than the caller
+        // keeps to itself.
+        return unsafe { paste_composite(bottom, top, loc, composite) };
+    }
 
-            let canvas_x = loc.x + x;
-            let canvas_y = loc.y + y;
+    let is_copy = composite.is_copy();
+    paste_framed(
+        bottom,
+        top,
+        loc,
+        cell,
+        decoration,
+        "paste_decorated",
+        |canvas_row, row| {
+            if is_copy {
+                canvas_row.copy_from_slice(row);
+            } else {
+                blend_row::<P>(canvas_row, row, composite);
+            }
+        },
+    );
+}
+
+/// The library's underlying decorating and converting paste method. Works like [paste_decorated](paste_decorated), but
+/// the top image may have any pixel type that converts into the pixel type of the canvas, the same way
+/// [paste_converted](paste_converted) does. This is only used internally and should not be used by the user, but is
+/// exposed through the raw module for documentation purposes.
+/// # Arguments
+/// * `bottom` - The image to paste onto.
+/// * `top` - The image to convert and paste, which can be any [ImageView](crate::ImageView).
+/// * `loc` - The location to paste the top image at, which must lie within the cell.
+/// * `cell` - The cell the top image is pasted into, which the decoration is drawn around.
+/// * `composite` - How to combine the converted top image with the bottom image.
+/// * `decoration` - The border and rounded corners to draw.
+///
+/// # Safety
+/// The same as for [paste_decorated](paste_decorated).
+pub unsafe fn paste_decorated_converted<P, Q, Container, V>(
+    bottom: &ImageCell<P, image::ImageBuffer<P, Container>>,
+    top: &V,
+    loc: Point,
+    cell: Rect,
+    composite: &Composite,
+    decoration: &CellDecoration<P>,
+) where
+    P: Pixel + Sync,
+    <P as Pixel>::Subpixel: Send + Sync,
+    Q: ConvertPixel<P> + Sync,
+    <Q as Pixel>::Subpixel: Sync,
+    Container: DerefMut<Target = [P::Subpixel]> + Sync,
+    V: ImageView<Pixel = Q>,
+{
+    if decoration.is_plain() {
+        // Safety: a plain decoration has no frame outside of the cell, so the paste covers no more than the caller
+        // keeps to itself.
+        return unsafe { paste_converted(bottom, top, loc, composite) };
+    }
 
-            let pixel = <P as Pixel>::from_slice(chunk);
-            unsafe {
-                let mut handout = bottom.request_handout(canvas_x, canvas_y);
-                handout.unsafe_put_pixel(*pixel);
+    let is_copy = composite.is_copy();
+    paste_framed(
+        bottom,
+        top,
+        loc,
+        cell,
+        decoration,
+        "paste_decorated_converted",
+        |canvas_row, row| {
+            if is_copy {
+                return Q::convert_row(row, canvas_row);
+            }
+
+            blend_converted_row::<P, Q>(canvas_row, row, composite);
+        },
+    );
+}
+
+/// Converts a row of pixels, pixel by pixel, and blends it onto a row of the canvas, of the same length, using the given
+/// composite.
+fn blend_converted_row<P, Q>(
+    canvas_row: &mut [P::Subpixel],
+    row: &[Q::Subpixel],
+    composite: &Composite,
+) where
+    P: Pixel,
+    Q: ConvertPixel<P>,
+{
+    let channels = <P as Pixel>::CHANNEL_COUNT as usize;
+    let top_channels = <Q as Pixel>::CHANNEL_COUNT as usize;
+    for (canvas_chunk, chunk) in canvas_row
+        .chunks_exact_mut(channels)
+        .zip(row.chunks_exact(top_channels))
+    {
+        let canvas_pixel = *<P as Pixel>::from_slice(canvas_chunk);
+        let mut converted = canvas_pixel;
+        Q::convert_pixel(chunk, converted.channels_mut());
+
+        let blended = composite.apply(&canvas_pixel, &converted);
+        canvas_chunk.copy_from_slice(blended.channels());
+    }
+}
+
+/// Blends a row of pixels onto a row of the canvas, of the same length, using the given composite.
+fn blend_row<P: Pixel>(canvas_row: &mut [P::Subpixel], row: &[P::Subpixel], composite: &Composite) {
+    let channels = <P as Pixel>::CHANNEL_COUNT as usize;
+    for (canvas_chunk, chunk) in canvas_row
+        .chunks_exact_mut(channels)
+        .zip(row.chunks_exact(channels))
+    {
+        let blended = composite.apply(
+            <P as Pixel>::from_slice(canvas_chunk),
+            <P as Pixel>::from_slice(chunk),
+        );
+        canvas_chunk.copy_from_slice(blended.channels());
+    }
+}
+
+/// Writes every row of the top image onto the rows of the canvas beneath it with `write`, in parallel over rows. The
+/// top image is clipped to the canvas, and the area it covers is claimed for the write.
+/// # Arguments
+/// * `write` - Called with a row of the canvas and the row of the top image, of the same width, to write onto it.
+fn paste_rows<P, Q, Container, V, F>(
+    bottom: &ImageCell<P, image::ImageBuffer<P, Container>>,
+    top: &V,
+    loc: Point,
+    writer: &'static str,
+    write: F,
+) where
+    P: Pixel + Sync,
+    <P as Pixel>::Subpixel: Sync,
+    Q: Pixel,
+    <Q as Pixel>::Subpixel: Sync,
+    Container: DerefMut<Target = [P::Subpixel]>,
+    V: ImageView<Pixel = Q>,
+    F: Fn(&mut [P::Subpixel], &[Q::Subpixel]) + Sync,
+{
+    let channels = <P as Pixel>::CHANNEL_COUNT as usize;
+    let top_channels = <Q as Pixel>::CHANNEL_COUNT as usize;
+    let (canvas_width, canvas_height) = bottom.dimensions();
+    let (top_width, top_height) = top.view_dimensions();
+
+    // Clip the top image to the canvas, so every row we write lies within the canvas buffer.
+    let width = top_width.min(canvas_width.saturating_sub(loc.x));
+    let height = top_height.min(canvas_height.saturating_sub(loc.y));
+    if width == 0 || height == 0 {
+        return;
+    }
+
+    let _claim = bottom.claim(
+        Rect {
+            x: loc.x,
+            y: loc.y,
+            width,
+            height,
+        },
+        writer,
+    );
+    let (width, height) = (width as usize, height as usize);
+
+    let canvas_stride = canvas_width as usize * channels;
+    let row_len = width * channels;
+    let top_row_len = width * top_channels;
+    let samples = top.samples();
+    let buffer = SharedBuffer(bottom.as_mut_ptr());
+
+    (0..height).into_par_iter().for_each(|y| {
+        let start = (loc.y as usize + y) * canvas_stride + loc.x as usize * channels;
+
+        // Safety: the clipped row lies within the canvas buffer, and no two rows of the top image overlap on it.
+        let canvas_row =
+            unsafe { std::slice::from_raw_parts_mut(buffer.get().add(start), row_len) };
+        match samples {
+            Some(samples) => write(canvas_row, samples.row(y, top_row_len)),
+            None => {
+                let mut row = Vec::with_capacity(top_row_len);
+                for x in 0..width as u32 {
+                    row.extend_from_slice(top.view_pixel(x, y as u32).channels());
+                }
+
+                write(canvas_row, &row);
+            }
+        }
+    });
+}
+
+/// Draws the decoration of the cell and writes the top image onto the canvas with `write`, like
+/// [paste_rows](paste_rows), in parallel over the rows of its frame. Each row of the frame is shaded first, with the
+/// shadow composited onto the canvas and the border mixed in where it covers the row. The top image is then written
+/// onto the shaded row, and mixed back with it where the rounded mask of the cell only partly covers a pixel. The part
+/// of the frame that lies on the canvas is claimed for the write.
+fn paste_framed<P, Q, Container, V, F>(
+    bottom: &ImageCell<P, image::ImageBuffer<P, Container>>,
+    top: &V,
+    loc: Point,
+    cell: Rect,
+    decoration: &CellDecoration<P>,
+    writer: &'static str,
+    write: F,
+) where
+    P: Pixel + Sync,
+    <P as Pixel>::Subpixel: Sync,
+    Q: Pixel,
+    <Q as Pixel>::Subpixel: Sync,
+    Container: DerefMut<Target = [P::Subpixel]>,
+    V: ImageView<Pixel = Q>,
+    F: Fn(&mut [P::Subpixel], &[Q::Subpixel]) + Sync,
+{
+    let channels = <P as Pixel>::CHANNEL_COUNT as usize;
+    let top_channels = <Q as Pixel>::CHANNEL_COUNT as usize;
+    let (canvas_width, canvas_height) = bottom.dimensions();
+    let (top_width, top_height) = top.view_dimensions();
+
+    let frame = decoration.frame(cell);
+
+    // Clip the frame to the canvas, and the top image to the frame.
+    let right = frame.x.saturating_add(frame.width).min(canvas_width);
+    let bottom_edge = frame.y.saturating_add(frame.height).min(canvas_height);
+    if frame.x >= right || frame.y >= bottom_edge {
+        return;
+    }
+
+    let _claim = bottom.claim(
+        Rect {
+            x: frame.x,
+            y: frame.y,
+            width: right - frame.x,
+            height: bottom_edge - frame.y,
+        },
+        writer,
+    );
+
+    let left = loc.x.max(frame.x);
+    let image_columns = left..loc.x.saturating_add(top_width).min(right);
+    let image_rows = loc.y.max(frame.y)..loc.y.saturating_add(top_height).min(bottom_edge);
+
+    let (mask, border) = decoration.shapes(cell);
+
+    // The shadow is cast by whatever the decoration leaves visible of the image, along with its border.
+    let top_alpha = |x: u32, y: u32| {
+        if has_alpha::<Q>() {
+            to_unit(top.view_pixel(x - loc.x, y - loc.y).channels()[top_channels - 1])
+        } else {
+            1.0
+        }
+    };
+    let shadow = decoration
+        .shadow
+        .filter(|shadow| shadow.opacity > 0.0)
+        .map(|shadow| {
+            let outset = decoration.outset();
+            let caster = Rect {
+                x: cell.x.saturating_sub(outset),
+                y: cell.y.saturating_sub(outset),
+                width: cell.width + 2 * outset,
+                height: cell.height + 2 * outset,
+            };
+
+            CastShadow::new(shadow, caster, |x, y| {
+                let mut alpha = match &border {
+                    Some((shape, _)) => shape.coverage(x, y),
+                    None => 0.0,
+                };
+                if image_columns.contains(&x) && image_rows.contains(&y) {
+                    alpha = alpha.max(mask.coverage(x, y) * top_alpha(x, y));
+                }
+
+                alpha
+            })
+        });
+    let over = Composite::new(CompositeOp::SourceOver);
+
+    let canvas_stride = canvas_width as usize * channels;
+    let frame_len = (right - frame.x) as usize * channels;
+    let samples = top.samples();
+    let buffer = SharedBuffer(bottom.as_mut_ptr());
+
+    // Every job reuses the same buffers for the rows of the top image and the shaded canvas beneath them.
+    let buffers = || (Vec::new(), Vec::new());
+    (frame.y..bottom_edge)
+        .into_par_iter()
+        .for_each_init(buffers, |(row, shaded), y| {
+            let start = y as usize * canvas_stride + frame.x as usize * channels;
+
+            // Safety: the clipped row of the frame lies within the canvas buffer, and no two rows of the frame overlap.
+            let canvas_row =
+                unsafe { std::slice::from_raw_parts_mut(buffer.get().add(start), frame_len) };
+
+            for (index, pixel) in canvas_row.chunks_exact_mut(channels).enumerate() {
+                let x = frame.x + index as u32;
+                let mut shaded = *<P as Pixel>::from_slice(pixel);
+
+                if let Some(shadow) = &shadow {
+                    let alpha = shadow.alpha_at(x, y);
+                    if alpha > 0.0 {
+                        shaded = over.with_opacity(alpha).apply(&shaded, &shadow.color);
+                    }
+                }
+                if let Some((shape, color)) = &border {
+                    shaded = mix(&shaded, color, shape.coverage(x, y));
+                }
+
+                pixel.copy_from_slice(shaded.channels());
+            }
+
+            if image_rows.contains(&y) && !image_columns.is_empty() {
+                let (top_x, top_y) = ((image_columns.start - loc.x) as usize, y - loc.y);
+                let width = image_columns.len();
+                let row: &[Q::Subpixel] = match samples {
+                    Some(samples) => &samples.row(top_y as usize, (top_x + width) * top_channels)
+                        [top_x * top_channels..],
+                    None => {
+                        row.clear();
+                        for x in top_x..top_x + width {
+                            row.extend_from_slice(top.view_pixel(x as u32, top_y).channels());
+                        }
+
+                        row
+                    }
+                };
+
+                let from = (image_columns.start - frame.x) as usize * channels;
+                let written = &mut canvas_row[from..from + width * channels];
+                shaded.clear();
+                shaded.extend_from_slice(written);
+                write(written, row);
+
+                for (index, pixel) in written.chunks_exact_mut(channels).enumerate() {
+                    let coverage = mask.coverage(image_columns.start + index as u32, y);
+                    if coverage < 1.0 {
+                        let beneath = <P as Pixel>::from_slice(
+                            &shaded[index * channels..(index + 1) * channels],
+                        );
+                        let mixed = mix(beneath, <P as Pixel>::from_slice(pixel), coverage);
+                        pixel.copy_from_slice(mixed.channels());
+                    }
+                }
             }
         });
 }
 
+/// The blurred alpha mask of a [Shadow](crate::Shadow), positioned on the canvas.
+struct CastShadow<P: Pixel> {
+    color: P,           // The color of the shadow.
+    opacity: f32,       // The opacity of the shadow where it is fully covered.
+    offset: (i64, i64), // Where the top left corner of the mask lies on the canvas, after the shadow is moved.
+    width: usize,       // The width of the mask.
+    height: usize,      // The height of the mask.
+    alpha: Vec<f32>,    // The blurred coverage of every pixel of the mask.
+}
+
+impl<P: Pixel> CastShadow<P> {
+    /// Builds the mask of the shadow cast by the caster, an area of the canvas, and blurs it in parallel.
+    /// # Arguments
+    /// * `alpha` - Returns how opaque the caster is at the given coordinates of the canvas.
+    fn new<F>(shadow: Shadow<P>, caster: Rect, alpha: F) -> Self
+    where
+        F: Fn(u32, u32) -> f32 + Sync,
+    {
+        // Leave room around the caster for the blur to spread into.
+        let radius = shadow.blur_radius;
+        let width = caster.width as usize + 2 * radius as usize;
+        let height = caster.height as usize + 2 * radius as usize;
+
+        let mut mask = vec![0.0; width * height];
+        if width > 0 {
+            mask.par_chunks_mut(width)
+                .enumerate()
+                .skip(radius as usize)
+                .take(caster.height as usize)
+                .for_each(|(y, row)| {
+                    let y = caster.y + (y - radius as usize) as u32;
+                    for (x, value) in row[radius as usize..][..caster.width as usize]
+                        .iter_mut()
+                        .enumerate()
+                    {
+                        *value = alpha(caster.x + x as u32, y);
+                    }
+                });
+        }
+
+        Self {
+            color: shadow.color,
+            opacity: shadow.opacity,
+            offset: (
+                caster.x as i64 - radius as i64 + shadow.offset.0 as i64,
+                caster.y as i64 - radius as i64 + shadow.offset.1 as i64,
+            ),
+            width,
+            height,
+            alpha: blur(&mask, width as u32, height as u32, radius, shadow.filter),
+        }
+    }
+
+    /// Returns the opacity of the shadow at the given coordinates of the canvas.
+    fn alpha_at(&self, x: u32, y: u32) -> f32 {
+        let (x, y) = (x as i64 - self.offset.0, y as i64 - self.offset.1);
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return 0.0;
+        }
+
+        self.alpha[y as usize * self.width + x as usize] * self.opacity
+    }
+}
+
+/// The library's underlying blur method, used to soften the edges of shadows. This is only used internally and should
+/// not be used by the user, but is exposed through the raw module for documentation purposes.
+///
+/// Every pass blurs the rows of the mask in parallel with a running sum, so the cost of a pass does not depend on the
+/// radius. The columns are blurred the same way, after transposing the mask. Values beyond the edges of the mask count
+/// as zero.
+/// # Arguments
+/// * `mask` - The values to blur, such as an alpha channel, row by row.
+/// * `width` - The width of the mask.
+/// * `height` - The height of the mask.
+/// * `radius` - How far every value spreads out, in pixels, in every direction.
+/// * `filter` - Whether to blur with a single box blur, or approximate a Gaussian blur with three of them.
+/// # Returns
+/// The blurred mask, with the same dimensions.
+/// # Panics
+/// This function will panic if the mask does not hold `width * height` values.
+pub fn blur(mask: &[f32], width: u32, height: u32, radius: u32, filter: BlurFilter) -> Vec<f32> {
+    let (width, height) = (width as usize, height as usize);
+    if mask.len() != width * height {
+        panic!(
+            "the mask holds {} values, not {}x{}",
+            mask.len(),
+            width,
+            height
+        );
+    }
+
+    // Three box blurs whose radii add up to the radius spread no further than it, and are close to a Gaussian blur.
+    let passes = match filter {
+        BlurFilter::Box => vec![radius],
+        BlurFilter::Gaussian => (0..3)
+            .map(|pass| radius / 3 + u32::from(pass < radius % 3))
+            .collect(),
+    };
+
+    let mut blurred = mask.to_vec();
+    if width == 0 || height == 0 || radius == 0 {
+        return blurred;
+    }
+
+    for (width, height) in [(width, height), (height, width)] {
+        for &pass in &passes {
+            box_blur_rows(&mut blurred, width, pass as usize);
+        }
+
+        blurred = transpose(&blurred, width, height);
+    }
+
+    blurred
+}
+
+/// Box blurs every row of a mask in parallel, with the given radius.
+fn box_blur_rows(mask: &mut [f32], width: usize, radius: usize) {
+    if radius == 0 {
+        return;
+    }
+
+    let scale = 1.0 / (2 * radius + 1) as f32;
+    mask.par_chunks_mut(width).for_each(|row| {
+        let source = row.to_vec();
+
+        // The sum of the window around the first value, which only reaches to the right of it.
+        let mut sum: f32 = source[..(radius + 1).min(width)].iter().sum();
+        for x in 0..width {
+            row[x] = sum * scale;
+
+            if x + radius + 1 < width {
+                sum += source[x + radius + 1];
+            }
+            if x >= radius {
+                sum -= source[x - radius];
+            }
+        }
+    });
+}
+
+/// Transposes a mask with the given dimensions, in parallel over the rows of the result.
+fn transpose(mask: &[f32], width: usize, height: usize) -> Vec<f32> {
+    let mut transposed = vec![0.0; mask.len()];
+    transposed
+        .par_chunks_mut(height)
+        .enumerate()
+        .for_each(|(x, column)| {
+            for (y, value) in column.iter_mut().enumerate() {
+                *value = mask[y * width + x];
+            }
+        });
+
+    transposed
+}
+
+/// Copies the pixel at (x, y) of an image into `out`, straight out of its buffer when it lies in a single one.
+#[inline]
+fn copy_pixel<P, V>(
+    image: &V,
+    samples: Option<Samples<'_, P::Subpixel>>,
+    x: usize,
+    y: usize,
+    out: &mut [P::Subpixel],
+) where
+    P: Pixel,
+    V: ImageView<Pixel = P>,
+{
+    match samples {
+        Some(samples) => {
+            let start = y * samples.stride + x * out.len();
+            out.copy_from_slice(&samples.data[start..start + out.len()]);
+        }
+        None => out.copy_from_slice(image.view_pixel(x as u32, y as u32).channels()),
+    }
+}
+
+/// A pointer to the subpixels of a canvas that can be shared across threads, which write to disjoint parts of it.
+#[derive(Clone, Copy)]
+struct SharedBuffer<T>(*mut T);
+
+unsafe impl<T: Sync> Send for SharedBuffer<T> {}
+unsafe impl<T: Sync> Sync for SharedBuffer<T> {}
+
+impl<T> SharedBuffer<T> {
+    /// Returns the pointer. Closures must call this rather than reading the field, so they capture the whole
+    /// `SharedBuffer` instead of the bare pointer, which cannot be shared across threads.
+    fn get(self) -> *mut T {
+        self.0
+    }
+}
+
 /// The library's underlying resize method. This is only used internally and should not be used by the user, but is exposed
 /// through the raw module for documentation purposes.
 /// # Arguments
@@ -50,45 +681,296 @@ pub fn paste<P, Container>(
 /// * `nheight` - The new height of the image.
 /// # Returns
 /// * A new image with the new dimensions. Note that the returned image's underlying buffer is not guaranteed to be the same as the input image's buffer. The returned buffer will be `Vec` based.
-pub fn resize_nearest_neighbor<P, U>(
-    image: &Image<P, U>,
-    nwidth: u32,
-    nheight: u32,
-) -> BufferedImage<P>
+pub fn resize_nearest_neighbor<P, V>(image: &V, nwidth: u32, nheight: u32) -> BufferedImage<P>
 where
     P: Pixel + Sync,
-    <P as Pixel>::Subpixel: Sync,
-    U: image::GenericImage<Pixel = P> + Sync,
+    <P as Pixel>::Subpixel: Send + Sync,
+    V: ImageView<Pixel = P>,
 {
-    // Create a cell to hold the new image.
-    let new_image: Image<P, _> = Image::new(nwidth, nheight);
-    let cell = ImageCell::new(new_image);
+    let channels = <P as Pixel>::CHANNEL_COUNT as usize;
+    let mut resized: BufferedImage<P> = Image::new(nwidth, nheight);
+    if nwidth == 0 || nheight == 0 {
+        return resized;
+    }
 
     // Grab the ratios of the new image to the old image.
-    let height_ratio = image.height() as f32 / nheight as f32;
-    let width_ratio = image.width() as f32 / nwidth as f32;
+    let (width, height) = image.view_dimensions();
+    let height_ratio = height as f32 / nheight as f32;
+    let width_ratio = width as f32 / nwidth as f32;
+
+    let samples = image.samples();
+    resized
+        .par_chunks_exact_mut(nwidth as usize * channels)
+        .enumerate()
+        .for_each(|(j, row)| {
+            let y = (j as f32 * height_ratio) as usize;
 
-    (0..nwidth).into_par_iter().for_each(|i| {
-        (0..nheight).into_par_iter().for_each(|j| {
-            let x = (i as f32 * width_ratio) as u32;
-            let y = (j as f32 * height_ratio) as u32;
+            for (i, chunk) in row.chunks_exact_mut(channels).enumerate() {
+                let x = (i as f32 * width_ratio) as usize;
+                copy_pixel(image, samples, x, y, chunk);
+            }
+        });
 
-            let pixel = image.get_pixel(x, y);
+    resized
+}
 
-            unsafe {
-                let mut handout = cell.request_handout(i, j);
-                handout.unsafe_put_pixel(pixel)
+/// The library's underlying crop method. This is only used internally and should not be used by the user, but is exposed
+/// through the raw module for documentation purposes.
+/// # Arguments
+/// * `image` - The image to crop, which can be any [ImageView](crate::ImageView).
+/// * `area` - The area of the image to keep. This area must lie within the image.
+/// # Returns
+/// * A new, `Vec` based, image holding the pixels of the given area.
+pub fn crop<P, V>(image: &V, area: Rect) -> BufferedImage<P>
+where
+    P: Pixel + Sync,
+    <P as Pixel>::Subpixel: Send + Sync,
+    V: ImageView<Pixel = P>,
+{
+    let channels = <P as Pixel>::CHANNEL_COUNT as usize;
+    let row_len = area.width as usize * channels;
+
+    let mut cropped: BufferedImage<P> = Image::new(area.width, area.height);
+    if row_len == 0 {
+        return cropped;
+    }
+
+    let samples = image.samples();
+    cropped
+        .par_chunks_exact_mut(row_len)
+        .enumerate()
+        .for_each(|(y, row)| {
+            let y = area.y as usize + y;
+            match samples {
+                // Every row of the cropped image is a contiguous slice of a row in the source image.
+                Some(samples) => {
+                    let start = area.x as usize * channels;
+                    row.copy_from_slice(&samples.row(y, start + row_len)[start..]);
+                }
+                None => {
+                    for (x, pixel) in row.chunks_exact_mut(channels).enumerate() {
+                        copy_pixel(image, samples, area.x as usize + x, y, pixel);
+                    }
+                }
             }
-        })
-    });
+        });
+
+    cropped
+}
+
+/// Rotates an image by 90 degrees clockwise, in parallel over the rows of the rotated image. Used by the
+/// [PackingMerger](crate::PackingMerger) to paste images that were rotated to fit.
+/// This is only used internally and should not be used by the user, but is exposed through the raw module for
+/// documentation purposes.
+/// # Arguments
+/// * `image` - The image to rotate, which can be any [ImageView](crate::ImageView).
+/// # Returns
+/// The rotated image, with its width and height swapped.
+pub fn rotate90<P, V>(image: &V) -> BufferedImage<P>
+where
+    P: Pixel + Sync,
+    <P as Pixel>::Subpixel: Send + Sync,
+    V: ImageView<Pixel = P>,
+{
+    let channels = <P as Pixel>::CHANNEL_COUNT as usize;
+    let (width, height) = image.view_dimensions();
+    let row_len = height as usize * channels;
+
+    let mut rotated: BufferedImage<P> = Image::new(height, width);
+    if row_len == 0 {
+        return rotated;
+    }
+
+    // Row `y` of the rotated image is column `y` of the source image, read from the bottom up.
+    let samples = image.samples();
+    rotated
+        .par_chunks_exact_mut(row_len)
+        .enumerate()
+        .for_each(|(y, row)| {
+            for (x, pixel) in row.chunks_exact_mut(channels).enumerate() {
+                copy_pixel(image, samples, y, height as usize - 1 - x, pixel);
+            }
+        });
+
+    rotated
+}
+
+/// Rotates an image by 90 degrees counter-clockwise, undoing [rotate90](rotate90). Used by the
+/// [Splitter](crate::Splitter) to restore images that were rotated to fit on a packed canvas.
+/// This is only used internally and should not be used by the user, but is exposed through the raw module for
+/// documentation purposes.
+/// # Arguments
+/// * `image` - The image to rotate, which can be any [ImageView](crate::ImageView).
+/// # Returns
+/// The rotated image, with its width and height swapped.
+pub fn rotate270<P, V>(image: &V) -> BufferedImage<P>
+where
+    P: Pixel + Sync,
+    <P as Pixel>::Subpixel: Send + Sync,
+    V: ImageView<Pixel = P>,
+{
+    let channels = <P as Pixel>::CHANNEL_COUNT as usize;
+    let (width, height) = image.view_dimensions();
+    let row_len = height as usize * channels;
+
+    let mut rotated: BufferedImage<P> = Image::new(height, width);
+    if row_len == 0 {
+        return rotated;
+    }
+
+    // Row `y` of the rotated image is column `width - 1 - y` of the source image, read from the top down.
+    let samples = image.samples();
+    rotated
+        .par_chunks_exact_mut(row_len)
+        .enumerate()
+        .for_each(|(y, row)| {
+            let column = width as usize - 1 - y;
+            for (x, pixel) in row.chunks_exact_mut(channels).enumerate() {
+                copy_pixel(image, samples, column, x, pixel);
+            }
+        });
+
+    rotated
+}
+
+/// The library's underlying resize method for every [ResizeFilter](crate::ResizeFilter). Nearest neighbor resizing is
+/// delegated to [resize_nearest_neighbor](resize_nearest_neighbor), every other filter is applied with a separable
+/// resampler: the image is first resized horizontally, then vertically, with both passes running in parallel over rows.
+/// This is only used internally and should not be used by the user, but is exposed through the raw module for
+/// documentation purposes.
+/// # Arguments
+/// * `image` - The image to resize.
+/// * `nwidth` - The new width of the image.
+/// * `nheight` - The new height of the image.
+/// * `filter` - The filter to resample the image with.
+/// # Returns
+/// * A new image with the new dimensions. The returned buffer will be `Vec` based.
+pub fn resize<P, V>(image: &V, nwidth: u32, nheight: u32, filter: ResizeFilter) -> BufferedImage<P>
+where
+    P: Pixel + Sync,
+    <P as Pixel>::Subpixel: Send + Sync,
+    V: ImageView<Pixel = P>,
+{
+    if filter == ResizeFilter::Nearest {
+        return resize_nearest_neighbor(image, nwidth, nheight);
+    }
+
+    let channels = <P as Pixel>::CHANNEL_COUNT as usize;
+    let (width, height) = image.view_dimensions();
+
+    let mut resized: BufferedImage<P> = Image::new(nwidth, nheight);
+    if nwidth == 0 || nheight == 0 || width == 0 || height == 0 {
+        return resized;
+    }
+
+    let horizontal = contributions(width, nwidth, filter);
+    let vertical = contributions(height, nheight, filter);
+
+    // Horizontal pass, from the source image into an intermediate buffer of `nwidth` by `height` unit values.
+    let mut intermediate = vec![0.0f32; nwidth as usize * height as usize * channels];
+    intermediate
+        .par_chunks_exact_mut(nwidth as usize * channels)
+        .enumerate()
+        .for_each(|(y, row)| {
+            for (x, contribution) in horizontal.iter().enumerate() {
+                let out = &mut row[x * channels..(x + 1) * channels];
+                for (offset, weight) in contribution.weights.iter().enumerate() {
+                    let pixel = image.view_pixel(contribution.start + offset as u32, y as u32);
+                    for (value, channel) in out.iter_mut().zip(pixel.channels()) {
+                        *value += to_unit(*channel) * weight;
+                    }
+                }
+            }
+        });
+
+    // Vertical pass, from the intermediate buffer into the resized image.
+    let intermediate_stride = nwidth as usize * channels;
+    resized
+        .par_chunks_exact_mut(nwidth as usize * channels)
+        .enumerate()
+        .for_each(|(y, row)| {
+            let contribution = &vertical[y];
+            for (index, subpixel) in row.iter_mut().enumerate() {
+                let mut value = 0.0;
+                for (offset, weight) in contribution.weights.iter().enumerate() {
+                    let source_y = contribution.start as usize + offset;
+                    value += intermediate[source_y * intermediate_stride + index] * weight;
+                }
+
+                *subpixel = from_unit(value);
+            }
+        });
+
+    resized
+}
 
-    cell.into_inner()
+/// The source pixels, and their normalized weights, that make up a single destination pixel along one axis.
+struct Contribution {
+    start: u32,
+    weights: Vec<f32>,
+}
+
+/// Computes the contributions of every destination pixel along an axis when resizing from `size` to `nsize` pixels.
+fn contributions(size: u32, nsize: u32, filter: ResizeFilter) -> Vec<Contribution> {
+    let ratio = size as f32 / nsize as f32;
+
+    // When downscaling, the kernel is stretched to cover every source pixel that falls in a destination pixel.
+    let scale = ratio.max(1.0);
+    let support = filter.support() * scale;
+
+    (0..nsize)
+        .map(|index| {
+            let center = (index as f32 + 0.5) * ratio;
+            let start = ((center - support).floor().max(0.0) as u32).min(size - 1);
+            let end = ((center + support).ceil() as u32).clamp(start + 1, size);
+
+            let mut weights: Vec<f32> = (start..end)
+                .map(|source| filter.kernel((source as f32 + 0.5 - center) / scale))
+                .collect();
+
+            let total: f32 = weights.iter().sum();
+            if total != 0.0 {
+                weights.iter_mut().for_each(|weight| *weight /= total);
+            } else {
+                // The kernel missed every sample, fall back to the nearest source pixel.
+                weights.iter_mut().for_each(|weight| *weight = 0.0);
+                let last = weights.len() - 1;
+                let nearest = ((center as u32).min(size - 1).saturating_sub(start)) as usize;
+                weights[nearest.min(last)] = 1.0;
+            }
+
+            Contribution { start, weights }
+        })
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use image::Rgba;
+    use image::{Luma, Rgba};
+
+    #[test]
+    fn test_paste_rows() {
+        let mut top: Image<Rgba<u8>, _> = Image::new(30, 20);
+        for (x, y, pixel) in top.enumerate_pixels_mut() {
+            *pixel = Rgba([x as u8, y as u8, 255, 128]);
+        }
+
+        let mut expected = image::RgbaImage::from_pixel(50, 40, Rgba([1, 2, 3, 4]));
+        image::imageops::replace(&mut expected, &*top, 5, 7);
+        image::imageops::replace(&mut expected, &*top, 35, 30);
+
+        // The second paste hangs off the bottom right corner of the canvas and is clipped.
+        let canvas = ImageCell::new(Image::new_from_pixel(50, 40, Rgba([1, 2, 3, 4])));
+        // Safety: the pastes run on this thread, one after another.
+        unsafe {
+            paste(&canvas, &top, Point { x: 5, y: 7 });
+            paste(&canvas, &top, Point { x: 35, y: 30 });
+            paste(&canvas, &top, Point { x: 50, y: 0 });
+        }
+
+        assert_eq!(canvas.into_inner().into_buffer(), expected);
+    }
 
     #[test]
     fn test_resize_nearest_neighbor() {
@@ -112,4 +994,141 @@ mod tests {
 
         assert_eq!(fast_resized_underlying, slow_resized);
     }
+
+    #[test]
+    fn test_resize_preserves_solid_color() {
+        let image: Image<Rgba<u16>, _> =
+            Image::new_from_pixel(40, 30, Rgba([1000, 20000, 65535, 30000]));
+
+        for filter in [
+            ResizeFilter::Bilinear,
+            ResizeFilter::CatmullRom,
+            ResizeFilter::Mitchell,
+            ResizeFilter::Lanczos3,
+            ResizeFilter::Box,
+        ] {
+            for (width, height) in [(4, 3), (120, 90), (40, 7)] {
+                let resized = resize(&image, width, height, filter);
+                assert_eq!(resized.dimensions(), (width, height));
+                assert!(
+                    resized
+                        .pixels()
+                        .all(|p| *p == Rgba([1000, 20000, 65535, 30000])),
+                    "{:?} changed the color of a solid image",
+                    filter
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_resize_box_averages() {
+        let mut image: Image<Luma<u8>, _> = Image::new(4, 1);
+        image.put_pixel(0, 0, Luma([0]));
+        image.put_pixel(1, 0, Luma([100]));
+        image.put_pixel(2, 0, Luma([200]));
+        image.put_pixel(3, 0, Luma([250]));
+
+        let resized = resize(&image, 2, 1, ResizeFilter::Box);
+        assert_eq!(resized.get_pixel(0, 0), &Luma([50]));
+        assert_eq!(resized.get_pixel(1, 0), &Luma([225]));
+    }
+
+    #[test]
+    fn test_resize_bilinear_matches_image_crate() {
+        let mut image: Image<Rgba<u8>, _> = Image::new(100, 100);
+        for i in 0..100 {
+            for j in 0..100 {
+                image.put_pixel(
+                    i,
+                    j,
+                    Rgba([(i * 2) as u8, (j * 2) as u8, (i + j) as u8, 255]),
+                );
+            }
+        }
+
+        let fast_resized = resize(&image, 37, 61, ResizeFilter::Bilinear).into_buffer();
+        let slow_resized =
+            image::imageops::resize(&*image, 37, 61, image::imageops::FilterType::Triangle);
+
+        for (fast, slow) in fast_resized.pixels().zip(slow_resized.pixels()) {
+            for (a, b) in fast.0.iter().zip(slow.0.iter()) {
+                assert!(a.abs_diff(*b) <= 2, "{:?} != {:?}", fast, slow);
+            }
+        }
+    }
+
+    #[test]
+    fn test_crop() {
+        let mut image: Image<Rgba<u8>, _> = Image::new(100, 100);
+        for i in 0..100 {
+            for j in 0..100 {
+                image.put_pixel(i, j, Rgba([i as u8, j as u8, 0, 255]));
+            }
+        }
+
+        let area = Rect {
+            x: 10,
+            y: 20,
+            width: 30,
+            height: 40,
+        };
+        let fast_cropped = crop(&image, area).into_buffer();
+        let slow_cropped = image::imageops::crop_imm(&*image, 10, 20, 30, 40).to_image();
+
+        assert_eq!(fast_cropped, slow_cropped);
+    }
+
+    #[test]
+    fn test_rotate90() {
+        let mut image: Image<Rgba<u8>, _> = Image::new(30, 20);
+        for i in 0..30 {
+            for j in 0..20 {
+                image.put_pixel(i, j, Rgba([i as u8, j as u8, 0, 255]));
+            }
+        }
+
+        let fast_rotated = rotate90(&image).into_buffer();
+        let slow_rotated = image::imageops::rotate90(&*image);
+
+        assert_eq!(fast_rotated, slow_rotated);
+        assert_eq!(rotate270(&rotate90(&image)).into_buffer(), *image);
+    }
+
+    #[test]
+    fn test_box_blur_spreads_point_evenly() {
+        let mut mask = vec![0.0; 25];
+        mask[12] = 9.0;
+
+        let blurred = blur(&mask, 5, 5, 1, BlurFilter::Box);
+        for y in 0..5 {
+            for x in 0..5 {
+                let expected = if (1..4).contains(&x) && (1..4).contains(&y) {
+                    1.0
+                } else {
+                    0.0
+                };
+                assert!((blurred[y * 5 + x] - expected).abs() < 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn test_gaussian_blur_keeps_mass_within_radius() {
+        let (width, height) = (21, 15);
+        let mut mask = vec![0.0; width * height];
+        mask[7 * width + 10] = 1.0;
+
+        let blurred = blur(&mask, width as u32, height as u32, 5, BlurFilter::Gaussian);
+        let total: f32 = blurred.iter().sum();
+        assert!((total - 1.0).abs() < 1e-4);
+
+        // Nothing spreads past the radius, and the center is the brightest value.
+        assert_eq!(blurred[7 * width + 4], 0.0);
+        assert!(blurred[7 * width + 5] > 0.0);
+        assert!(blurred
+            .iter()
+            .all(|&value| value <= blurred[7 * width + 10]));
+        assert!((blurred[6 * width + 10] - blurred[8 * width + 10]).abs() < 1e-6);
+    }
 }
@@ -2,20 +2,41 @@
 //! It is built on top of the image crate and works to boost performance by utilizing parallel processing and
 //! avoiding unnecessary costly operations.
 //!
-//! The main type of this crate is the [KnownSizeMerger](crate::KnownSizeMerger) struct, but, more will be added in the future.
+//! The main type of this crate is the [KnownSizeMerger] struct. When the number of images is not
+//! known ahead of time, the [UnknownSizeMerger] grows its canvas as images are pushed, and the
+//! [PackingMerger] packs images of any size onto a compact canvas.
+mod atlas;
 mod cell;
+mod claims;
+mod composite;
 mod core;
+mod error;
 mod functions;
 mod merger;
+mod packer;
+mod splitter;
+mod text;
+mod view;
 
+pub use crate::atlas::*;
+pub use crate::composite::*;
 pub use crate::core::*;
+pub use crate::error::*;
 pub use crate::merger::*;
+pub use crate::splitter::*;
+pub use crate::text::{BitmapFont, TextAlign, TextStyle};
+pub use crate::view::*;
 pub use image::{ImageBuffer, Luma, LumaA, Pixel, Rgb, Rgba};
 
-/// Unsafe functions and types that are used internally by this crate. These are exposed for advanced users who want to
-/// implement their own merger. These functions and types are not guaranteed to be stable.
+/// Low level functions and types that are used internally by this crate. These are exposed for advanced users who want to
+/// implement their own merger. The paste functions write to a shared [ImageCell](crate::raw::ImageCell), so they are
+/// `unsafe` and their callers must keep writes from overlapping or being read at the same time. To write from many
+/// threads without any `unsafe` code, split the canvas with [ImageCell::regions_mut](crate::raw::ImageCell::regions_mut)
+/// and paste into each region with [RegionMut::paste](crate::raw::RegionMut::paste). These functions and types are not
+/// guaranteed to be stable.
 pub mod raw {
     pub use crate::cell::*;
+    pub use crate::claims::Batch;
     pub use crate::functions::*;
 }
 
@@ -0,0 +1,190 @@
+use crate::{cell::RegionMut, composite::mix, BufferedImage};
+
+use image::Pixel;
+use std::fmt::{self, Debug, Formatter};
+
+/// What a merger fills its canvas with where no image has been pasted: the margin, the gutters between cells, and cells
+/// that are empty or have been cleared. Every variant is a function of the position on the canvas, so a cleared cell is
+/// drawn exactly as it was before an image covered it, and the gutters around it stay seamless.
+///
+/// # Type Parameters
+/// * `P` - The pixel type of the canvas.
+///
+/// # Example
+/// ```
+/// use image_merger::{Background, KnownSizeMerger, Merger, MergerBuilder, Point, Rgb};
+///
+/// let merger: KnownSizeMerger<Rgb<u8>, _> = KnownSizeMerger::builder((100, 100))
+///     .with_images_per_row(5)
+///     .with_total_images(10)
+///     .with_padding(Point { x: 10, y: 10 })
+///     .with_background(Background::Checkerboard {
+///         first: Rgb([200, 200, 200]),
+///         second: Rgb([255, 255, 255]),
+///         size: 8,
+///     })
+///     .build();
+///
+/// assert_eq!(*merger.get_canvas().get_pixel(8, 0), Rgb([255, 255, 255]));
+/// ```
+pub enum Background<P: Pixel> {
+    /// A single color.
+    Solid(P),
+    /// A gradient from `start` to `end` across the whole canvas, along a direction given in degrees clockwise from
+    /// left to right, so `0.0` runs from the left edge to the right edge and `90.0` from the top edge to the bottom edge.
+    LinearGradient { start: P, end: P, angle: f32 },
+    /// A gradient from `inner` at the center of the canvas to `outer` at its corners.
+    RadialGradient { inner: P, outer: P },
+    /// Squares of `size` pixels alternating between `first` and `second`, starting with `first` in the top left corner.
+    Checkerboard { first: P, second: P, size: u32 },
+    /// An image repeated across the canvas, starting in the top left corner.
+    Pattern(BufferedImage<P>),
+}
+
+impl<P: Pixel> Clone for Background<P> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Solid(pixel) => Self::Solid(*pixel),
+            Self::LinearGradient { start, end, angle } => Self::LinearGradient {
+                start: *start,
+                end: *end,
+                angle: *angle,
+            },
+            Self::RadialGradient { inner, outer } => Self::RadialGradient {
+                inner: *inner,
+                outer: *outer,
+            },
+            Self::Checkerboard {
+                first,
+                second,
+                size,
+            } => Self::Checkerboard {
+                first: *first,
+                second: *second,
+                size: *size,
+            },
+            Self::Pattern(image) => Self::Pattern((**image).clone().into()),
+        }
+    }
+}
+
+impl<P: Pixel + Debug> Debug for Background<P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Solid(pixel) => f.debug_tuple("Solid").field(pixel).finish(),
+            Self::LinearGradient { start, end, angle } => f
+                .debug_struct("LinearGradient")
+                .field("start", start)
+                .field("end", end)
+                .field("angle", angle)
+                .finish(),
+            Self::RadialGradient { inner, outer } => f
+                .debug_struct("RadialGradient")
+                .field("inner", inner)
+                .field("outer", outer)
+                .finish(),
+            Self::Checkerboard {
+                first,
+                second,
+                size,
+            } => f
+                .debug_struct("Checkerboard")
+                .field("first", first)
+                .field("second", second)
+                .field("size", size)
+                .finish(),
+            // The pixels of a pattern are not worth printing, only its size.
+            Self::Pattern(image) => f.debug_tuple("Pattern").field(&image.dimensions()).finish(),
+        }
+    }
+}
+
+impl<P: Pixel> From<P> for Background<P> {
+    fn from(pixel: P) -> Self {
+        Self::Solid(pixel)
+    }
+}
+
+impl<P: Pixel> Background<P> {
+    /// Returns the color of the background at the given position of a canvas.
+    /// # Arguments
+    /// * `x` - The x coordinate on the canvas.
+    /// * `y` - The y coordinate on the canvas.
+    /// * `dimensions` - The dimensions, (width, height), of the canvas, which gradients are stretched across.
+    pub fn pixel_at(&self, x: u32, y: u32, dimensions: (u32, u32)) -> P {
+        // Gradients are sampled at the center of each pixel.
+        let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+        let (width, height) = (dimensions.0 as f32, dimensions.1 as f32);
+
+        match self {
+            Self::Solid(pixel) => *pixel,
+            Self::LinearGradient { start, end, angle } => {
+                let (dy, dx) = angle.to_radians().sin_cos();
+
+                // Project the pixel onto the direction, relative to the corners of the canvas that project the lowest
+                // and the highest, so the gradient always spans the canvas from edge to edge.
+                let corners = [0.0, width * dx, height * dy, width * dx + height * dy];
+                let low = corners.iter().copied().fold(f32::INFINITY, f32::min);
+                let high = corners.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+                let t = if high > low {
+                    (px * dx + py * dy - low) / (high - low)
+                } else {
+                    0.0
+                };
+
+                mix(start, end, t)
+            }
+            Self::RadialGradient { inner, outer } => {
+                let (cx, cy) = (width / 2.0, height / 2.0);
+                let radius = cx.hypot(cy);
+                let t = if radius > 0.0 {
+                    (px - cx).hypot(py - cy) / radius
+                } else {
+                    0.0
+                };
+
+                mix(inner, outer, t)
+            }
+            Self::Checkerboard {
+                first,
+                second,
+                size,
+            } => {
+                let size = (*size).max(1);
+                if (x / size + y / size).is_multiple_of(2) {
+                    *first
+                } else {
+                    *second
+                }
+            }
+            Self::Pattern(image) => *image.get_pixel(x % image.width(), y % image.height()),
+        }
+    }
+
+    /// Returns an error message if the background cannot be drawn, such as a checkerboard with empty squares.
+    pub(crate) fn validate(&self) -> Result<(), &'static str> {
+        match self {
+            Self::Checkerboard { size: 0, .. } => {
+                Err("checkerboard squares must be at least a pixel wide")
+            }
+            Self::Pattern(image) if image.width() == 0 || image.height() == 0 => {
+                Err("background patterns must not be empty")
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Draws the background onto a region of a canvas with the given dimensions, in parallel over the rows of the region.
+    pub(crate) fn fill(&self, region: &mut RegionMut<'_, P>, dimensions: (u32, u32))
+    where
+        P: Sync,
+        <P as Pixel>::Subpixel: Send + Sync,
+    {
+        if let Self::Solid(pixel) = self {
+            return region.fill(*pixel);
+        }
+
+        let rect = region.get_rect();
+        region.fill_with(|x, y| self.pixel_at(rect.x + x, rect.y + y, dimensions));
+    }
+}
@@ -0,0 +1,485 @@
+use super::{
+    background::Background,
+    core::Padding,
+    known::{canvas_size, total_rows},
+    policy::SizePolicy,
+    spacing::{Margin, Spacing},
+};
+use crate::{
+    Alignment, CellDecoration, Composite, FitMode, Image, KnownSizeMerger, MergeError,
+    ResizeFilter, TextStyle, UnknownSizeMerger,
+};
+
+use image::Pixel;
+use std::ops::DerefMut;
+
+/// A builder that validates a set of options and constructs a merger from them. The builders of the mergers in this
+/// crate implement this trait, so a merger can be configured by name rather than through a growing list of positional
+/// arguments.
+///
+/// # Example
+/// ```
+/// use image_merger::{KnownSizeMerger, MergerBuilder, Rgb};
+///
+/// let merger: KnownSizeMerger<Rgb<u8>, _> = KnownSizeMerger::builder((100, 100))
+///     .with_images_per_row(5)
+///     .with_total_images(10)
+///     .build();
+/// ```
+pub trait MergerBuilder: Sized {
+    /// The merger this builder constructs.
+    type Merger;
+
+    /// Validates the options of this builder and constructs the merger.
+    /// # Errors
+    /// A [MergeError](crate::MergeError) describing the first invalid option.
+    fn try_build(self) -> Result<Self::Merger, MergeError>;
+
+    /// Same as `try_build`, but panics instead of returning an error.
+    /// # Panics
+    /// This function will panic if any option is invalid. Use `try_build` to handle the error instead.
+    fn build(self) -> Self::Merger {
+        self.try_build().unwrap_or_else(|err| panic!("{}", err))
+    }
+}
+
+/// Configures and constructs a [KnownSizeMerger](crate::KnownSizeMerger). Every option other than the layout, the
+/// number of images per row and in total, starts at the same default the merger's setters use.
+///
+/// # Type Parameters
+/// * `P` - The pixel type of the canvas.
+///
+/// # Example
+/// ```
+/// use image_merger::{
+///     Alignment, KnownSizeMerger, Merger, MergerBuilder, Point, ResizeFilter, Rgb, SizePolicy,
+/// };
+///
+/// let merger: KnownSizeMerger<Rgb<u8>, _> = KnownSizeMerger::builder((100, 100))
+///     .with_images_per_row(5)
+///     .with_total_images(10)
+///     .with_padding(Point { x: 4, y: 4 })
+///     .with_margin(8)
+///     .with_background(Rgb([255, 255, 255]))
+///     .with_size_policy(SizePolicy::CenterSmaller)
+///     .with_alignment(Alignment::Bottom)
+///     .with_resize_filter(ResizeFilter::Lanczos3)
+///     .build();
+///
+/// assert_eq!(merger.get_canvas().dimensions(), (532, 220));
+/// ```
+#[derive(Debug, Clone)]
+pub struct KnownSizeMergerBuilder<P: Pixel> {
+    pub(crate) image_dimensions: (u32, u32), // The dimensions of the images being pasted.
+    pub(crate) images_per_row: u32,          // The number of images per row.
+    pub(crate) total_images: u32,            // The total number of images the canvas holds.
+    pub(crate) spacing: Spacing, // The margin around the images and the gutters between them.
+    pub(crate) background: Option<Background<P>>, // What the canvas is filled with, or None to leave it as is.
+    pub(crate) alignment: Alignment, // Where smaller images are placed inside their cells.
+    pub(crate) decoration: CellDecoration<P>, // The border and rounded corners drawn around every image.
+    pub(crate) captions: Option<TextStyle<P>>, // The style of the captions below every image, or None for no captions.
+    pub(crate) title: Option<(String, TextStyle<P>)>, // The title above the grid and its style, or None for no title.
+    pub(crate) size_policy: SizePolicy, // What to do with images that do not match the image dimensions.
+    pub(crate) composite: Composite,    // How pushed images are combined with the canvas.
+    pub(crate) resize_filter: ResizeFilter, // The filter used when images have to be resized.
+    pub(crate) fit_mode: FitMode<P>,    // How resized images keep their aspect ratio.
+}
+
+impl<P: Pixel> KnownSizeMergerBuilder<P> {
+    /// Creates a new builder for a merger of images with the given dimensions. The number of images per row and in total
+    /// must be set before building.
+    /// # Arguments
+    /// * `image_dimensions` - The dimensions of the images being pasted (images must be a uniform size)
+    pub fn new(image_dimensions: (u32, u32)) -> Self {
+        Self {
+            image_dimensions,
+            images_per_row: 0,
+            total_images: 0,
+            spacing: Spacing::default(),
+            background: None,
+            alignment: Alignment::default(),
+            decoration: CellDecoration::default(),
+            captions: None,
+            title: None,
+            size_policy: SizePolicy::default(),
+            composite: Composite::default(),
+            resize_filter: ResizeFilter::default(),
+            fit_mode: FitMode::default(),
+        }
+    }
+
+    /// Sets the number of images per row.
+    pub fn with_images_per_row(self, images_per_row: u32) -> Self {
+        Self {
+            images_per_row,
+            ..self
+        }
+    }
+
+    /// Sets the total number of images to be in the final canvas.
+    pub fn with_total_images(self, total_images: u32) -> Self {
+        Self {
+            total_images,
+            ..self
+        }
+    }
+
+    /// Sets the padding between images, the gutters of its [Spacing](crate::Spacing). By default, images are placed
+    /// right next to each other.
+    pub fn with_padding(mut self, padding: Padding) -> Self {
+        self.spacing.gutter = padding;
+        self
+    }
+
+    /// Sets the space left between the images and the edges of the canvas, either one size for every edge or a
+    /// [Margin](crate::Margin) for each. By default, there is no margin.
+    pub fn with_margin(mut self, margin: impl Into<Margin>) -> Self {
+        self.spacing.margin = margin.into();
+        self
+    }
+
+    /// Sets the margin, gutters and gutter overrides of the canvas all at once, replacing any padding or margin set
+    /// before.
+    pub fn with_spacing(self, spacing: Spacing) -> Self {
+        Self { spacing, ..self }
+    }
+
+    /// Sets what the canvas is filled with before any image is pushed, either a single color or a
+    /// [Background](crate::Background) such as a gradient or a pattern. Cells that are removed or replaced later are
+    /// drawn with it again. By default, a canvas created by `build` is zeroed, and cleared cells are zeroed again, while
+    /// the container given to `build_from_raw` is left as is, and so are its cells when they are cleared.
+    pub fn with_background(self, background: impl Into<Background<P>>) -> Self {
+        Self {
+            background: Some(background.into()),
+            ..self
+        }
+    }
+
+    /// Sets where images smaller than their cell are placed inside of it by the
+    /// [CenterSmaller](crate::SizePolicy::CenterSmaller) size policy. By default, they are centered.
+    pub fn with_alignment(self, alignment: Alignment) -> Self {
+        Self { alignment, ..self }
+    }
+
+    /// Sets the border and rounded corners drawn around every image as it is pasted. By default, images are pasted as
+    /// they are.
+    pub fn with_decoration(self, decoration: CellDecoration<P>) -> Self {
+        Self { decoration, ..self }
+    }
+
+    /// Reserves a band below every image for a caption, such as its file name or index, drawn with the given style.
+    /// The band starts below the outside border or shadow of the decoration, if any. Captions are set with
+    /// [set_caption](crate::KnownSizeMerger::set_caption) once the merger is built. By default, there are no captions.
+    pub fn with_captions(self, style: TextStyle<P>) -> Self {
+        Self {
+            captions: Some(style),
+            ..self
+        }
+    }
+
+    /// Draws a title in a band above the grid, which is added to the top margin, aligned between the left and right
+    /// margins. By default, there is no title.
+    pub fn with_title(self, title: impl Into<String>, style: TextStyle<P>) -> Self {
+        Self {
+            title: Some((title.into(), style)),
+            ..self
+        }
+    }
+
+    /// Sets the policy used for images that do not match the image dimensions. By default, such images are rejected.
+    pub fn with_size_policy(self, size_policy: SizePolicy) -> Self {
+        Self {
+            size_policy,
+            ..self
+        }
+    }
+
+    /// Sets how pushed images are combined with the canvas. By default, pushed images replace the canvas beneath them.
+    pub fn with_composite(self, composite: Composite) -> Self {
+        Self { composite, ..self }
+    }
+
+    /// Sets the filter used when images have to be resized. By default, nearest neighbor sampling is used.
+    pub fn with_resize_filter(self, resize_filter: ResizeFilter) -> Self {
+        Self {
+            resize_filter,
+            ..self
+        }
+    }
+
+    /// Sets how images pushed with `push_resized` are fitted into the image dimensions. By default, images are stretched.
+    pub fn with_fit_mode(self, fit_mode: FitMode<P>) -> Self {
+        Self { fit_mode, ..self }
+    }
+
+    /// Returns the height of the caption band below every image and of the title band above the grid, either of which
+    /// is zero if there is none.
+    pub(crate) fn text_bands(&self) -> Result<(u32, u32), MergeError> {
+        let styles = [
+            self.captions.as_ref(),
+            self.title.as_ref().map(|(_, style)| style),
+        ];
+
+        let mut bands = [0; 2];
+        for (band, style) in bands.iter_mut().zip(styles) {
+            if let Some(style) = style {
+                style.validate().map_err(MergeError::InvalidLayout)?;
+                style.advance().ok_or(MergeError::CanvasTooLarge)?;
+                *band = style.band_height().ok_or(MergeError::CanvasTooLarge)?;
+            }
+        }
+
+        Ok((bands[0], bands[1]))
+    }
+
+    /// Returns the space between every image and its caption band, which holds the bottom of the frame the decoration
+    /// draws around the image, so captions are never drawn over. Without captions, the frame reaches into the gutter.
+    pub(crate) fn caption_offset(&self, caption_band: u32) -> u32 {
+        match caption_band {
+            0 => 0,
+            _ => self.decoration.extents().bottom,
+        }
+    }
+
+    /// Returns the spacing of the grid, with the title band added to the top margin.
+    pub(crate) fn grid_spacing(&self, title_band: u32) -> Result<Spacing, MergeError> {
+        let mut spacing = self.spacing.clone();
+        spacing.margin.top = spacing
+            .margin
+            .top
+            .checked_add(title_band)
+            .ok_or(MergeError::CanvasTooLarge)?;
+
+        Ok(spacing)
+    }
+
+    /// Validates the layout, returning the number of rows and the dimensions, (width, height), of the canvas.
+    fn layout(&self) -> Result<(u32, (u32, u32)), MergeError> {
+        if self.image_dimensions.0 == 0 || self.image_dimensions.1 == 0 {
+            return Err(MergeError::InvalidLayout(
+                "image dimensions must be greater than zero",
+            ));
+        }
+
+        if let Some(Err(reason)) = self.background.as_ref().map(Background::validate) {
+            return Err(MergeError::InvalidLayout(reason));
+        }
+
+        let (caption_band, title_band) = self.text_bands()?;
+        let spacing = self.grid_spacing(title_band)?;
+        let slot_height = self
+            .image_dimensions
+            .1
+            .checked_add(self.caption_offset(caption_band))
+            .and_then(|height| height.checked_add(caption_band))
+            .ok_or(MergeError::CanvasTooLarge)?;
+
+        let total_rows = total_rows(self.images_per_row, self.total_images)?;
+        if let Some((&column, _)) = self.spacing.column_gutters.last_key_value() {
+            if column + 1 >= self.images_per_row {
+                return Err(MergeError::InvalidLayout(
+                    "column gutters can only be set between two columns",
+                ));
+            }
+        }
+        if let Some((&row, _)) = self.spacing.row_gutters.last_key_value() {
+            if row + 1 >= total_rows {
+                return Err(MergeError::InvalidLayout(
+                    "row gutters can only be set between two rows",
+                ));
+            }
+        }
+
+        self.decoration
+            .validate(&spacing, self.images_per_row, total_rows, caption_band > 0)
+            .map_err(MergeError::InvalidLayout)?;
+
+        let dimensions = canvas_size::<P>(
+            (self.image_dimensions.0, slot_height),
+            self.images_per_row,
+            total_rows,
+            &spacing,
+        )?;
+
+        Ok((total_rows, dimensions))
+    }
+
+    /// Same as `build`, but uses the given container for the canvas. This is useful if you need to use a specific
+    /// container type that is not Vec.
+    /// # Arguments
+    /// * `container` - The container to use for the underlying canvas. This container must be big enough to hold every
+    ///   image, along with the padding and margin around them.
+    ///
+    /// # Panics
+    /// This function will panic if any option is invalid. Use `try_build_from_raw` to handle the error instead.
+    pub fn build_from_raw<Container>(self, container: Container) -> KnownSizeMerger<P, Container>
+    where
+        P: Sync,
+        <P as Pixel>::Subpixel: Send + Sync,
+        Container: DerefMut<Target = [P::Subpixel]> + Sync,
+    {
+        self.try_build_from_raw(container)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Same as `build_from_raw`, but returns a [MergeError](crate::MergeError) instead of panicking when the merger
+    /// cannot be created.
+    /// # Arguments
+    /// * `container` - The container to use for the underlying canvas.
+    ///
+    /// # Errors
+    /// * [MergeError::InvalidLayout](crate::MergeError::InvalidLayout) - If the image dimensions, `images_per_row` or
+    ///   `total_images` are zero, the background cannot be drawn, a gutter is set after the last column or row, an
+    ///   outside border does not fit between the cells, or text is drawn at a scale of zero.
+    /// * [MergeError::CanvasTooLarge](crate::MergeError::CanvasTooLarge) - If the canvas dimensions overflow.
+    /// * [MergeError::BufferTooSmall](crate::MergeError::BufferTooSmall) - If the container cannot hold the canvas.
+    pub fn try_build_from_raw<Container>(
+        self,
+        container: Container,
+    ) -> Result<KnownSizeMerger<P, Container>, MergeError>
+    where
+        P: Sync,
+        <P as Pixel>::Subpixel: Send + Sync,
+        Container: DerefMut<Target = [P::Subpixel]> + Sync,
+    {
+        let (total_rows, (width, height)) = self.layout()?;
+
+        let required = width as usize * height as usize * <P as Pixel>::CHANNEL_COUNT as usize;
+        let actual = container.len();
+
+        let canvas = Image::new_from_raw(width, height, container)
+            .ok_or(MergeError::BufferTooSmall { required, actual })?;
+
+        Ok(KnownSizeMerger::from_builder(
+            self, canvas, total_rows, false,
+        ))
+    }
+}
+
+impl<P> MergerBuilder for KnownSizeMergerBuilder<P>
+where
+    P: Pixel + Sync,
+    <P as Pixel>::Subpixel: Send + Sync,
+{
+    type Merger = KnownSizeMerger<P, Vec<P::Subpixel>>;
+
+    /// Validates the options of this builder and constructs the merger, with a `Vec` as the container of its canvas.
+    /// # Errors
+    /// * [MergeError::InvalidLayout](crate::MergeError::InvalidLayout) - If the image dimensions, `images_per_row` or
+    ///   `total_images` are zero, the background cannot be drawn, a gutter is set after the last column or row, an
+    ///   outside border does not fit between the cells, or text is drawn at a scale of zero.
+    /// * [MergeError::CanvasTooLarge](crate::MergeError::CanvasTooLarge) - If the canvas dimensions overflow.
+    fn try_build(self) -> Result<Self::Merger, MergeError> {
+        let (total_rows, (width, height)) = self.layout()?;
+
+        Ok(KnownSizeMerger::from_builder(
+            self,
+            Image::new(width, height),
+            total_rows,
+            true,
+        ))
+    }
+}
+
+/// Configures and constructs an [UnknownSizeMerger](crate::UnknownSizeMerger). The options that can be changed after
+/// the canvas is created, such as the size policy, are set on the merger itself.
+///
+/// # Type Parameters
+/// * `P` - The pixel type of the canvas.
+///
+/// # Example
+/// ```
+/// use image_merger::{Background, Merger, MergerBuilder, Point, Rgb, UnknownSizeMerger};
+///
+/// let mut merger: UnknownSizeMerger<Rgb<u8>> = UnknownSizeMerger::builder((100, 100))
+///     .with_images_per_row(5)
+///     .with_capacity(20)
+///     .with_padding(Point { x: 4, y: 4 })
+///     .with_background(Background::LinearGradient {
+///         start: Rgb([255, 255, 255]),
+///         end: Rgb([0, 0, 0]),
+///         angle: 90.0,
+///     })
+///     .build();
+///
+/// merger.push(&image_merger::Image::new(100, 100));
+/// ```
+#[derive(Debug, Clone)]
+pub struct UnknownSizeMergerBuilder<P: Pixel> {
+    inner: KnownSizeMergerBuilder<P>, // The options of the canvas, which starts with room for `capacity` images.
+    capacity: u32, // The number of images the canvas holds before it has to grow.
+}
+
+impl<P: Pixel> UnknownSizeMergerBuilder<P> {
+    /// Creates a new builder for a merger of images with the given dimensions. The number of images per row must be set
+    /// before building.
+    /// # Arguments
+    /// * `image_dimensions` - The dimensions of the images being pasted (images must be a uniform size)
+    pub fn new(image_dimensions: (u32, u32)) -> Self {
+        Self {
+            inner: KnownSizeMergerBuilder::new(image_dimensions),
+            capacity: 0,
+        }
+    }
+
+    /// Sets the number of images per row.
+    pub fn with_images_per_row(self, images_per_row: u32) -> Self {
+        Self {
+            inner: self.inner.with_images_per_row(images_per_row),
+            ..self
+        }
+    }
+
+    /// Sets the number of images the canvas can hold before it has to grow. By default, it holds a single row.
+    pub fn with_capacity(self, capacity: u32) -> Self {
+        Self { capacity, ..self }
+    }
+
+    /// Sets the padding between images. By default, images are placed right next to each other.
+    pub fn with_padding(self, padding: Padding) -> Self {
+        Self {
+            inner: self.inner.with_padding(padding),
+            ..self
+        }
+    }
+
+    /// Sets the space left between the images and the edges of the canvas, either one size for every edge or a
+    /// [Margin](crate::Margin) for each. By default, there is no margin.
+    pub fn with_margin(self, margin: impl Into<Margin>) -> Self {
+        Self {
+            inner: self.inner.with_margin(margin),
+            ..self
+        }
+    }
+
+    /// Sets what the canvas is filled with where no image has been pasted, including the rows it grows by and cells that
+    /// are removed later. By default, the canvas is zeroed. Gradients are stretched across the canvas the merger is
+    /// built with, and continue past it as the canvas grows, so set a capacity close to the number of images expected.
+    pub fn with_background(self, background: impl Into<Background<P>>) -> Self {
+        Self {
+            inner: self.inner.with_background(background),
+            ..self
+        }
+    }
+}
+
+impl<P> MergerBuilder for UnknownSizeMergerBuilder<P>
+where
+    P: Pixel + Sync,
+    <P as Pixel>::Subpixel: Send + Sync,
+{
+    type Merger = UnknownSizeMerger<P>;
+
+    /// Validates the options of this builder and constructs the merger.
+    /// # Errors
+    /// * [MergeError::InvalidLayout](crate::MergeError::InvalidLayout) - If the image dimensions or `images_per_row` are
+    ///   zero, or the background cannot be drawn.
+    /// * [MergeError::CanvasTooLarge](crate::MergeError::CanvasTooLarge) - If the canvas dimensions overflow.
+    fn try_build(self) -> Result<Self::Merger, MergeError> {
+        // The known size merger always needs at least one row to build its canvas.
+        let capacity = self.capacity.max(1);
+        let inner = self.inner.with_total_images(capacity).try_build()?;
+
+        Ok(UnknownSizeMerger::from_inner(inner))
+    }
+}
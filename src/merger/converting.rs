@@ -0,0 +1,295 @@
+use crate::{ImageView, MergeError};
+
+use image::{
+    buffer::ConvertBuffer, DynamicImage, ImageBuffer, Luma, LumaA, Pixel, Primitive, Rgb, Rgba,
+};
+use num_traits::NumCast;
+
+/// A pixel type that can be converted into the pixel type `P` of a canvas. Conversions follow the image crate: channels
+/// are added or dropped as needed, for example an opaque alpha channel is added when converting `Rgb` into `Rgba`, and
+/// subpixels are rescaled between bit depths, so a `u16` of 65535 or an `f32` of 1.0 both become a `u8` of 255.
+///
+/// This trait is implemented for every pair of pixel types the image crate can convert between, and does not need to be
+/// implemented by hand.
+///
+/// # Type Parameters
+/// * `P` - The pixel type to convert into.
+pub trait ConvertPixel<P: Pixel>: Pixel {
+    /// Converts a single pixel of this type into a `P`.
+    /// # Arguments
+    /// * `pixel` - The subpixels of the pixel to convert.
+    /// * `converted` - Where to write the subpixels of the converted pixel.
+    fn convert_pixel(pixel: &[Self::Subpixel], converted: &mut [P::Subpixel]);
+
+    /// Converts a row of pixels of this type into a row of `P`s.
+    /// # Arguments
+    /// * `row` - The subpixels of the row to convert.
+    /// * `converted` - Where to write the subpixels of the converted row. Must hold exactly as many pixels as `row`.
+    fn convert_row(row: &[Self::Subpixel], converted: &mut [P::Subpixel]) {
+        for (pixel, converted) in row
+            .chunks_exact(<Self as Pixel>::CHANNEL_COUNT as usize)
+            .zip(converted.chunks_exact_mut(<P as Pixel>::CHANNEL_COUNT as usize))
+        {
+            Self::convert_pixel(pixel, converted);
+        }
+    }
+}
+
+// The image crate's own conversions are only reachable through `ConvertBuffer`, which allocates a new buffer for every
+// call, so they are mirrored here and the bound only limits the implementation to the pairs the image crate supports.
+impl<P, Q> ConvertPixel<P> for Q
+where
+    P: Pixel,
+    Q: Pixel,
+    for<'a> ImageBuffer<Q, &'a [Q::Subpixel]>: ConvertBuffer<ImageBuffer<P, Vec<P::Subpixel>>>,
+{
+    fn convert_pixel(pixel: &[Self::Subpixel], converted: &mut [P::Subpixel]) {
+        let (has_color, has_alpha) = color_model::<Q>();
+        let (has_converted_color, has_converted_alpha) = color_model::<P>();
+
+        match (has_color, has_converted_color) {
+            (true, true) => {
+                for (converted, &subpixel) in converted.iter_mut().zip(&pixel[..3]) {
+                    *converted = convert_subpixel(subpixel);
+                }
+            }
+            (false, true) => converted[..3].fill(convert_subpixel(pixel[0])),
+            (true, false) => converted[0] = convert_subpixel(luma(&pixel[..3])),
+            (false, false) => converted[0] = convert_subpixel(pixel[0]),
+        }
+
+        if has_converted_alpha {
+            converted[converted.len() - 1] = if has_alpha {
+                convert_subpixel(pixel[pixel.len() - 1])
+            } else {
+                P::Subpixel::DEFAULT_MAX_VALUE
+            };
+        }
+    }
+}
+
+/// Returns whether a pixel type has color channels, rather than a single luma channel, and whether it has an alpha
+/// channel, following the pixel types of the image crate.
+fn color_model<P: Pixel>() -> (bool, bool) {
+    let channels = <P as Pixel>::CHANNEL_COUNT;
+    (channels >= 3, channels % 2 == 0)
+}
+
+/// Whether a subpixel type holds floating point values between 0 and 1, rather than integers up to its maximum.
+fn is_float<T: Primitive>() -> bool {
+    T::DEFAULT_MAX_VALUE.to_f64() == Some(1.0)
+}
+
+/// Computes the sRGB luminance of a color, in its own subpixel type. Integer luminance is rounded down.
+fn luma<T: Primitive>(rgb: &[T]) -> T {
+    const WEIGHTS: [u32; 3] = [2126, 7152, 722];
+
+    if is_float::<T>() {
+        let sum: f64 = rgb
+            .iter()
+            .zip(WEIGHTS)
+            .map(|(subpixel, weight)| subpixel.to_f64().unwrap_or(0.0) * weight as f64)
+            .sum();
+        NumCast::from(sum / 10000.0).unwrap_or(T::DEFAULT_MAX_VALUE)
+    } else {
+        let sum: i128 = rgb
+            .iter()
+            .zip(WEIGHTS)
+            .map(|(subpixel, weight)| subpixel.to_i128().unwrap_or(0) * weight as i128)
+            .sum();
+        NumCast::from(sum / 10000).unwrap_or(T::DEFAULT_MAX_VALUE)
+    }
+}
+
+/// Rescales a subpixel into another subpixel type, rounding to the nearest value.
+fn convert_subpixel<S: Primitive, T: Primitive>(subpixel: S) -> T {
+    let (max, converted_max) = (S::DEFAULT_MAX_VALUE, T::DEFAULT_MAX_VALUE);
+    if max.to_f64() == converted_max.to_f64() {
+        return NumCast::from(subpixel).unwrap_or(converted_max);
+    }
+
+    let value = if is_float::<T>() {
+        let value = subpixel.to_f32().unwrap_or(0.0) / max.to_f32().unwrap_or(1.0);
+        value.clamp(0.0, 1.0) as f64
+    } else if is_float::<S>() {
+        let value = subpixel.to_f32().unwrap_or(0.0).clamp(0.0, 1.0);
+        (value * converted_max.to_f32().unwrap_or(0.0)).round() as f64
+    } else {
+        let scale = converted_max.to_f64().unwrap_or(0.0) / max.to_f64().unwrap_or(1.0);
+        (subpixel.to_f64().unwrap_or(0.0) * scale).round()
+    };
+
+    NumCast::from(value).unwrap_or(converted_max)
+}
+
+/// A canvas pixel type that every pixel type of a `DynamicImage` can be converted into, which allows
+/// [push_dynamic](ConvertingMerger::push_dynamic) to push decoded images without knowing their pixel type up front.
+///
+/// This trait is implemented for every such pixel type, including `Rgb` and `Rgba` of `u8`, `u16` and `f32`, and does
+/// not need to be implemented by hand.
+pub trait DynamicPixel: Pixel + Sync {
+    /// Pushes the buffer of a `DynamicImage` onto the canvas of a merger, converting it into this pixel type.
+    /// # Arguments
+    /// * `merger` - The merger to push the image onto.
+    /// * `image` - The image to push.
+    /// # Errors
+    /// Any error `try_push_converted` returns for the buffer of the image.
+    fn push_dynamic_onto<M>(merger: &mut M, image: &DynamicImage) -> Result<(), MergeError>
+    where
+        M: ConvertingMerger<Self> + ?Sized,
+        <Self as Pixel>::Subpixel: Sync;
+}
+
+impl<P> DynamicPixel for P
+where
+    P: Pixel + Sync,
+    Luma<u8>: ConvertPixel<P>,
+    LumaA<u8>: ConvertPixel<P>,
+    Rgb<u8>: ConvertPixel<P>,
+    Rgba<u8>: ConvertPixel<P>,
+    Luma<u16>: ConvertPixel<P>,
+    LumaA<u16>: ConvertPixel<P>,
+    Rgb<u16>: ConvertPixel<P>,
+    Rgba<u16>: ConvertPixel<P>,
+    Rgb<f32>: ConvertPixel<P>,
+    Rgba<f32>: ConvertPixel<P>,
+{
+    fn push_dynamic_onto<M>(merger: &mut M, image: &DynamicImage) -> Result<(), MergeError>
+    where
+        M: ConvertingMerger<Self> + ?Sized,
+        <Self as Pixel>::Subpixel: Sync,
+    {
+        match image {
+            DynamicImage::ImageLuma8(buffer) => merger.try_push_converted(buffer),
+            DynamicImage::ImageLumaA8(buffer) => merger.try_push_converted(buffer),
+            DynamicImage::ImageRgb8(buffer) => merger.try_push_converted(buffer),
+            DynamicImage::ImageRgba8(buffer) => merger.try_push_converted(buffer),
+            DynamicImage::ImageLuma16(buffer) => merger.try_push_converted(buffer),
+            DynamicImage::ImageLumaA16(buffer) => merger.try_push_converted(buffer),
+            DynamicImage::ImageRgb16(buffer) => merger.try_push_converted(buffer),
+            DynamicImage::ImageRgba16(buffer) => merger.try_push_converted(buffer),
+            DynamicImage::ImageRgb32F(buffer) => merger.try_push_converted(buffer),
+            DynamicImage::ImageRgba32F(buffer) => merger.try_push_converted(buffer),
+            // DynamicImage is non exhaustive, so convert any future variant through the widest pixel type.
+            _ => merger.try_push_converted(&image.to_rgba32f()),
+        }
+    }
+}
+
+/// A trait that allows a Merger to push images whose pixel type differs from the canvas, converting every image into the
+/// pixel type of the canvas as it is pasted. This saves converting, and holding, a copy of every image before pushing it,
+/// such as when pasting `Rgb<u8>` photos onto an `Rgba<u8>` canvas, or 16 bit scans onto an 8 bit canvas.
+///
+/// Converted images are fitted into their cells, and combined with the canvas, the same way `push` does.
+///
+/// # Type Parameters
+/// * `P` - The pixel type of the canvas.
+///
+/// # Example
+/// ```
+/// use image_merger::{ConvertingMerger, Image, KnownSizeMerger, Luma, Rgb, Rgba};
+///
+/// let mut merger: KnownSizeMerger<Rgba<u8>, _> = KnownSizeMerger::new((100, 100), 5, 10, None);
+///
+/// let photo: image_merger::BufferedImage<Rgb<u8>> = Image::new(100, 100);
+/// let scan: image_merger::BufferedImage<Luma<u16>> = Image::new(100, 100);
+/// merger.push_converted(&photo);
+/// merger.push_converted(&scan);
+/// ```
+pub trait ConvertingMerger<P>
+where
+    P: Pixel + Sync,
+    <P as Pixel>::Subpixel: Sync,
+{
+    /// Pushes an image of any convertible pixel type onto the canvas, converting it into the pixel type of the canvas.
+    /// # Arguments
+    /// * `image` - The image to push onto the canvas, which can be any [ImageView](crate::ImageView). Its pixel type, `Q`,
+    ///   must convert into the pixel type of the canvas. Other `GenericImageView`s must be wrapped in a
+    ///   [GenericView](crate::GenericView), or pushed with [push_dynamic](ConvertingMerger::push_dynamic) if they are a
+    ///   `DynamicImage`.
+    /// # Panics
+    /// This function will panic if the image cannot be pushed onto the canvas. Use `try_push_converted` to handle the error
+    /// instead.
+    fn push_converted<Q, V>(&mut self, image: &V)
+    where
+        Q: ConvertPixel<P> + Sync,
+        <Q as Pixel>::Subpixel: Send + Sync,
+        V: ImageView<Pixel = Q>,
+    {
+        if let Err(err) = self.try_push_converted(image) {
+            panic!("{}", err);
+        }
+    }
+
+    /// Bulk pushes N images of any convertible pixel type onto the canvas, converting them into the pixel type of the
+    /// canvas while they are pasted in parallel.
+    /// # Arguments
+    /// * `images` - The images to push onto the canvas. Their pixel type, `Q`, must convert into the pixel type of the
+    ///   canvas.
+    /// # Panics
+    /// This function will panic if the images cannot be pushed onto the canvas. Use `try_bulk_push_converted` to handle
+    /// the error instead.
+    fn bulk_push_converted<Q, V>(&mut self, images: &[&V])
+    where
+        Q: ConvertPixel<P> + Sync,
+        <Q as Pixel>::Subpixel: Send + Sync,
+        V: ImageView<Pixel = Q>,
+    {
+        if let Err(err) = self.try_bulk_push_converted(images) {
+            panic!("{}", err);
+        }
+    }
+
+    /// Same as `push_converted`, but returns a [MergeError](crate::MergeError) instead of panicking when the image cannot
+    /// be pushed.
+    /// # Arguments
+    /// * `image` - The image to push onto the canvas.
+    /// # Errors
+    /// Any error `try_push` returns for an image of the same dimensions.
+    fn try_push_converted<Q, V>(&mut self, image: &V) -> Result<(), MergeError>
+    where
+        Q: ConvertPixel<P> + Sync,
+        <Q as Pixel>::Subpixel: Send + Sync,
+        V: ImageView<Pixel = Q>;
+
+    /// Same as `bulk_push_converted`, but returns a [MergeError](crate::MergeError) instead of panicking when the images
+    /// cannot be pushed. No images are pasted if an error is returned.
+    /// # Arguments
+    /// * `images` - The images to push onto the canvas.
+    /// # Errors
+    /// Any error `try_bulk_push` returns for images of the same dimensions.
+    fn try_bulk_push_converted<Q, V>(&mut self, images: &[&V]) -> Result<(), MergeError>
+    where
+        Q: ConvertPixel<P> + Sync,
+        <Q as Pixel>::Subpixel: Send + Sync,
+        V: ImageView<Pixel = Q>;
+
+    /// Pushes a `DynamicImage`, such as one returned by `image::open`, onto the canvas, converting it from whatever
+    /// pixel type it was decoded as into the pixel type of the canvas.
+    /// # Arguments
+    /// * `image` - The image to push onto the canvas.
+    /// # Panics
+    /// This function will panic if the image cannot be pushed onto the canvas. Use `try_push_dynamic` to handle the error
+    /// instead.
+    fn push_dynamic(&mut self, image: &DynamicImage)
+    where
+        P: DynamicPixel,
+    {
+        if let Err(err) = self.try_push_dynamic(image) {
+            panic!("{}", err);
+        }
+    }
+
+    /// Same as `push_dynamic`, but returns a [MergeError](crate::MergeError) instead of panicking when the image cannot
+    /// be pushed.
+    /// # Arguments
+    /// * `image` - The image to push onto the canvas.
+    /// # Errors
+    /// Any error `try_push` returns for an image of the same dimensions.
+    fn try_push_dynamic(&mut self, image: &DynamicImage) -> Result<(), MergeError>
+    where
+        P: DynamicPixel,
+    {
+        P::push_dynamic_onto(self, image)
+    }
+}
@@ -1,14 +1,38 @@
-use crate::core::Image;
+use crate::{core::Image, ImageView, MergeError};
 use image::Pixel;
 use std::{marker::Sync, ops::DerefMut};
 
-/// Represents a point on any canvas.
+/// Represents a point on any canvas, in pixels from its top left corner.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Point {
+    /// The distance from the left edge of the canvas, in pixels.
     pub x: u32,
+    /// The distance from the top edge of the canvas, in pixels.
     pub y: u32,
 }
 
+/// Represents a rectangular area on any canvas, with its top left corner at (`x`, `y`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rect {
+    /// The distance of the left edge of the area from the left edge of the canvas, in pixels.
+    pub x: u32,
+    /// The distance of the top edge of the area from the top edge of the canvas, in pixels.
+    pub y: u32,
+    /// The width of the area, in pixels.
+    pub width: u32,
+    /// The height of the area, in pixels.
+    pub height: u32,
+}
+
+/// Represents where an image was placed on a canvas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Placement {
+    /// The area of the canvas the image covers.
+    pub rect: Rect,
+    /// Whether the image was rotated 90 degrees clockwise to fit, in which case `rect` holds the rotated dimensions.
+    pub rotated: bool,
+}
+
 /// Represents the padding between images on a canvas.
 /// # Fields
 /// * `x` - The padding between images on the x axis.
@@ -33,15 +57,57 @@ where
     /// Allows the merger to push an image to the canvas. This can be used in a loop to paste a large number of images without
     /// having to hold all them in memory.
     /// # Arguments
-    /// * `image` - The image to push onto the canvas. Its pixel type, `P`, must match the canvas, and its `Container` must be dereferenceable to
-    /// a slice of `P::Subpixel`s.
-    fn push(&mut self, image: &Image<P, image::ImageBuffer<P, Container>>);
+    /// * `image` - The image to push onto the canvas. This can be any [ImageView](crate::ImageView) whose pixel type, `P`,
+    ///   matches the canvas, such as an [Image](crate::Image), an `ImageBuffer` with any container or a `SubImage` of one.
+    ///   Other `GenericImageView`s, such as a `DynamicImage`, are not `ImageView`s themselves and must be wrapped in a
+    ///   [GenericView](crate::GenericView) to be pushed.
+    /// # Panics
+    /// This function will panic if the image cannot be pushed onto the canvas. Use `try_push` to handle the error instead.
+    fn push<V>(&mut self, image: &V)
+    where
+        V: ImageView<Pixel = P>,
+    {
+        if let Err(err) = self.try_push(image) {
+            panic!("{}", err);
+        }
+    }
 
     /// Allows the merger to bulk push N images to the canvas. This is useful for when you have a large number of images to paste.
     /// The downside is that you have to hold all of the images in memory at once, which can be a problem if you have a large number of images.
     /// # Arguments
-    /// * `images` - The images to push onto the canvas. Note that the argument type is `&[&Image<...>]`, the func
-    /// does not need to take ownership of the images, it only needs to read them. The pixel type, `P`, of the images must match the canvas, and
-    /// their `Container` must be dereferenceable to a slice of `P::Subpixel`s.
-    fn bulk_push(&mut self, images: &[&Image<P, image::ImageBuffer<P, Container>>]);
+    /// * `images` - The images to push onto the canvas. Note that the argument type is `&[&V]`, the func does not need to
+    ///   take ownership of the images, it only needs to read them. The images can be any [ImageView](crate::ImageView) whose
+    ///   pixel type, `P`, matches the canvas. Other `GenericImageView`s must be wrapped in a
+    ///   [GenericView](crate::GenericView), as with `push`.
+    /// # Panics
+    /// This function will panic if the images cannot be pushed onto the canvas. Use `try_bulk_push` to handle the error instead.
+    fn bulk_push<V>(&mut self, images: &[&V])
+    where
+        V: ImageView<Pixel = P>,
+    {
+        if let Err(err) = self.try_bulk_push(images) {
+            panic!("{}", err);
+        }
+    }
+
+    /// Same as `push`, but returns a [MergeError](crate::MergeError) instead of panicking when the image cannot be pushed.
+    /// # Arguments
+    /// * `image` - The image to push onto the canvas.
+    /// # Errors
+    /// * [MergeError::CanvasFull](crate::MergeError::CanvasFull) - If there is no space left on the canvas.
+    /// * [MergeError::DimensionMismatch](crate::MergeError::DimensionMismatch) - If the image does not match the merger's image dimensions.
+    fn try_push<V>(&mut self, image: &V) -> Result<(), MergeError>
+    where
+        V: ImageView<Pixel = P>;
+
+    /// Same as `bulk_push`, but returns a [MergeError](crate::MergeError) instead of panicking when the images cannot be pushed.
+    /// No images are pasted if an error is returned.
+    /// # Arguments
+    /// * `images` - The images to push onto the canvas.
+    /// # Errors
+    /// * [MergeError::CanvasFull](crate::MergeError::CanvasFull) - If there is not enough space left on the canvas for all the images.
+    /// * [MergeError::DimensionMismatch](crate::MergeError::DimensionMismatch) - If any image does not match the merger's image dimensions.
+    fn try_bulk_push<V>(&mut self, images: &[&V]) -> Result<(), MergeError>
+    where
+        V: ImageView<Pixel = P>;
 }
@@ -0,0 +1,353 @@
+use super::{
+    core::Rect,
+    spacing::{Margin, Spacing},
+};
+
+use image::Pixel;
+use std::collections::BTreeMap;
+
+/// Where a [Border](crate::Border) is drawn relative to the edges of its cell.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BorderPlacement {
+    /// The border is drawn over the outer pixels of the cell, on top of the image.
+    #[default]
+    Inside,
+    /// The border is drawn around the cell, in the margin and gutters, so the whole image stays visible. The margin and
+    /// half of every gutter must be at least as wide as the border.
+    Outside,
+}
+
+/// A border drawn around every image of a merger.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Border<P: Pixel> {
+    /// The width of the border, in pixels.
+    pub width: u32,
+    /// The color of the border.
+    pub color: P,
+    /// Whether the border lies inside or outside of the cell.
+    pub placement: BorderPlacement,
+}
+
+/// How a [Shadow](crate::Shadow) is blurred.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BlurFilter {
+    /// A single box blur, which is the fastest, but leaves the shadow with hard, square edges.
+    Box,
+    /// An approximation of a Gaussian blur made of three box blurs, which fades out smoothly.
+    #[default]
+    Gaussian,
+}
+
+/// A soft shadow cast by every image of a merger onto the canvas beneath it. A shadow without an offset and with a
+/// light color makes a glow.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Shadow<P: Pixel> {
+    /// How far the shadow is moved from the image, (x, y), in pixels.
+    pub offset: (i32, i32),
+    /// How far the edges of the shadow spread out, in pixels.
+    pub blur_radius: u32,
+    /// The color of the shadow.
+    pub color: P,
+    /// The opacity of the shadow where it is fully covered, from `0.0` to `1.0`.
+    pub opacity: f32,
+    /// How the shadow is blurred.
+    pub filter: BlurFilter,
+}
+
+impl<P: Pixel> Shadow<P> {
+    /// Creates a drop shadow with a Gaussian blur.
+    /// # Arguments
+    /// * `offset` - How far the shadow is moved from the image, (x, y), in pixels.
+    /// * `blur_radius` - How far the edges of the shadow spread out, in pixels.
+    /// * `color` - The color of the shadow.
+    /// * `opacity` - The opacity of the shadow, clamped between `0.0` and `1.0`.
+    pub fn new(offset: (i32, i32), blur_radius: u32, color: P, opacity: f32) -> Self {
+        Self {
+            offset,
+            blur_radius,
+            color,
+            opacity: opacity.clamp(0.0, 1.0),
+            filter: BlurFilter::default(),
+        }
+    }
+
+    /// Creates a glow, a shadow that spreads out evenly on every side of the image.
+    pub fn glow(blur_radius: u32, color: P, opacity: f32) -> Self {
+        Self::new((0, 0), blur_radius, color, opacity)
+    }
+
+    /// Returns this shadow with its blur filter set to the given value.
+    pub fn with_filter(self, filter: BlurFilter) -> Self {
+        Self { filter, ..self }
+    }
+}
+
+/// Decorates every cell of a merger as its image is pasted, with a border, rounded corners and a shadow. All of them
+/// are applied in the same pass that writes the image, rather than over the canvas afterwards, and the edges of rounded
+/// corners are anti-aliased against the background beneath them. The shadow is composited onto the canvas first, so
+/// the border and the image lie on top of it.
+///
+/// # Type Parameters
+/// * `P` - The pixel type of the canvas.
+///
+/// # Example
+/// ```
+/// use image_merger::{
+///     BorderPlacement, CellDecoration, KnownSizeMerger, Merger, MergerBuilder, Point, Rgb,
+/// };
+///
+/// let decoration = CellDecoration::default()
+///     .with_border(2, Rgb([0, 0, 0]), BorderPlacement::Outside)
+///     .with_corner_radius(12);
+///
+/// let mut merger: KnownSizeMerger<Rgb<u8>, _> = KnownSizeMerger::builder((100, 100))
+///     .with_images_per_row(5)
+///     .with_total_images(10)
+///     .with_padding(Point { x: 4, y: 4 })
+///     .with_margin(2)
+///     .with_background(Rgb([255, 255, 255]))
+///     .with_decoration(decoration)
+///     .build();
+///
+/// merger.push(&image_merger::BufferedImage::new_from_pixel(100, 100, Rgb([255, 0, 0])));
+/// assert_eq!(*merger.get_canvas().get_pixel(50, 1), Rgb([0, 0, 0]));
+/// assert_eq!(*merger.get_canvas().get_pixel(2, 2), Rgb([255, 255, 255]));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CellDecoration<P: Pixel> {
+    /// The border around every image, or None for no border.
+    pub border: Option<Border<P>>,
+    /// The radius of the rounded corners of every image, or 0 for square corners.
+    pub corner_radius: u32,
+    /// The shadow cast by every image, or None for no shadow.
+    pub shadow: Option<Shadow<P>>,
+}
+
+impl<P: Pixel> Default for CellDecoration<P> {
+    fn default() -> Self {
+        Self {
+            border: None,
+            corner_radius: 0,
+            shadow: None,
+        }
+    }
+}
+
+/// A rectangle with rounded corners, in canvas coordinates.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RoundedRect {
+    left: f32,
+    top: f32,
+    right: f32,
+    bottom: f32,
+    radius: f32,
+}
+
+impl RoundedRect {
+    fn new(x: i64, y: i64, width: i64, height: i64, radius: u32) -> Self {
+        let (width, height) = (width.max(0) as f32, height.max(0) as f32);
+
+        Self {
+            left: x as f32,
+            top: y as f32,
+            right: x as f32 + width,
+            bottom: y as f32 + height,
+            radius: (radius as f32).min(width / 2.0).min(height / 2.0),
+        }
+    }
+
+    /// Returns how much of the pixel at the given coordinates lies within the rectangle, from `0.0` to `1.0`.
+    pub(crate) fn coverage(&self, x: u32, y: u32) -> f32 {
+        let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+        if px < self.left || px > self.right || py < self.top || py > self.bottom {
+            return 0.0;
+        }
+
+        // Only pixels in a corner lie away from the inner rectangle the corner circles are centered on.
+        let cx = px.clamp(self.left + self.radius, self.right - self.radius);
+        let cy = py.clamp(self.top + self.radius, self.bottom - self.radius);
+        let distance = (px - cx).hypot(py - cy);
+        if distance == 0.0 {
+            return 1.0;
+        }
+
+        (self.radius - distance + 0.5).clamp(0.0, 1.0)
+    }
+}
+
+impl<P: Pixel> CellDecoration<P> {
+    /// Returns this decoration with a border of the given width, color and placement.
+    pub fn with_border(self, width: u32, color: P, placement: BorderPlacement) -> Self {
+        Self {
+            border: Some(Border {
+                width,
+                color,
+                placement,
+            }),
+            ..self
+        }
+    }
+
+    /// Returns this decoration with the corners of every image rounded off to the given radius.
+    pub fn with_corner_radius(self, corner_radius: u32) -> Self {
+        Self {
+            corner_radius,
+            ..self
+        }
+    }
+
+    /// Returns this decoration with every image casting the given shadow, such as a drop shadow or a glow.
+    pub fn with_shadow(self, shadow: Shadow<P>) -> Self {
+        Self {
+            shadow: Some(shadow),
+            ..self
+        }
+    }
+
+    /// Returns true if the decoration leaves images as they are.
+    pub(crate) fn is_plain(&self) -> bool {
+        self.corner_radius == 0
+            && self.border.is_none_or(|border| border.width == 0)
+            && self.shadow.is_none_or(|shadow| shadow.opacity <= 0.0)
+    }
+
+    /// Returns the width of the border outside of the cell, if any.
+    pub(crate) fn outset(&self) -> u32 {
+        match self.border {
+            Some(Border {
+                width,
+                placement: BorderPlacement::Outside,
+                ..
+            }) => width,
+            _ => 0,
+        }
+    }
+
+    /// Returns how far the decoration reaches past each edge of its cell, with an outside border or a shadow.
+    pub(crate) fn extents(&self) -> Margin {
+        let outset = self.outset();
+        let shadow = match self.shadow {
+            Some(shadow) if shadow.opacity > 0.0 => shadow,
+            _ => return Margin::uniform(outset),
+        };
+
+        // The shadow is cast by the image and its border, moved by the offset and spread out by the blur.
+        let (x, y) = (shadow.offset.0 as i64, shadow.offset.1 as i64);
+        let reach = |shift: i64| {
+            (outset as i64 + shadow.blur_radius as i64 + shift).max(outset as i64) as u32
+        };
+
+        Margin {
+            top: reach(-y),
+            right: reach(x),
+            bottom: reach(y),
+            left: reach(-x),
+        }
+    }
+
+    /// Returns the area of the canvas the decorated cell covers, which is larger than the cell for outside borders
+    /// and shadows.
+    pub(crate) fn frame(&self, cell: Rect) -> Rect {
+        let extents = self.extents();
+
+        Rect {
+            x: cell.x.saturating_sub(extents.left),
+            y: cell.y.saturating_sub(extents.top),
+            width: cell.width + extents.left + extents.right,
+            height: cell.height + extents.top + extents.bottom,
+        }
+    }
+
+    /// Returns the shape the image is masked to, and the shape of the border with its color, if there is one.
+    pub(crate) fn shapes(&self, cell: Rect) -> (RoundedRect, Option<(RoundedRect, P)>) {
+        let (x, y) = (cell.x as i64, cell.y as i64);
+        let (width, height) = (cell.width as i64, cell.height as i64);
+        let radius = self.corner_radius;
+
+        match self.border {
+            Some(border) if border.width > 0 => {
+                let w = border.width as i64;
+                match border.placement {
+                    BorderPlacement::Inside => (
+                        RoundedRect::new(
+                            x + w,
+                            y + w,
+                            width - 2 * w,
+                            height - 2 * w,
+                            radius.saturating_sub(border.width),
+                        ),
+                        Some((RoundedRect::new(x, y, width, height, radius), border.color)),
+                    ),
+                    BorderPlacement::Outside => {
+                        // The outer edge of the border runs parallel to the rounded corner of the image.
+                        let outer_radius = if radius > 0 { radius + border.width } else { 0 };
+
+                        (
+                            RoundedRect::new(x, y, width, height, radius),
+                            Some((
+                                RoundedRect::new(
+                                    x - w,
+                                    y - w,
+                                    width + 2 * w,
+                                    height + 2 * w,
+                                    outer_radius,
+                                ),
+                                border.color,
+                            )),
+                        )
+                    }
+                }
+            }
+            _ => (RoundedRect::new(x, y, width, height, radius), None),
+        }
+    }
+
+    /// Returns an error message if an outside border or a shadow does not fit in the margin or gutters of the given
+    /// layout, where it would be clipped or overlap the decoration of a neighbouring cell. With captions, the bottom of
+    /// the frame lies above the caption band of its cell rather than in the gutter below.
+    pub(crate) fn validate(
+        &self,
+        spacing: &Spacing,
+        columns: u32,
+        rows: u32,
+        captioned: bool,
+    ) -> Result<(), &'static str> {
+        let mut extents = self.extents();
+        if captioned {
+            extents.bottom = 0;
+        }
+        if extents == Margin::default() {
+            return Ok(());
+        }
+
+        let margin = spacing.margin;
+        let fits_margin = margin.top >= extents.top
+            && margin.right >= extents.right
+            && margin.bottom >= extents.bottom
+            && margin.left >= extents.left;
+
+        // Only the gutters between the columns or rows of the layout matter, and the default gutter only if one of
+        // them is not overridden.
+        let fits_gutters =
+            |count: u32, gutter: u32, overrides: &BTreeMap<u32, u32>, needed: u32| {
+                let gaps = count.saturating_sub(1);
+                let overridden = overrides.range(..gaps);
+
+                (overridden.clone().count() as u32 == gaps || gutter >= needed)
+                    && overridden.into_iter().all(|(_, &gap)| gap >= needed)
+            };
+
+        let needed_x = extents.left + extents.right;
+        let needed_y = extents.top + extents.bottom;
+        if !fits_margin
+            || !fits_gutters(columns, spacing.gutter.x, &spacing.column_gutters, needed_x)
+            || !fits_gutters(rows, spacing.gutter.y, &spacing.row_gutters, needed_y)
+        {
+            return Err(
+                "outside borders and shadows must fit in the margin and gutters around every cell",
+            );
+        }
+
+        Ok(())
+    }
+}
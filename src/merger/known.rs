@@ -1,14 +1,25 @@
-use super::core::{Merger, Padding, Point};
+use super::{
+    background::Background,
+    builder::{KnownSizeMergerBuilder, MergerBuilder},
+    core::{Merger, Padding, Placement, Point, Rect},
+    occupancy::Occupancy,
+    paths::{image_dimensions, image_paths, PathMerger, SortOrder},
+    policy::{Fit, SizePolicy},
+    spacing::Spacing,
+    streaming::{self, image_bytes, ImageSource, StreamOptions, StreamingMerger},
+};
 use crate::{
     cell::ImageCell,
-    functions::{paste, resize_nearest_neighbor},
-    BufferedImage, Image, ResizableMerger,
+    functions::{crop, paste, paste_decorated},
+    Alignment, Atlas, BufferedImage, CellDecoration, Composite, ConvertPixel, ConvertingMerger,
+    FitMode, Image, ImageView, MergeError, ResizableMerger, ResizeFilter, TextStyle,
+    TryFromWithFormat,
 };
 
 use image::Pixel;
 use num_traits::Zero;
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
-use std::ops::DerefMut;
+use std::{ops::DerefMut, path::Path};
 
 /// A known size merger that allows you to paste images onto a canvas. This merger is useful when you already know the size
 /// of all the images being pushed onto the canvas. This merger has multiple implementations, one for any container type and
@@ -34,17 +45,27 @@ where
 {
     canvas: ImageCell<P, image::ImageBuffer<P, Container>>,
     image_dimensions: (u32, u32), // The dimensions of the images being pasted (images must be a uniform size)
-    num_images: u32,              // The number of images that have been pasted to the canvas
+    occupancy: Occupancy,         // Which cells of the canvas hold an image.
     images_per_row: u32,          // The number of pages per row.
-    last_pasted_index: i32, // The index of the last pasted image, starts at -1 if not images have been pasted.
-    total_rows: u32,        // The total number of rows currently on the canvas.
-    padding: Option<Padding>,
+    total_rows: u32,              // The total number of rows currently on the canvas.
+    spacing: Spacing,             // The margin around the images and the gutters between them.
+    background: Option<Background<P>>, // What the canvas shows where no image has been pasted, None to never draw over it.
+    background_dimensions: (u32, u32), // The canvas dimensions gradients are stretched across, fixed when built.
+    alignment: Alignment,              // Where smaller images are placed inside their cells.
+    decoration: CellDecoration<P>,     // The border and rounded corners drawn around every image.
+    captions: Option<TextStyle<P>>, // The style of the captions below every image, or None for no captions.
+    caption_height: u32, // The height of the band below every image captions are drawn in.
+    caption_offset: u32, // The space between every image and its caption band, which the bottom of its frame lies in.
+    size_policy: SizePolicy, // What to do with images that do not match the image dimensions.
+    composite: Composite, // How pushed images are combined with the canvas.
+    resize_filter: ResizeFilter, // The filter used when images have to be resized.
+    fit_mode: FitMode<P>, // How resized images keep their aspect ratio.
 }
 
 impl<P, Container> KnownSizeMerger<P, Container>
 where
     P: Pixel + Sync,
-    <P as Pixel>::Subpixel: Sync,
+    <P as Pixel>::Subpixel: Send + Sync,
     Container: DerefMut<Target = [P::Subpixel]> + Sync,
 {
     /// Constructs a new KnownSizeMerger from a raw image buffer. This is useful if you need to use a specific container type that is not Vec. Typically,
@@ -55,11 +76,12 @@ where
     /// * `total_images` - The total number of images to be in the final canvas.
     /// * `padding` - The padding between images, or None for no padding.
     /// * `container` - The container to use for the underlying canvas. This container must be big enough to hold all the potential images
-    /// that will be pasted to the canvas.
+    ///   that will be pasted to the canvas.
     ///
     /// # Returns
     /// * `Some` - If the merger was successfully created.
-    /// * `None` - If the merger could not be created. This will happen if the container is not large enough to fit all the images.
+    /// * `None` - If the merger could not be created. This will happen if the container is not large enough to fit all the images,
+    ///   or if the layout is invalid. Use `try_new_from_raw` to find out why the merger could not be created.
     ///
     /// # Example
     /// ```
@@ -75,30 +97,112 @@ where
         padding: Option<Padding>,
         container: Container,
     ) -> Option<Self> {
-        let total_rows = (total_images + images_per_row - 1) / images_per_row;
-
-        let image_gaps_x = (images_per_row - 1) * padding.as_ref().map(|p| p.x).unwrap_or(0);
-        let image_gaps_y = (total_rows - 1) * padding.as_ref().map(|p| p.y).unwrap_or(0);
-
-        Image::new_from_raw(
-            (image_dimensions.0 * images_per_row) + image_gaps_x,
-            (image_dimensions.1 * total_rows) + image_gaps_y,
-            container,
-        )
-        .map(|canvas| Self {
-            canvas: ImageCell::new(canvas),
+        Self::try_new_from_raw(
             image_dimensions,
-            num_images: 0,
             images_per_row,
-            last_pasted_index: -1,
-            total_rows,
+            total_images,
             padding,
-        })
+            container,
+        )
+        .ok()
+    }
+
+    /// Same as `new_from_raw`, but returns a [MergeError](crate::MergeError) describing why the merger could not be created.
+    ///
+    /// # Errors
+    /// * [MergeError::InvalidLayout](crate::MergeError::InvalidLayout) - If the image dimensions, `images_per_row` or
+    ///   `total_images` are zero.
+    /// * [MergeError::CanvasTooLarge](crate::MergeError::CanvasTooLarge) - If the canvas dimensions overflow.
+    /// * [MergeError::BufferTooSmall](crate::MergeError::BufferTooSmall) - If the container cannot hold the canvas.
+    pub fn try_new_from_raw(
+        image_dimensions: (u32, u32),
+        images_per_row: u32,
+        total_images: u32,
+        padding: Option<Padding>,
+        container: Container,
+    ) -> Result<Self, MergeError> {
+        let builder = KnownSizeMergerBuilder {
+            spacing: Spacing::from(padding),
+            ..KnownSizeMergerBuilder::new(image_dimensions)
+                .with_images_per_row(images_per_row)
+                .with_total_images(total_images)
+        };
+
+        builder.try_build_from_raw(container)
+    }
+
+    /// Constructs a merger from the options of a builder, around a canvas the builder has already validated. A zeroed
+    /// canvas keeps zeros as its background when the builder has none, any other canvas is never drawn over.
+    pub(crate) fn from_builder(
+        builder: KnownSizeMergerBuilder<P>,
+        canvas: Image<P, image::ImageBuffer<P, Container>>,
+        total_rows: u32,
+        zeroed: bool,
+    ) -> Self {
+        // Can always unwrap here because the builder has already validated the text bands.
+        let (caption_height, title_band) = builder.text_bands().unwrap();
+        let caption_offset = builder.caption_offset(caption_height);
+        let spacing = builder.grid_spacing(title_band).unwrap();
+
+        let mut canvas = ImageCell::new(canvas);
+        let dimensions = canvas.dimensions();
+        let background = match builder.background {
+            Some(background) => {
+                let whole = Rect {
+                    x: 0,
+                    y: 0,
+                    width: dimensions.0,
+                    height: dimensions.1,
+                };
+
+                // Can always unwrap here because the region covers exactly the canvas.
+                background.fill(&mut canvas.region_mut(whole).unwrap(), dimensions);
+                Some(background)
+            }
+            None if zeroed => {
+                let zeros = vec![Zero::zero(); <P as Pixel>::CHANNEL_COUNT as usize];
+                Some(Background::Solid(*P::from_slice(&zeros)))
+            }
+            None => None,
+        };
+
+        if let Some((title, style)) = &builder.title {
+            let margin = builder.spacing.margin;
+            let band = Rect {
+                x: margin.left,
+                y: 0,
+                width: dimensions.0 - margin.left - margin.right,
+                height: title_band,
+            };
+
+            // Can always unwrap here because the band lies above the grid, within the canvas.
+            style.draw(&mut canvas.region_mut(band).unwrap(), title);
+        }
+
+        Self {
+            canvas,
+            image_dimensions: builder.image_dimensions,
+            occupancy: Occupancy::new(builder.images_per_row * total_rows),
+            images_per_row: builder.images_per_row,
+            total_rows,
+            spacing,
+            background,
+            background_dimensions: dimensions,
+            alignment: builder.alignment,
+            decoration: builder.decoration,
+            captions: builder.captions,
+            caption_height,
+            caption_offset,
+            size_policy: builder.size_policy,
+            composite: builder.composite,
+            resize_filter: builder.resize_filter,
+            fit_mode: builder.fit_mode,
+        }
     }
 
-    /// Returns the number of images that have been pasted to the canvas.
+    /// Returns the number of images on the canvas.
     pub fn get_num_images(&self) -> u32 {
-        self.num_images
+        self.occupancy.filled()
     }
 
     /// Returns the dimensions, (x, y), of the images being pasted to the canvas.
@@ -106,67 +210,664 @@ where
         self.image_dimensions
     }
 
+    /// Returns the margin around the images and the gutters between them. The band of the title, if there is one, is
+    /// part of the top margin.
+    pub fn get_spacing(&self) -> &Spacing {
+        &self.spacing
+    }
+
+    /// Returns what the canvas shows where no image has been pasted, which empty and cleared cells are drawn with. This
+    /// is None for a merger built from a raw container without a background, whose pixels are never drawn over.
+    pub fn get_background(&self) -> Option<&Background<P>> {
+        self.background.as_ref()
+    }
+
+    /// Returns the border and rounded corners drawn around every image as it is pasted.
+    pub fn get_decoration(&self) -> &CellDecoration<P> {
+        &self.decoration
+    }
+
+    /// Returns the style of the captions below every image, if the merger has a caption band.
+    pub fn get_captions(&self) -> Option<&TextStyle<P>> {
+        self.captions.as_ref()
+    }
+
+    /// Returns the area of the canvas below the slot at the given index that its caption is drawn in.
+    /// # Returns
+    /// * `Some` - The area of the caption band.
+    /// * `None` - If the merger has no caption band, or the index is outside of the canvas.
+    pub fn get_caption_band(&self, index: u32) -> Option<Rect> {
+        (self.captions.is_some() && index < self.capacity())
+            .then(|| self.get_caption_band_unchecked(index))
+    }
+
+    /// Sets the caption below the slot at the given index, replacing any caption drawn there before. The slot does not
+    /// need to hold an image. Text too long for the band is cut off according to the style of the captions.
+    /// # Arguments
+    /// * `index` - The index of the slot. Indices start at 0 and work left to right, top to bottom.
+    /// * `text` - The caption, such as the file name or index of the image.
+    /// # Panics
+    /// This function will panic if the caption cannot be set. Use `try_set_caption` to handle the error instead.
+    pub fn set_caption(&mut self, index: u32, text: &str) {
+        if let Err(err) = self.try_set_caption(index, text) {
+            panic!("{}", err);
+        }
+    }
+
+    /// Same as `set_caption`, but returns a [MergeError](crate::MergeError) instead of panicking.
+    /// # Errors
+    /// * [MergeError::CellOutOfBounds](crate::MergeError::CellOutOfBounds) - If the index lies outside of the canvas.
+    /// * [MergeError::InvalidLayout](crate::MergeError::InvalidLayout) - If the merger was built without captions.
+    pub fn try_set_caption(&mut self, index: u32, text: &str) -> Result<(), MergeError> {
+        self.check_index(index)?;
+        if self.captions.is_none() {
+            return Err(MergeError::InvalidLayout(
+                "the merger was built without a caption band",
+            ));
+        }
+
+        let band = self.get_caption_band_unchecked(index);
+        self.draw_background(band)?;
+        if let Some(style) = &self.captions {
+            style.draw(&mut self.canvas.region_mut(band)?, text);
+        }
+
+        Ok(())
+    }
+
+    /// Draws the background over an area of the canvas, as it would look had nothing ever been pasted there. The area is
+    /// left as it is when there is no background.
+    fn draw_background(&mut self, area: Rect) -> Result<(), MergeError> {
+        let region = &mut self.canvas.region_mut(area)?;
+        if let Some(background) = &self.background {
+            background.fill(region, self.background_dimensions);
+        }
+
+        Ok(())
+    }
+
     #[inline(always)]
     fn additional_space(&self) -> u32 {
-        (self.images_per_row * self.total_rows) - self.num_images
+        self.capacity() - self.occupancy.filled()
+    }
+
+    /// Returns the total number of image slots on the canvas, filled or not.
+    #[inline(always)]
+    pub(crate) fn capacity(&self) -> u32 {
+        self.images_per_row * self.total_rows
+    }
+
+    /// Returns the number of images per row.
+    pub(crate) fn images_per_row(&self) -> u32 {
+        self.images_per_row
+    }
+
+    /// Returns the index of the last slot that holds an image, if any.
+    pub(crate) fn last_occupied(&self) -> Option<u32> {
+        self.occupancy.last_filled()
+    }
+
+    /// Returns the policy used for images that do not match the image dimensions of the merger.
+    pub fn get_size_policy(&self) -> SizePolicy {
+        self.size_policy
+    }
+
+    /// Sets the policy used for images that do not match the image dimensions of the merger. By default, such images are
+    /// rejected.
+    /// # Arguments
+    /// * `size_policy` - The policy to use for subsequently pushed images.
+    pub fn set_size_policy(&mut self, size_policy: SizePolicy) {
+        self.size_policy = size_policy;
+    }
+
+    /// Returns where images smaller than their cell are placed inside of it.
+    pub fn get_alignment(&self) -> Alignment {
+        self.alignment
+    }
+
+    /// Sets where images smaller than their cell are placed inside of it by the
+    /// [CenterSmaller](crate::SizePolicy::CenterSmaller) size policy. By default, they are centered.
+    /// # Arguments
+    /// * `alignment` - The alignment to use for subsequently pushed images.
+    pub fn set_alignment(&mut self, alignment: Alignment) {
+        self.alignment = alignment;
+    }
+
+    /// Returns how pushed images are combined with the canvas.
+    pub fn get_composite(&self) -> Composite {
+        self.composite
+    }
+
+    /// Sets how pushed images are combined with the canvas. By default, pushed images replace the canvas beneath them.
+    /// # Arguments
+    /// * `composite` - The composite to use for subsequently pushed images.
+    pub fn set_composite(&mut self, composite: Composite) {
+        self.composite = composite;
+    }
+
+    /// Returns the filter used when images have to be resized.
+    pub fn get_resize_filter(&self) -> ResizeFilter {
+        self.resize_filter
+    }
+
+    /// Sets the filter used when images have to be resized, either by `push_resized` or by the
+    /// [ResizeToFit](crate::SizePolicy::ResizeToFit) size policy. By default, nearest neighbor sampling is used.
+    /// # Arguments
+    /// * `resize_filter` - The filter to use for subsequently resized images.
+    pub fn set_resize_filter(&mut self, resize_filter: ResizeFilter) {
+        self.resize_filter = resize_filter;
+    }
+
+    /// Returns how images pushed with `push_resized` are fitted into the image dimensions.
+    pub fn get_fit_mode(&self) -> FitMode<P> {
+        self.fit_mode
+    }
+
+    /// Sets how images pushed with `push_resized` are fitted into the image dimensions. By default, images are stretched.
+    /// # Arguments
+    /// * `fit_mode` - The fit mode to use for subsequently resized images.
+    pub fn set_fit_mode(&mut self, fit_mode: FitMode<P>) {
+        self.fit_mode = fit_mode;
+    }
+
+    /// Returns the area of the canvas covered by the slot at the given index. Indices start at 0 and work left to right,
+    /// top to bottom.
+    /// # Returns
+    /// * `Some` - The area of the slot.
+    /// * `None` - If the index is outside of the canvas.
+    pub fn get_cell(&self, index: u32) -> Option<Rect> {
+        (index < self.capacity()).then(|| self.get_cell_unchecked(index))
+    }
+
+    /// Returns true if the slot at the given index holds an image. Slots outside of the canvas never do.
+    pub fn is_occupied(&self, index: u32) -> bool {
+        index < self.capacity() && self.occupancy.get(index)
+    }
+
+    /// Returns where every image on the canvas was placed, in the order of their slots.
+    pub fn get_placements(&self) -> Vec<Placement> {
+        self.occupancy
+            .filled_cells()
+            .map(|index| Placement {
+                rect: self.get_cell_unchecked(index),
+                rotated: false,
+            })
+            .collect()
+    }
+
+    /// Returns an [Atlas](crate::Atlas) of every image on the canvas, which can be serialized for other tools to use
+    /// the canvas as a sprite atlas.
+    pub fn get_atlas(&self) -> Atlas {
+        Atlas::new(self.canvas.dimensions(), &self.get_placements())
+    }
+
+    /// Returns the area of the canvas covered by the slot at the given index.
+    fn get_cell_unchecked(&self, index: u32) -> Rect {
+        let (x, y) = self.get_paste_coordinates_unchecked(index);
+
+        Rect {
+            x,
+            y,
+            width: self.image_dimensions.0,
+            height: self.image_dimensions.1,
+        }
+    }
+
+    /// Returns the area of the canvas covered by the caption band of the slot at the given index.
+    fn get_caption_band_unchecked(&self, index: u32) -> Rect {
+        let cell = self.get_cell_unchecked(index);
+
+        Rect {
+            y: cell.y + cell.height + self.caption_offset,
+            height: self.caption_height,
+            ..cell
+        }
+    }
+
+    /// Returns the area of the canvas covered by the slot at the given index together with its caption band, which
+    /// move together when images are swapped or shifted.
+    fn get_slot_unchecked(&self, index: u32) -> Rect {
+        Rect {
+            height: self.slot_dimensions().1,
+            ..self.get_cell_unchecked(index)
+        }
+    }
+
+    /// Returns the area of the canvas covered by the slot at the given index together with the frame its decoration
+    /// draws around the cell, which is everything that moves with the image when images are swapped or shifted.
+    fn get_framed_slot_unchecked(&self, index: u32) -> Rect {
+        let slot = self.get_slot_unchecked(index);
+        let frame = self.decoration.frame(self.get_cell_unchecked(index));
+        let bottom = (slot.y + slot.height).max(frame.y + frame.height);
+
+        Rect {
+            height: bottom - frame.y,
+            ..frame
+        }
+    }
+
+    /// Returns whether the frames of neighbouring slots overlap, which happens when they share the gutter between them.
+    /// The frames of every slot are then drawn into each other, so the pixels of a framed slot can't be moved as they
+    /// are without carrying part of a neighbouring frame along.
+    fn frames_overlap(&self) -> bool {
+        let columns = (1..self.images_per_row).map(|column| (column - 1, column));
+        let rows = (1..self.total_rows)
+            .map(|row| ((row - 1) * self.images_per_row, row * self.images_per_row));
+
+        columns.chain(rows).any(|(a, b)| {
+            overlaps(
+                self.get_framed_slot_unchecked(a),
+                self.get_framed_slot_unchecked(b),
+            )
+        })
+    }
+
+    /// Moves the images and captions of slots by drawing them again, for when their framed slots cannot be copied as
+    /// they are because they overlap. Every slot whose frame overlaps a moved slot is drawn again in place, since the
+    /// background is drawn over every framed slot involved before the images are pasted again with their decoration.
+    /// # Arguments
+    /// * `moves` - The slots to move, as (from, to) pairs. Every slot moves at once, so two slots can be swapped.
+    fn redraw_moved(&mut self, moves: &[(u32, u32)]) -> Result<(), MergeError> {
+        let areas: Vec<_> = moves
+            .iter()
+            .flat_map(|&(from, to)| [from, to])
+            .map(|slot| self.get_framed_slot_unchecked(slot))
+            .collect();
+        let neighbours = (0..self.capacity()).filter(|&slot| {
+            let area = self.get_framed_slot_unchecked(slot);
+            !moves.iter().any(|&(from, to)| slot == from || slot == to)
+                && areas.iter().any(|&other| overlaps(area, other))
+        });
+        let moves: Vec<_> = moves
+            .iter()
+            .copied()
+            .chain(neighbours.map(|slot| (slot, slot)))
+            .collect();
+
+        let contents: Vec<_> = moves
+            .iter()
+            .map(|&(from, _)| {
+                let image = self
+                    .occupancy
+                    .get(from)
+                    .then(|| crop(&*self.canvas, self.get_cell_unchecked(from)));
+                (
+                    image,
+                    crop(&*self.canvas, self.get_caption_band_unchecked(from)),
+                )
+            })
+            .collect();
+
+        for &(from, to) in &moves {
+            self.draw_background(self.get_framed_slot_unchecked(from))?;
+            self.draw_background(self.get_framed_slot_unchecked(to))?;
+        }
+
+        // The images were composited when they were first pasted, so they are copied back as they are.
+        let copy = Composite::default();
+        for (&(_, to), (image, caption)) in moves.iter().zip(contents) {
+            let cell = self.get_cell_unchecked(to);
+            if let Some(image) = &image {
+                let loc = Point {
+                    x: cell.x,
+                    y: cell.y,
+                };
+                // Safety: the merger is borrowed mutably, and the slots are pasted one after another.
+                unsafe { paste_decorated(&self.canvas, image, loc, cell, &copy, &self.decoration) };
+            }
+
+            let band = self.get_caption_band_unchecked(to);
+            let loc = Point {
+                x: band.x,
+                y: band.y,
+            };
+            // Safety: the merger is borrowed mutably, and the slots are pasted one after another.
+            unsafe { paste(&self.canvas, &caption, loc) };
+            self.occupancy.set(to, image.is_some());
+        }
+
+        Ok(())
+    }
+
+    /// Draws the background over the slot at the given index, along with its frame and caption band, and marks it as
+    /// free. The slots whose frames overlap its frame are drawn again, so they keep their part of a shared border.
+    fn erase_slot(&mut self, index: u32) -> Result<(), MergeError> {
+        self.draw_background(self.get_caption_band_unchecked(index))?;
+        self.occupancy.set(index, false);
+
+        // The slot is free and its caption band blank, so drawing it again in place leaves only the background.
+        self.redraw_moved(&[(index, index)])
+    }
+
+    /// Returns the dimensions, (width, height), of every slot together with its caption band, which the grid is laid
+    /// out with.
+    fn slot_dimensions(&self) -> (u32, u32) {
+        (
+            self.image_dimensions.0,
+            self.image_dimensions.1 + self.caption_offset + self.caption_height,
+        )
+    }
+
+    /// Checks that `requested` more images fit on the canvas.
+    fn check_space(&self, requested: u32) -> Result<(), MergeError> {
+        let remaining = self.additional_space();
+        if remaining < requested {
+            return Err(MergeError::CanvasFull {
+                remaining,
+                requested,
+            });
+        }
+
+        Ok(())
     }
 
     fn get_paste_coordinates_unchecked(&self, index: u32) -> (u32, u32) {
-        let offset_x = index % self.images_per_row;
-        let offset_y = index / self.images_per_row;
+        let cell = grid_cell(
+            index,
+            self.slot_dimensions(),
+            self.images_per_row,
+            &self.spacing,
+        );
+
+        (cell.x, cell.y)
+    }
+
+    /// Converts the number of images of a bulk push to a count of slots, which can never be more than `u32::MAX`.
+    fn count(&self, len: usize) -> Result<u32, MergeError> {
+        u32::try_from(len).map_err(|_| MergeError::CanvasFull {
+            remaining: self.additional_space(),
+            requested: u32::MAX,
+        })
+    }
+
+    /// Checks that images of the given dimensions can be pasted under the size policy, returning how to paste each.
+    pub(crate) fn fit_all(
+        &self,
+        dimensions: impl IntoIterator<Item = (u32, u32)>,
+    ) -> Result<Vec<Fit>, MergeError> {
+        dimensions
+            .into_iter()
+            .map(|dimensions| {
+                self.size_policy
+                    .fit(dimensions, self.image_dimensions, self.alignment)
+            })
+            .collect()
+    }
+
+    /// Returns the indices of the first `requested` free slots, which pushed images fill in order.
+    fn free_slots(&self, requested: u32) -> Result<Vec<u32>, MergeError> {
+        self.check_space(requested)?;
+
+        Ok(self
+            .occupancy
+            .free_from(0)
+            .take(requested as usize)
+            .collect())
+    }
+
+    /// Checks that the given index lies on the canvas.
+    fn check_index(&self, index: u32) -> Result<(), MergeError> {
+        let capacity = self.capacity();
+        if index >= capacity {
+            return Err(MergeError::CellOutOfBounds { index, capacity });
+        }
+
+        Ok(())
+    }
+
+    /// Replaces the image in the slot at the given index, or fills the slot if it is empty. The slot is cleared to the
+    /// background first, so nothing of the old image is left around a smaller new one.
+    /// # Arguments
+    /// * `index` - The index of the slot. Indices start at 0 and work left to right, top to bottom.
+    /// * `image` - The image to put in the slot, fitted according to the size policy of the merger.
+    /// # Panics
+    /// This function will panic if the image cannot be put in the slot. Use `try_replace_image` to handle the error
+    /// instead.
+    pub fn replace_image<V>(&mut self, index: u32, image: &V)
+    where
+        V: ImageView<Pixel = P>,
+        <P as Pixel>::Subpixel: Send,
+    {
+        if let Err(err) = self.try_replace_image(index, image) {
+            panic!("{}", err);
+        }
+    }
+
+    /// Same as `replace_image`, but returns a [MergeError](crate::MergeError) instead of panicking.
+    /// # Errors
+    /// * [MergeError::CellOutOfBounds](crate::MergeError::CellOutOfBounds) - If the index lies outside of the canvas.
+    /// * Any error `try_push` returns for the image.
+    pub fn try_replace_image<V>(&mut self, index: u32, image: &V) -> Result<(), MergeError>
+    where
+        V: ImageView<Pixel = P>,
+        <P as Pixel>::Subpixel: Send,
+    {
+        self.check_index(index)?;
+        let fit = self.size_policy.fit(
+            image.view_dimensions(),
+            self.image_dimensions,
+            self.alignment,
+        )?;
+
+        let cell = self.get_cell_unchecked(index);
+        self.draw_background(self.decoration.frame(cell))?;
+        // Safety: the merger is borrowed mutably, so nothing else reads or writes the canvas.
+        unsafe {
+            fit.paste(
+                &self.canvas,
+                image,
+                cell,
+                &self.composite,
+                &self.decoration,
+                self.resize_filter,
+            );
+        }
+        self.occupancy.set(index, true);
+
+        Ok(())
+    }
+
+    /// Swaps the images in the slots at the given indices, along with their captions, decoration and whether they hold
+    /// an image at all. When the frames of neighbouring slots share a gutter, the swapped images are pasted again with
+    /// their decoration instead.
+    /// # Panics
+    /// This function will panic if either index lies outside of the canvas. Use `try_swap_images` to handle the error
+    /// instead.
+    pub fn swap_images(&mut self, a: u32, b: u32) {
+        if let Err(err) = self.try_swap_images(a, b) {
+            panic!("{}", err);
+        }
+    }
+
+    /// Same as `swap_images`, but returns a [MergeError](crate::MergeError) instead of panicking.
+    /// # Errors
+    /// * [MergeError::CellOutOfBounds](crate::MergeError::CellOutOfBounds) - If either index lies outside of the canvas.
+    pub fn try_swap_images(&mut self, a: u32, b: u32) -> Result<(), MergeError> {
+        self.check_index(a)?;
+        self.check_index(b)?;
+        if a == b {
+            return Ok(());
+        }
+
+        let (area_a, area_b) = (
+            self.get_framed_slot_unchecked(a),
+            self.get_framed_slot_unchecked(b),
+        );
+        if self.frames_overlap() || !can_move(area_a, area_b) {
+            return self.redraw_moved(&[(a, b), (b, a)]);
+        }
+
+        let mut regions = self.canvas.regions_mut(&[area_a, area_b])?;
+        let (first, second) = regions.split_at_mut(1);
+        for y in 0..area_a.height {
+            first[0].row_mut(y).swap_with_slice(second[0].row_mut(y));
+        }
+
+        let (filled_a, filled_b) = (self.occupancy.get(a), self.occupancy.get(b));
+        self.occupancy.set(a, filled_b);
+        self.occupancy.set(b, filled_a);
+
+        Ok(())
+    }
+
+    /// Fills the slot at the given index with a single color and marks it as free, so the next push fills it. The
+    /// decoration frame and caption band of the slot are cleared to the background.
+    /// # Arguments
+    /// * `index` - The index of the slot.
+    /// * `fill` - The color to fill the slot with. Use `remove_image` to draw the background instead.
+    /// # Panics
+    /// This function will panic if the index lies outside of the canvas. Use `try_clear_cell` to handle the error
+    /// instead.
+    pub fn clear_cell(&mut self, index: u32, fill: P) {
+        if let Err(err) = self.try_clear_cell(index, fill) {
+            panic!("{}", err);
+        }
+    }
+
+    /// Same as `clear_cell`, but returns a [MergeError](crate::MergeError) instead of panicking.
+    /// # Errors
+    /// * [MergeError::CellOutOfBounds](crate::MergeError::CellOutOfBounds) - If the index lies outside of the canvas.
+    pub fn try_clear_cell(&mut self, index: u32, fill: P) -> Result<(), MergeError> {
+        self.check_index(index)?;
+
+        self.erase_slot(index)?;
+        self.canvas
+            .region_mut(self.get_cell_unchecked(index))?
+            .fill(fill);
+
+        Ok(())
+    }
+
+    /// Inserts an image at the given index. If the slot is taken, it and the slots after it are shifted one slot
+    /// forward with their captions, up to the first free slot, the same way inserting into a `Vec` does. The inserted
+    /// image starts without a caption.
+    /// # Arguments
+    /// * `index` - The index to insert the image at.
+    /// * `image` - The image to insert, fitted according to the size policy of the merger.
+    /// # Panics
+    /// This function will panic if the image cannot be inserted. Use `try_insert_at` to handle the error instead.
+    pub fn insert_at<V>(&mut self, index: u32, image: &V)
+    where
+        V: ImageView<Pixel = P>,
+        <P as Pixel>::Subpixel: Send,
+    {
+        if let Err(err) = self.try_insert_at(index, image) {
+            panic!("{}", err);
+        }
+    }
 
-        let padding_x = self.padding.as_ref().map(|p| p.x).unwrap_or(0) * offset_x;
-        let padding_y = self.padding.as_ref().map(|p| p.y).unwrap_or(0) * offset_y;
+    /// Same as `insert_at`, but returns a [MergeError](crate::MergeError) instead of panicking. Nothing is shifted if an
+    /// error is returned.
+    /// # Errors
+    /// * [MergeError::CellOutOfBounds](crate::MergeError::CellOutOfBounds) - If the index lies outside of the canvas.
+    /// * [MergeError::CanvasFull](crate::MergeError::CanvasFull) - If there is no free slot at or after the index.
+    /// * Any error `try_push` returns for the image.
+    pub fn try_insert_at<V>(&mut self, index: u32, image: &V) -> Result<(), MergeError>
+    where
+        V: ImageView<Pixel = P>,
+        <P as Pixel>::Subpixel: Send,
+    {
+        self.check_index(index)?;
+        let free = self
+            .occupancy
+            .free_from(index)
+            .next()
+            .ok_or(MergeError::CanvasFull {
+                remaining: 0,
+                requested: 1,
+            })?;
+        self.size_policy.fit(
+            image.view_dimensions(),
+            self.image_dimensions,
+            self.alignment,
+        )?;
 
-        let x = (offset_x * self.image_dimensions.0) + padding_x;
-        let y = (offset_y * self.image_dimensions.1) + padding_y;
+        // Shift from the back, so every slot is copied forward before it is overwritten.
+        let moves: Vec<_> = (index..free).rev().map(|slot| (slot, slot + 1)).collect();
+        let areas: Vec<_> = moves
+            .iter()
+            .map(|&(from, to)| {
+                (
+                    self.get_framed_slot_unchecked(from),
+                    self.get_framed_slot_unchecked(to),
+                )
+            })
+            .collect();
+        if !self.frames_overlap() && areas.iter().all(|&(from, to)| can_move(from, to)) {
+            for (from, to) in areas {
+                let mut regions = self.canvas.regions_mut(&[from, to])?;
+                let (first, second) = regions.split_at_mut(1);
+                for y in 0..from.height {
+                    second[0].row_mut(y).copy_from_slice(first[0].row(y));
+                }
+            }
+            self.occupancy.set(free, true);
+        } else {
+            self.redraw_moved(&moves)?;
+        }
+        if free > index {
+            self.draw_background(self.get_caption_band_unchecked(index))?;
+        }
 
-        (x, y)
+        self.try_replace_image(index, image)
     }
 
-    fn get_next_paste_coordinates(&mut self) -> (u32, u32) {
-        if self.additional_space() == 0 {
-            panic!("No more space on the canvas!");
+    /// Removes the image in the slot at the given index, filling the slot and its caption band with the background and
+    /// marking it as free. Indices start at 0 and work left to right, top to bottom. A merger built from a raw container
+    /// without a background only marks the slot as free.
+    /// # Arguments
+    /// * `index` - The index of the image to remove.
+    /// # Panics
+    /// This function will panic if the index lies outside of the canvas. Use `try_remove_image` to handle the error
+    /// instead.
+    pub fn remove_image(&mut self, index: u32) {
+        if let Err(err) = self.try_remove_image(index) {
+            panic!("{}", err);
         }
+    }
+
+    /// Same as `remove_image`, but returns a [MergeError](crate::MergeError) instead of panicking.
+    /// # Errors
+    /// * [MergeError::CellOutOfBounds](crate::MergeError::CellOutOfBounds) - If the index lies outside of the canvas.
+    pub fn try_remove_image(&mut self, index: u32) -> Result<(), MergeError> {
+        self.check_index(index)?;
 
-        self.get_paste_coordinates_unchecked((self.last_pasted_index + 1) as u32)
+        self.erase_slot(index)
     }
 
-    /// Removes an image from the canvas at the given index. Indices start at 0 and work left to right, top to bottom. Most of the time
-    /// you will not need to use this function, and rather, can use the `remove_image` method instead. This method is useful if you need
-    /// to manually manage a specific Container type that is not `Vec`.
+    /// Removes an image from the canvas at the given index, pasting the contents of a container over it. Most of the
+    /// time you will not need to use this function, and rather, can use the `remove_image` or `clear_cell` methods
+    /// instead.
     ///
     /// # Arguments
     /// * `index` - The index of the image to remove.
     /// * `container` - The container to use to replace the image. The container must be the same size as the image being removed,
-    /// thus, the container must be the same size as the image dimensions.
+    ///   thus, the container must be the same size as the image dimensions.
     ///
     /// # Returns
     /// * `Some` - If the image was successfully removed.
-    /// * `None` - If the image could not be removed. This will happen if the container is not large enough to fit the image.
+    /// * `None` - If the image could not be removed. This will happen if the index lies outside of the canvas, or the
+    ///   container is not large enough to fit the image.
     pub fn remove_image_raw(&mut self, index: u32, container: Container) -> Option<()> {
-        let offset_x = index % self.images_per_row;
-        let offset_y = index / self.images_per_row;
-
-        let x = offset_x * self.image_dimensions.0;
-        let y = offset_y * self.image_dimensions.1;
+        let cell = self.get_cell(index)?;
+        let replacement = Image::new_from_raw(cell.width, cell.height, container)?;
+        let loc = Point {
+            x: cell.x,
+            y: cell.y,
+        };
 
-        let black_image =
-            Image::new_from_raw(self.image_dimensions.0, self.image_dimensions.1, container);
+        // Safety: the merger is borrowed mutably, so nothing else reads or writes the canvas.
+        unsafe { paste(&self.canvas, &replacement, loc) };
+        self.occupancy.set(index, false);
 
-        if let Some(black_image) = black_image {
-            paste(&self.canvas, &black_image, Point { x, y });
-            Some(())
-        } else {
-            None
-        }
+        Some(())
     }
 }
 
 impl<P> KnownSizeMerger<P, Vec<P::Subpixel>>
 where
     P: Pixel + Sync,
-    <P as Pixel>::Subpixel: Sync,
+    <P as Pixel>::Subpixel: Send + Sync,
 {
     /// Constructs a new KnownSizeMerger. By default, this will create an underlying canvas with `Vec` as the container type. If you
     /// need to use a different container type, you can use the `new_from_raw` method.
@@ -176,62 +877,117 @@ where
     /// * `images_per_row` - The number of images per row.
     /// * `total_images` - The total number of images to be in the final canvas.
     /// * `padding` - The padding between images, or None for no padding.
+    ///
+    /// # Panics
+    /// This function will panic if the layout is invalid or the canvas is too large. Use `try_new` to handle these cases.
     pub fn new(
         image_dimensions: (u32, u32),
         images_per_row: u32,
         total_images: u32,
         padding: Option<Padding>,
     ) -> Self {
-        let total_rows = (total_images + images_per_row - 1) / images_per_row;
-
-        let image_gaps_x = (images_per_row - 1) * padding.as_ref().map(|p| p.x).unwrap_or(0);
-        let image_gaps_y = (total_rows - 1) * padding.as_ref().map(|p| p.y).unwrap_or(0);
+        Self::try_new(image_dimensions, images_per_row, total_images, padding)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
 
-        let canvas = Image::new(
-            (image_dimensions.0 * images_per_row) + image_gaps_x,
-            (image_dimensions.1 * total_rows) + image_gaps_y,
-        );
+    /// Same as `new`, but returns a [MergeError](crate::MergeError) instead of panicking when the merger cannot be created.
+    ///
+    /// # Errors
+    /// * [MergeError::InvalidLayout](crate::MergeError::InvalidLayout) - If the image dimensions, `images_per_row` or
+    ///   `total_images` are zero.
+    /// * [MergeError::CanvasTooLarge](crate::MergeError::CanvasTooLarge) - If the canvas dimensions overflow.
+    ///
+    /// # Example
+    /// ```
+    /// use image_merger::{KnownSizeMerger, MergeError, Rgb};
+    ///
+    /// let merger = KnownSizeMerger::<Rgb<u8>, _>::try_new((100_000, 100_000), 100_000, 10, None);
+    /// assert!(matches!(merger, Err(MergeError::CanvasTooLarge)));
+    /// ```
+    pub fn try_new(
+        image_dimensions: (u32, u32),
+        images_per_row: u32,
+        total_images: u32,
+        padding: Option<Padding>,
+    ) -> Result<Self, MergeError> {
+        let builder = KnownSizeMergerBuilder {
+            spacing: Spacing::from(padding),
+            ..Self::builder(image_dimensions)
+                .with_images_per_row(images_per_row)
+                .with_total_images(total_images)
+        };
 
-        Self {
-            canvas: ImageCell::new(canvas),
-            image_dimensions,
-            num_images: 0,
-            images_per_row,
-            last_pasted_index: -1,
-            total_rows,
-            padding,
-        }
+        builder.try_build()
     }
 
-    /// Removes an image from the canvas at a given index. Indexing starts at 0 and works left to right, top to bottom.
+    /// Returns a [KnownSizeMergerBuilder](crate::KnownSizeMergerBuilder) for a merger of images with the given
+    /// dimensions, which can set every option of the merger before it is created.
     /// # Arguments
-    /// * `index` - The index of the image to remove.
-    pub fn remove_image(&mut self, index: u32) {
-        let container: Vec<<P as Pixel>::Subpixel> = vec![
-            Zero::zero();
-            (self.image_dimensions.0 * self.image_dimensions.1 * <P as Pixel>::CHANNEL_COUNT as u32)
-                as usize
-        ];
+    /// * `image_dimensions` - The dimensions of the images being pasted (images must be a uniform size)
+    ///
+    /// # Example
+    /// ```
+    /// use image_merger::{KnownSizeMerger, MergerBuilder, Rgb};
+    ///
+    /// let merger: KnownSizeMerger<Rgb<u8>, _> = KnownSizeMerger::builder((100, 100))
+    ///     .with_images_per_row(5)
+    ///     .with_total_images(10)
+    ///     .with_margin(10)
+    ///     .with_background(Rgb([255, 255, 255]))
+    ///     .build();
+    /// ```
+    pub fn builder(image_dimensions: (u32, u32)) -> KnownSizeMergerBuilder<P> {
+        KnownSizeMergerBuilder::new(image_dimensions)
+    }
 
-        self.remove_image_raw(index, container).unwrap(); // Can always unwrap here because we know the buffer is the right size.
+    /// Grows or shrinks the canvas so it holds exactly `total_rows` rows of images. Rows that already exist keep their
+    /// pixels, new rows are drawn with the background and rows past `total_rows` are dropped. Used by mergers that manage a growable canvas.
+    /// Gradients keep the dimensions of the canvas the merger was built with, so new rows continue them without a seam.
+    pub(crate) fn resize_rows(&mut self, total_rows: u32) -> Result<(), MergeError> {
+        let (width, height) = canvas_size::<P>(
+            self.slot_dimensions(),
+            self.images_per_row,
+            total_rows,
+            &self.spacing,
+        )?;
+        let old_height = self.canvas.height();
 
-        let offset_x = index % self.images_per_row;
-        let offset_y = index / self.images_per_row;
+        // Take the canvas out of its cell so we can reuse the underlying buffer. Rows are stored top to bottom,
+        // so resizing the buffer only ever touches the rows at the end of the canvas.
+        let canvas = std::mem::replace(&mut self.canvas, ImageCell::new(Image::new(0, 0)));
+        let mut buffer = canvas.into_inner().into_buffer().into_raw();
+        let len = width as usize * height as usize * <P as Pixel>::CHANNEL_COUNT as usize;
 
-        let x = offset_x * self.image_dimensions.0;
-        let y = offset_y * self.image_dimensions.1;
+        if len < buffer.len() {
+            buffer.truncate(len);
+            buffer.shrink_to_fit();
+        } else {
+            buffer.resize(len, Zero::zero());
+        }
 
-        let black_image: BufferedImage<P> =
-            Image::new(self.image_dimensions.0, self.image_dimensions.1);
+        // Can always unwrap here because we sized the buffer ourselves.
+        self.canvas = ImageCell::new(Image::new_from_raw(width, height, buffer).unwrap());
+        self.total_rows = total_rows;
+        self.occupancy.resize(self.images_per_row * total_rows);
 
-        paste(&self.canvas, &black_image, Point { x, y });
+        // Everything below the last row that is kept, including the old bottom margin and the gutters between the new
+        // rows, is background now.
+        let keep = old_height
+            .min(height)
+            .saturating_sub(self.spacing.margin.bottom);
+        self.draw_background(Rect {
+            x: 0,
+            y: keep,
+            width,
+            height: height - keep,
+        })
     }
 }
 
 impl<P, Container> Merger<P, Container> for KnownSizeMerger<P, Container>
 where
-    P: Pixel + Sync,
-    <P as Pixel>::Subpixel: Sync,
+    P: Pixel + Send + Sync,
+    <P as Pixel>::Subpixel: Send + Sync,
     Container: DerefMut<Target = [P::Subpixel]> + Sync,
 {
     fn get_canvas(&self) -> &Image<P, image::ImageBuffer<P, Container>> {
@@ -242,35 +998,179 @@ where
         self.canvas.into_inner()
     }
 
-    fn push(&mut self, image: &Image<P, image::ImageBuffer<P, Container>>) {
-        let (x, y) = self.get_next_paste_coordinates();
+    fn try_push<V>(&mut self, image: &V) -> Result<(), MergeError>
+    where
+        V: ImageView<Pixel = P>,
+    {
+        let fit = self.size_policy.fit(
+            image.view_dimensions(),
+            self.image_dimensions,
+            self.alignment,
+        )?;
+        let slot = self.free_slots(1)?[0];
 
-        paste(&self.canvas, image, Point { x, y });
+        // Safety: the merger is borrowed mutably, so nothing else reads or writes the canvas.
+        unsafe {
+            fit.paste(
+                &self.canvas,
+                image,
+                self.get_cell_unchecked(slot),
+                &self.composite,
+                &self.decoration,
+                self.resize_filter,
+            );
+        }
+        self.occupancy.set(slot, true);
 
-        self.last_pasted_index += 1;
-        self.num_images += 1;
+        Ok(())
     }
 
-    fn bulk_push(&mut self, images: &[&Image<P, image::ImageBuffer<P, Container>>]) {
-        // If we can't fit all the images we need to panic.
-        if self.additional_space() < images.len() as u32 {
-            // TODO: Maybe only take as many images as we can fit?
-            panic!("There is not enough space on the canvas to fit all the requested images.");
-        }
+    fn try_bulk_push<V>(&mut self, images: &[&V]) -> Result<(), MergeError>
+    where
+        V: ImageView<Pixel = P>,
+    {
+        // Validate everything up front so a failed bulk push leaves the canvas untouched.
+        let slots = self.free_slots(self.count(images.len())?)?;
+        let fits = self.fit_all(images.iter().map(|image| image.view_dimensions()))?;
 
+        let _batch = self.canvas.batch();
         (0..images.len()).into_par_iter().for_each(|index| {
             let image = images[index];
 
-            // The image coordinates can easily be calculated by using the last_pasted_index
-            // and making the calculations ourselves.
-            let offset_index = (index as i32 + self.last_pasted_index + 1) as u32;
+            let cell = self.get_cell_unchecked(slots[index]);
+            // Safety: every image is pasted into its own slot, and the frames of different slots never overlap in a
+            // validated layout. The merger is borrowed mutably, so nothing else reads or writes the canvas.
+            unsafe {
+                fits[index].paste(
+                    &self.canvas,
+                    image,
+                    cell,
+                    &self.composite,
+                    &self.decoration,
+                    self.resize_filter,
+                );
+            }
+        });
+
+        for slot in slots {
+            self.occupancy.set(slot, true);
+        }
+
+        Ok(())
+    }
+}
+
+impl<P, Container> ConvertingMerger<P> for KnownSizeMerger<P, Container>
+where
+    P: Pixel + Send + Sync,
+    <P as Pixel>::Subpixel: Send + Sync,
+    Container: DerefMut<Target = [P::Subpixel]> + Sync,
+{
+    fn try_push_converted<Q, V>(&mut self, image: &V) -> Result<(), MergeError>
+    where
+        Q: ConvertPixel<P> + Sync,
+        <Q as Pixel>::Subpixel: Send + Sync,
+        V: ImageView<Pixel = Q>,
+    {
+        let fit = self.size_policy.fit(
+            image.view_dimensions(),
+            self.image_dimensions,
+            self.alignment,
+        )?;
+        let slot = self.free_slots(1)?[0];
+
+        // Safety: the merger is borrowed mutably, so nothing else reads or writes the canvas.
+        unsafe {
+            fit.paste_converted(
+                &self.canvas,
+                image,
+                self.get_cell_unchecked(slot),
+                &self.composite,
+                &self.decoration,
+                self.resize_filter,
+            );
+        }
+        self.occupancy.set(slot, true);
+
+        Ok(())
+    }
+
+    fn try_bulk_push_converted<Q, V>(&mut self, images: &[&V]) -> Result<(), MergeError>
+    where
+        Q: ConvertPixel<P> + Sync,
+        <Q as Pixel>::Subpixel: Send + Sync,
+        V: ImageView<Pixel = Q>,
+    {
+        // Validate everything up front so a failed bulk push leaves the canvas untouched.
+        let slots = self.free_slots(self.count(images.len())?)?;
+        let fits = self.fit_all(images.iter().map(|image| image.view_dimensions()))?;
 
-            let (x, y) = self.get_paste_coordinates_unchecked(offset_index);
-            paste(&self.canvas, image, Point { x, y });
+        let _batch = self.canvas.batch();
+        (0..images.len()).into_par_iter().for_each(|index| {
+            let cell = self.get_cell_unchecked(slots[index]);
+            // Safety: every image is pasted into its own slot, and the frames of different slots never overlap in a
+            // validated layout. The merger is borrowed mutably, so nothing else reads or writes the canvas.
+            unsafe {
+                fits[index].paste_converted(
+                    &self.canvas,
+                    images[index],
+                    cell,
+                    &self.composite,
+                    &self.decoration,
+                    self.resize_filter,
+                );
+            }
         });
 
-        self.last_pasted_index += images.len() as i32;
-        self.num_images += images.len() as u32;
+        for slot in slots {
+            self.occupancy.set(slot, true);
+        }
+
+        Ok(())
+    }
+}
+
+impl<P> KnownSizeMerger<P, Vec<P::Subpixel>>
+where
+    P: Pixel + Send + Sync,
+    <P as Pixel>::Subpixel: Send + Sync,
+    BufferedImage<P>: TryFromWithFormat<Vec<u8>> + Send,
+{
+    /// Constructs a new KnownSizeMerger holding every image in a directory. The image dimensions are read from the
+    /// first image, and the images are decoded in parallel and pushed a few at a time. Images are pushed with the default
+    /// [SizePolicy](crate::SizePolicy), so every image must match the dimensions of the first one.
+    ///
+    /// # Arguments
+    /// * `dir` - The directory to merge the images of, see [image_paths](crate::image_paths).
+    /// * `images_per_row` - The number of images per row.
+    /// * `padding` - The padding between images, or None for no padding.
+    /// * `order` - The order the images are merged in.
+    ///
+    /// # Errors
+    /// * [MergeError::InvalidLayout](crate::MergeError::InvalidLayout) - If the directory holds no images, or
+    ///   `images_per_row` is zero.
+    /// * [MergeError::LoadError](crate::MergeError::LoadError) - If the directory or an image cannot be read.
+    /// * Any error returned by `try_new` or `try_bulk_push`.
+    pub fn from_dir(
+        dir: impl AsRef<Path>,
+        images_per_row: u32,
+        padding: Option<Padding>,
+        order: SortOrder,
+    ) -> Result<Self, MergeError> {
+        let paths = image_paths(dir, order)?;
+        let first = paths.first().ok_or(MergeError::InvalidLayout(
+            "the directory does not hold any images",
+        ))?;
+
+        let mut merger = Self::try_new(
+            image_dimensions(first)?,
+            images_per_row,
+            paths.len() as u32,
+            padding,
+        )?;
+        merger.try_bulk_push_paths(&paths)?;
+
+        Ok(merger)
     }
 }
 
@@ -279,25 +1179,239 @@ where
     P: Pixel + Sync + Send,
     <P as Pixel>::Subpixel: Sync + Send,
 {
-    fn push_resized(&mut self, image: &BufferedImage<P>) {
-        let (width, height) = self.image_dimensions;
-        let resized = resize_nearest_neighbor(image, width, height);
-        self.push(&resized);
+    fn try_push_resized(&mut self, image: &BufferedImage<P>) -> Result<(), MergeError> {
+        self.check_space(1)?;
+
+        let resized = self
+            .fit_mode
+            .resize(image, self.image_dimensions, self.resize_filter);
+        self.try_push(&resized)
     }
 
-    fn bulk_push_resized(&mut self, images: &[&BufferedImage<P>]) {
+    fn try_bulk_push_resized(&mut self, images: &[&BufferedImage<P>]) -> Result<(), MergeError> {
+        // Don't waste time resizing images that won't fit on the canvas.
+        self.check_space(self.count(images.len())?)?;
+
         // Resize all the images in parallel then push them
-        let resized_images: Vec<BufferedImage<P>> = images
+        let resized_images = self.resize_all(images);
+
+        // Convert Vec<T> to [&T] for the bulk push method
+        let resized_images_ref: Vec<&BufferedImage<P>> = resized_images.iter().collect();
+
+        self.try_bulk_push(&resized_images_ref)
+    }
+}
+
+impl<P, Container> KnownSizeMerger<P, Container>
+where
+    P: Pixel + Send + Sync,
+    <P as Pixel>::Subpixel: Send + Sync,
+    Container: DerefMut<Target = [P::Subpixel]> + Sync,
+{
+    /// Resizes images to the image dimensions of the merger in parallel, according to its fit mode. Used by mergers that
+    /// resize images before growing the canvas for them.
+    pub(crate) fn resize_all(&self, images: &[&BufferedImage<P>]) -> Vec<BufferedImage<P>> {
+        images
             .into_par_iter()
             .map(|image| {
-                let (width, height) = self.image_dimensions;
-                return resize_nearest_neighbor(image, width, height);
+                self.fit_mode
+                    .resize(image, self.image_dimensions, self.resize_filter)
             })
-            .collect();
+            .collect()
+    }
 
-        // Convert Vec<T> to [&T] for the bulk push method
-        let resized_images_ref: Vec<&BufferedImage<P>> = resized_images.iter().collect();
+    /// Streams sources into the free slots of the canvas, taking no more sources than there are free slots. Used by
+    /// mergers that grow the canvas between runs.
+    pub(crate) fn stream_into<I>(
+        &mut self,
+        sources: &mut I,
+        options: &StreamOptions,
+    ) -> Result<(), MergeError>
+    where
+        I: Iterator,
+        I::Item: ImageSource<P>,
+    {
+        let slots: Vec<u32> = self.occupancy.free_from(0).collect();
+        let (width, height) = self.image_dimensions;
+
+        let this = &*self;
+        let streamed = {
+            // The pastes of a run may happen at the same time, unlike the clearing pastes below.
+            let _batch = this.canvas.batch();
+            streaming::run(
+                sources.by_ref().take(slots.len()),
+                options,
+                image_bytes::<P>(width, height),
+                |index, image| {
+                    let cell = this.get_cell_unchecked(slots[index as usize]);
+                    let loc = Point {
+                        x: cell.x,
+                        y: cell.y,
+                    };
+
+                    if image.dimensions() == this.image_dimensions {
+                        // Safety: every source is pasted into its own free slot, and the frames of different slots never overlap
+                        // in a validated layout. The merger is borrowed mutably for the whole run.
+                        unsafe {
+                            paste_decorated(
+                                &this.canvas,
+                                &image,
+                                loc,
+                                cell,
+                                &this.composite,
+                                &this.decoration,
+                            );
+                        }
+                    } else {
+                        let resized =
+                            this.fit_mode
+                                .resize(&image, this.image_dimensions, this.resize_filter);
+                        drop(image);
+                        // Safety: every source is pasted into its own free slot, and the frames of different slots never overlap
+                        // in a validated layout. The merger is borrowed mutably for the whole run.
+                        unsafe {
+                            paste_decorated(
+                                &this.canvas,
+                                &resized,
+                                loc,
+                                cell,
+                                &this.composite,
+                                &this.decoration,
+                            );
+                        }
+                    }
+                },
+            )
+        };
+
+        for &slot in &slots[..streamed.pushed as usize] {
+            self.occupancy.set(slot, true);
+        }
+
+        match streamed.error {
+            Some(err) => {
+                // Images after the failing one may already have been pasted, clear their slots so the next push can
+                // take them.
+                for &slot in &slots[streamed.pushed as usize..streamed.started as usize] {
+                    let cell = self.get_cell_unchecked(slot);
+                    self.draw_background(self.decoration.frame(cell))?;
+                }
+
+                Err(err)
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+impl<P, Container> StreamingMerger<P> for KnownSizeMerger<P, Container>
+where
+    P: Pixel + Send + Sync,
+    <P as Pixel>::Subpixel: Send + Sync,
+    Container: DerefMut<Target = [P::Subpixel]> + Sync,
+{
+    fn try_push_stream<I>(&mut self, sources: I, options: &StreamOptions) -> Result<(), MergeError>
+    where
+        I: IntoIterator,
+        I::Item: ImageSource<P>,
+    {
+        let mut sources = sources.into_iter();
+
+        // Fail before doing any work if the stream is known to be too long.
+        let (len, _) = sources.size_hint();
+        self.check_space(len.try_into().unwrap_or(u32::MAX))?;
+
+        self.stream_into(&mut sources, options)?;
+
+        let requested = sources.count();
+        if requested > 0 {
+            return Err(MergeError::CanvasFull {
+                remaining: 0,
+                requested: requested.try_into().unwrap_or(u32::MAX),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns whether two areas share any pixel.
+fn overlaps(a: Rect, b: Rect) -> bool {
+    a.x < b.x + b.width && b.x < a.x + a.width && a.y < b.y + b.height && b.y < a.y + a.height
+}
+
+/// Returns whether the pixels of one area can be copied to another as they are, which needs them to have the same
+/// dimensions and not overlap.
+fn can_move(from: Rect, to: Rect) -> bool {
+    from.width == to.width && from.height == to.height && !overlaps(from, to)
+}
+
+/// Computes the area of the cell at the given index of a grid, with indices working left to right, top to bottom.
+pub(crate) fn grid_cell(
+    index: u32,
+    image_dimensions: (u32, u32),
+    images_per_row: u32,
+    spacing: &Spacing,
+) -> Rect {
+    let column = index % images_per_row;
+    let row = index / images_per_row;
+
+    Rect {
+        x: spacing.column_x(column, image_dimensions.0) as u32,
+        y: spacing.row_y(row, image_dimensions.1) as u32,
+        width: image_dimensions.0,
+        height: image_dimensions.1,
+    }
+}
+
+/// Computes the number of rows needed to hold `total_images` images.
+pub(crate) fn total_rows(images_per_row: u32, total_images: u32) -> Result<u32, MergeError> {
+    if images_per_row == 0 {
+        return Err(MergeError::InvalidLayout(
+            "images_per_row must be greater than zero",
+        ));
+    }
+
+    if total_images == 0 {
+        return Err(MergeError::InvalidLayout(
+            "total_images must be greater than zero",
+        ));
+    }
+
+    Ok(total_images.div_ceil(images_per_row))
+}
+
+/// Computes the dimensions, (width, height), of a canvas holding `total_rows` rows of `images_per_row` images, spaced
+/// out by `spacing`. Fails if the dimensions overflow a `u32` or the canvas buffer would not be addressable.
+pub(crate) fn canvas_size<P: Pixel>(
+    image_dimensions: (u32, u32),
+    images_per_row: u32,
+    total_rows: u32,
+    spacing: &Spacing,
+) -> Result<(u32, u32), MergeError> {
+    let width = u32::try_from(spacing.canvas_width(images_per_row, image_dimensions.0));
+    let height = u32::try_from(spacing.canvas_height(total_rows, image_dimensions.1));
+
+    match (width, height) {
+        (Ok(width), Ok(height)) => check_canvas_size::<P>(width, height),
+        _ => Err(MergeError::CanvasTooLarge),
+    }
+}
+
+/// Checks that a canvas with the given dimensions has an addressable buffer, returning the dimensions if it does.
+pub(crate) fn check_canvas_size<P: Pixel>(
+    width: u32,
+    height: u32,
+) -> Result<(u32, u32), MergeError> {
+    let fits = (width as usize)
+        .checked_mul(height as usize)
+        .and_then(|len| len.checked_mul(<P as Pixel>::CHANNEL_COUNT as usize))
+        .and_then(|len| len.checked_mul(std::mem::size_of::<P::Subpixel>()))
+        .is_some_and(|bytes| bytes <= isize::MAX as usize);
 
-        self.bulk_push(&resized_images_ref);
+    if fits {
+        Ok((width, height))
+    } else {
+        Err(MergeError::CanvasTooLarge)
     }
 }
@@ -1,7 +1,28 @@
+mod background;
+mod builder;
+mod converting;
 mod core;
+mod decoration;
 mod known;
+mod occupancy;
+mod packing;
+mod paths;
+mod policy;
 mod resizable;
+mod spacing;
+mod streaming;
+mod unknown;
 
+pub use background::Background;
+pub use builder::{KnownSizeMergerBuilder, MergerBuilder, UnknownSizeMergerBuilder};
+pub use converting::{ConvertPixel, ConvertingMerger, DynamicPixel};
 pub use core::*;
+pub use decoration::{BlurFilter, Border, BorderPlacement, CellDecoration, Shadow};
 pub use known::*;
+pub use packing::*;
+pub use paths::{image_paths, load_image, PathMerger, SortOrder};
+pub use policy::SizePolicy;
 pub use resizable::*;
+pub use spacing::{Margin, Spacing};
+pub use streaming::{ImageSource, StreamOptions, StreamingMerger};
+pub use unknown::*;
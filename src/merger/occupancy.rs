@@ -0,0 +1,174 @@
+/// A bitmap of which cells of a grid hold an image, so pushes can fill the first free cell and edits can tell filled
+/// cells from empty ones.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Occupancy {
+    words: Vec<u64>, // One bit per cell, set if the cell holds an image.
+    len: u32,        // The number of cells tracked.
+    filled: u32,     // The number of set bits.
+}
+
+impl Occupancy {
+    /// Creates a bitmap of `len` empty cells.
+    pub(crate) fn new(len: u32) -> Self {
+        Self {
+            words: vec![0; (len as usize).div_ceil(64)],
+            len,
+            filled: 0,
+        }
+    }
+
+    /// Returns the number of filled cells.
+    pub(crate) fn filled(&self) -> u32 {
+        self.filled
+    }
+
+    /// Returns true if the given cell holds an image.
+    pub(crate) fn get(&self, index: u32) -> bool {
+        self.words[index as usize / 64] & (1 << (index % 64)) != 0
+    }
+
+    /// Marks the given cell as filled or empty.
+    pub(crate) fn set(&mut self, index: u32, filled: bool) {
+        if self.get(index) == filled {
+            return;
+        }
+
+        self.words[index as usize / 64] ^= 1 << (index % 64);
+        if filled {
+            self.filled += 1;
+        } else {
+            self.filled -= 1;
+        }
+    }
+
+    /// Returns the indices of the empty cells at or after `from`, in order.
+    pub(crate) fn free_from(&self, from: u32) -> impl Iterator<Item = u32> + '_ {
+        self.scan(from, false)
+    }
+
+    /// Returns the indices of the filled cells, in order.
+    pub(crate) fn filled_cells(&self) -> impl Iterator<Item = u32> + '_ {
+        self.scan(0, true)
+    }
+
+    /// Returns the index of the last filled cell, if any.
+    pub(crate) fn last_filled(&self) -> Option<u32> {
+        let (index, word) = self
+            .words
+            .iter()
+            .enumerate()
+            .rfind(|(_, word)| **word != 0)?;
+
+        Some(index as u32 * 64 + 63 - word.leading_zeros())
+    }
+
+    /// Returns the indices of the cells at or after `from` that are filled, or empty, in order. Whole words are skipped
+    /// at a time, and the next match in a word is found from the length of the run of bits before it.
+    fn scan(&self, from: u32, filled: bool) -> impl Iterator<Item = u32> + '_ {
+        let mut next = from as u64;
+        std::iter::from_fn(move || {
+            while next < self.len as u64 {
+                let (word, bit) = ((next / 64) as usize, next % 64);
+
+                // Count the cells before `next` as part of the run, so it ends at the first match at or after it.
+                let skipped = (1u64 << bit) - 1;
+                let run = if filled {
+                    (!self.words[word] | skipped).trailing_ones()
+                } else {
+                    (self.words[word] | skipped).trailing_ones()
+                };
+
+                if run < 64 {
+                    let index = word as u64 * 64 + run as u64;
+                    next = index + 1;
+
+                    // The bits past the last cell are never set, so they would match as empty cells.
+                    return (index < self.len as u64).then_some(index as u32);
+                }
+
+                next = (word as u64 + 1) * 64;
+            }
+
+            None
+        })
+    }
+
+    /// Grows or shrinks the bitmap to `len` cells. New cells are empty, and cells past `len` are dropped.
+    pub(crate) fn resize(&mut self, len: u32) {
+        for index in len..self.len {
+            self.set(index, false);
+        }
+
+        self.words.resize((len as usize).div_ceil(64), 0);
+        self.len = len;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_and_free() {
+        let mut occupancy = Occupancy::new(130);
+        for index in [0, 1, 64, 129] {
+            occupancy.set(index, true);
+        }
+        occupancy.set(1, true);
+
+        assert_eq!(occupancy.filled(), 4);
+        assert_eq!(
+            occupancy.free_from(0).take(2).collect::<Vec<_>>(),
+            vec![2, 3]
+        );
+        assert_eq!(
+            occupancy.free_from(63).take(2).collect::<Vec<_>>(),
+            vec![63, 65]
+        );
+        assert_eq!(occupancy.last_filled(), Some(129));
+
+        occupancy.set(129, false);
+        assert_eq!(occupancy.filled(), 3);
+        assert_eq!(occupancy.last_filled(), Some(64));
+    }
+
+    #[test]
+    fn test_resize() {
+        let mut occupancy = Occupancy::new(70);
+        occupancy.set(3, true);
+        occupancy.set(69, true);
+
+        occupancy.resize(10);
+        assert_eq!(occupancy.filled(), 1);
+        assert_eq!(occupancy.filled_cells().collect::<Vec<_>>(), vec![3]);
+
+        occupancy.resize(200);
+        assert!(!occupancy.get(69));
+        assert_eq!(occupancy.free_from(199).collect::<Vec<_>>(), vec![199]);
+    }
+
+    #[test]
+    fn test_scan_matches_every_cell() {
+        let mut occupancy = Occupancy::new(200);
+        for index in (0..200).filter(|index| index % 3 == 0 || (64..130).contains(index)) {
+            occupancy.set(index, true);
+        }
+
+        for from in [0, 1, 63, 64, 65, 128, 130, 199, 200] {
+            assert_eq!(
+                occupancy.free_from(from).collect::<Vec<_>>(),
+                (from..200)
+                    .filter(|&index| !occupancy.get(index))
+                    .collect::<Vec<_>>()
+            );
+        }
+        assert_eq!(
+            occupancy.filled_cells().collect::<Vec<_>>(),
+            (0..200)
+                .filter(|&index| occupancy.get(index))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(occupancy.last_filled(), Some(198));
+        assert_eq!(Occupancy::new(10).last_filled(), None);
+    }
+}
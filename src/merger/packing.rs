@@ -0,0 +1,546 @@
+use super::{
+    core::{Merger, Padding, Placement, Point},
+    known::check_canvas_size,
+};
+use crate::{
+    cell::ImageCell,
+    functions::{paste_composite, paste_converted, rotate90},
+    packer::Bin,
+    Atlas, BufferedImage, Composite, ConvertPixel, ConvertingMerger, Image, ImageView, MergeError,
+};
+
+use image::Pixel;
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+/// The algorithm a [PackingMerger](PackingMerger) uses to decide where images go on its canvas.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PackingAlgorithm {
+    /// Tracks every maximal free rectangle and places images using the best short side fit heuristic. Produces the most
+    /// compact canvases, at the cost of being the slowest. This is the default.
+    #[default]
+    MaxRects,
+    /// Tracks only the top edge of the placed images and places images as low as possible. Very fast, but cannot fill
+    /// gaps left underneath taller images.
+    Skyline,
+    /// Cuts the free space in two after every placement, placing images using the best area fit heuristic. A middle
+    /// ground between the other two algorithms.
+    Guillotine,
+}
+
+/// Options that control how a [PackingMerger](PackingMerger) packs images.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PackingOptions {
+    /// The algorithm used to place images.
+    pub algorithm: PackingAlgorithm,
+    /// The padding between images, or None for no padding.
+    pub padding: Option<Padding>,
+    /// Whether images may be rotated 90 degrees clockwise to fit better.
+    pub allow_rotation: bool,
+}
+
+/// A packing merger that allows you to paste images of any size onto a canvas, sprite atlas style. Unlike the
+/// [KnownSizeMerger](crate::KnownSizeMerger), images are not laid out in a grid but packed as tightly as the chosen
+/// [PackingAlgorithm](PackingAlgorithm) allows. Where every image ended up is available through `get_placements`.
+///
+/// A PackingMerger can either be created with a fixed size canvas using `new`, in which case images are placed as they
+/// are pushed, or from a set of images using `pack`, which computes a compact canvas for them.
+///
+/// # Type Parameters
+/// * `P` - The pixel type of the underlying image.
+///
+/// # Example
+/// ```
+/// use image_merger::{Merger, PackingMerger, PackingOptions, BufferedImage, Rgb};
+///
+/// let wide: BufferedImage<Rgb<u8>> = BufferedImage::new(200, 50);
+/// let tall: BufferedImage<Rgb<u8>> = BufferedImage::new(50, 120);
+///
+/// let merger = PackingMerger::pack(&[&wide, &tall, &tall], PackingOptions::default());
+/// assert_eq!(merger.get_placements().len(), 3);
+///
+/// let canvas = merger.into_canvas();
+/// assert!(canvas.width() >= 200);
+/// ```
+pub struct PackingMerger<P>
+where
+    P: Pixel,
+    <P as Pixel>::Subpixel: Sync,
+{
+    canvas: ImageCell<P, image::ImageBuffer<P, Vec<P::Subpixel>>>,
+    bin: Bin, // The free space left on the canvas, padded on the right and bottom.
+    options: PackingOptions, // How images are packed.
+    placements: Vec<Placement>, // Where every pushed image was placed, in the order they were pushed.
+    composite: Composite,       // How pushed images are combined with the canvas.
+}
+
+impl<P> PackingMerger<P>
+where
+    P: Pixel + Sync + Send,
+    <P as Pixel>::Subpixel: Sync + Send,
+{
+    /// Constructs a new PackingMerger with a fixed size canvas. Images are placed on the canvas as they are pushed, and
+    /// pushes fail with [MergeError::CanvasFull](crate::MergeError::CanvasFull) once an image no longer fits.
+    /// # Arguments
+    /// * `dimensions` - The dimensions, (width, height), of the canvas.
+    /// * `options` - How images are packed.
+    ///
+    /// # Panics
+    /// This function will panic if the canvas is too large. Use `try_new` to handle this case.
+    pub fn new(dimensions: (u32, u32), options: PackingOptions) -> Self {
+        Self::try_new(dimensions, options).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Same as `new`, but returns a [MergeError](crate::MergeError) instead of panicking when the merger cannot be created.
+    ///
+    /// # Errors
+    /// * [MergeError::CanvasTooLarge](crate::MergeError::CanvasTooLarge) - If the canvas buffer would not fit in memory.
+    pub fn try_new(dimensions: (u32, u32), options: PackingOptions) -> Result<Self, MergeError> {
+        let (width, height) = check_canvas_size::<P>(dimensions.0, dimensions.1)?;
+        let padding = padding_of(&options);
+
+        Ok(Self {
+            canvas: ImageCell::new(Image::new(width, height)),
+            bin: Bin::new(
+                options.algorithm,
+                width.saturating_add(padding.x),
+                height.saturating_add(padding.y),
+                padding,
+            ),
+            options,
+            placements: Vec::new(),
+            composite: Composite::default(),
+        })
+    }
+
+    /// Packs all the given images onto the most compact canvas the packer can find, trying several canvas widths and
+    /// keeping the one with the smallest area. More images can be pushed afterwards if they fit in the leftover space.
+    /// # Arguments
+    /// * `images` - The images to pack. Their placements are returned by `get_placements` in the same order.
+    /// * `options` - How images are packed.
+    ///
+    /// # Panics
+    /// This function will panic if no images are given or the canvas is too large. Use `try_pack` to handle these cases.
+    pub fn pack(images: &[&BufferedImage<P>], options: PackingOptions) -> Self {
+        Self::try_pack(images, options).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Same as `pack`, but returns a [MergeError](crate::MergeError) instead of panicking when the images cannot be packed.
+    ///
+    /// # Errors
+    /// * [MergeError::InvalidLayout](crate::MergeError::InvalidLayout) - If no images are given.
+    /// * [MergeError::CanvasTooLarge](crate::MergeError::CanvasTooLarge) - If the canvas dimensions overflow or its
+    ///   buffer would not fit in memory.
+    pub fn try_pack(
+        images: &[&BufferedImage<P>],
+        options: PackingOptions,
+    ) -> Result<Self, MergeError> {
+        if images.is_empty() {
+            return Err(MergeError::InvalidLayout(
+                "there must be at least one image to pack",
+            ));
+        }
+
+        let padding = padding_of(&options);
+        let sizes = padded_sizes(images, padding, options.allow_rotation);
+
+        // Every candidate bin is tall enough to stack all the images on top of each other, so packing only fails if
+        // the candidate is too narrow. The padding is always added after rotating.
+        let (narrowest, widest, tallest) = sizes.iter().fold(
+            (0, 0, 0),
+            |(narrowest, widest, tallest), &((w, h), (rotated_w, rotated_h))| {
+                (
+                    narrowest.max(w.min(rotated_w)),
+                    widest + w.max(rotated_w),
+                    tallest + h.max(rotated_h),
+                )
+            },
+        );
+
+        let height = u32::try_from(tallest).map_err(|_| MergeError::CanvasTooLarge)?;
+        let area: u64 = sizes.iter().map(|&((w, h), _)| w * h).sum();
+        let side = (area as f64).sqrt();
+
+        let mut candidates: Vec<u64> = [1.0, 1.1, 1.25, 1.5, 1.75, 2.0]
+            .iter()
+            .map(|factor| (side * factor).ceil() as u64)
+            .chain([narrowest])
+            .map(|width| width.clamp(narrowest, widest.max(narrowest)))
+            .collect();
+        candidates.sort_unstable();
+        candidates.dedup();
+
+        let order = packing_order(&sizes);
+        let mut best: Option<Packed> = None;
+        for width in candidates {
+            let Ok(width) = u32::try_from(width) else {
+                continue;
+            };
+
+            let mut bin = Bin::new(options.algorithm, width, height, padding);
+            let Some(placements) = place_all(&mut bin, images, &order, &options) else {
+                continue;
+            };
+
+            let extent = placements
+                .iter()
+                .fold((0, 0), |(width, height), placement| {
+                    (
+                        width.max(placement.rect.x + placement.rect.width),
+                        height.max(placement.rect.y + placement.rect.height),
+                    )
+                });
+
+            // Prefer the smallest canvas, then the squarest one.
+            let score = (extent.0 as u64 * extent.1 as u64, extent.0.max(extent.1));
+            if best.as_ref().is_none_or(|best| score < best.score) {
+                best = Some(Packed {
+                    score,
+                    bin,
+                    placements,
+                    extent,
+                });
+            }
+        }
+
+        let Packed {
+            mut bin,
+            placements,
+            extent: (width, height),
+            ..
+        } = best.ok_or(MergeError::CanvasTooLarge)?;
+        let (width, height) = check_canvas_size::<P>(width, height)?;
+        bin.shrink(
+            width.saturating_add(padding.x),
+            height.saturating_add(padding.y),
+        );
+
+        let merger = Self {
+            canvas: ImageCell::new(Image::new(width, height)),
+            bin,
+            options,
+            placements,
+            composite: Composite::default(),
+        };
+
+        // Safety: the placements were packed without overlapping, and nothing else holds the new merger.
+        unsafe { merger.paste_all(images, &merger.placements) };
+        Ok(merger)
+    }
+
+    /// Returns the number of images that have been pasted to the canvas.
+    pub fn get_num_images(&self) -> u32 {
+        self.placements.len() as u32
+    }
+
+    /// Returns where every image was placed on the canvas, in the order the images were pushed.
+    pub fn get_placements(&self) -> &[Placement] {
+        &self.placements
+    }
+
+    /// Returns an [Atlas](crate::Atlas) of every image pushed so far, which can be serialized for other tools to use
+    /// the canvas as a sprite atlas.
+    pub fn get_atlas(&self) -> Atlas {
+        Atlas::new(self.canvas.dimensions(), &self.placements)
+    }
+
+    /// Returns the options the merger packs images with.
+    pub fn get_options(&self) -> &PackingOptions {
+        &self.options
+    }
+
+    /// Returns how pushed images are combined with the canvas.
+    pub fn get_composite(&self) -> &Composite {
+        &self.composite
+    }
+
+    /// Sets how pushed images are combined with the canvas. Only affects images pushed after this call.
+    /// # Arguments
+    /// * `composite` - The new composite to use.
+    pub fn set_composite(&mut self, composite: Composite) {
+        self.composite = composite;
+    }
+
+    /// Places every image of a bulk push in a copy of the free space, so a failed bulk push leaves the merger untouched.
+    /// Returns the free space left after placing them, and their placements in the order of the images.
+    fn place_bulk<Q, V>(&self, images: &[&V]) -> Result<(Bin, Vec<Placement>), MergeError>
+    where
+        Q: Pixel,
+        V: ImageView<Pixel = Q>,
+    {
+        let mut bin = self.bin.clone();
+        let sizes = padded_sizes(
+            images,
+            padding_of(&self.options),
+            self.options.allow_rotation,
+        );
+
+        let order = packing_order(&sizes);
+        let placements = place_all(&mut bin, images, &order, &self.options).ok_or_else(|| {
+            // Report how many of the images would have fit on their own.
+            let mut bin = self.bin.clone();
+            let remaining = order
+                .iter()
+                .take_while(|&&index| place(&mut bin, images[index], &self.options).is_some())
+                .count();
+
+            MergeError::CanvasFull {
+                remaining: remaining as u32,
+                requested: images.len() as u32,
+            }
+        })?;
+
+        Ok((bin, placements))
+    }
+
+    /// Pastes every image onto the canvas at its placement, in parallel.
+    /// # Safety
+    /// Nothing else may read or write the canvas while the images are pasted, and the placements must not overlap.
+    unsafe fn paste_all<V>(&self, images: &[&V], placements: &[Placement])
+    where
+        V: ImageView<Pixel = P>,
+    {
+        let _batch = self.canvas.batch();
+        (0..images.len()).into_par_iter().for_each(|index| {
+            // Safety: every image is pasted at its own placement, which the caller keeps from overlapping another
+            // and from being read while it is written.
+            unsafe {
+                paste_placed(
+                    &self.canvas,
+                    images[index],
+                    &placements[index],
+                    &self.composite,
+                );
+            }
+        });
+    }
+}
+
+impl<P> Merger<P, Vec<<P as Pixel>::Subpixel>> for PackingMerger<P>
+where
+    P: Pixel + Sync + Send,
+    <P as Pixel>::Subpixel: Sync + Send,
+{
+    fn get_canvas(&self) -> &BufferedImage<P> {
+        &self.canvas
+    }
+
+    fn into_canvas(self) -> BufferedImage<P> {
+        self.canvas.into_inner()
+    }
+
+    fn try_push<V>(&mut self, image: &V) -> Result<(), MergeError>
+    where
+        V: ImageView<Pixel = P>,
+    {
+        let placement =
+            place(&mut self.bin, image, &self.options).ok_or(MergeError::CanvasFull {
+                remaining: 0,
+                requested: 1,
+            })?;
+
+        // Safety: the merger is borrowed mutably, so nothing else reads or writes the canvas.
+        unsafe { paste_placed(&self.canvas, image, &placement, &self.composite) };
+        self.placements.push(placement);
+
+        Ok(())
+    }
+
+    fn try_bulk_push<V>(&mut self, images: &[&V]) -> Result<(), MergeError>
+    where
+        V: ImageView<Pixel = P>,
+    {
+        let (bin, placements) = self.place_bulk(images)?;
+
+        // Safety: the placements were packed without overlapping, and the merger is borrowed mutably.
+        unsafe { self.paste_all(images, &placements) };
+        self.bin = bin;
+        self.placements.extend(placements);
+
+        Ok(())
+    }
+}
+
+impl<P> ConvertingMerger<P> for PackingMerger<P>
+where
+    P: Pixel + Sync + Send,
+    <P as Pixel>::Subpixel: Sync + Send,
+{
+    fn try_push_converted<Q, V>(&mut self, image: &V) -> Result<(), MergeError>
+    where
+        Q: ConvertPixel<P> + Sync,
+        <Q as Pixel>::Subpixel: Send + Sync,
+        V: ImageView<Pixel = Q>,
+    {
+        let placement =
+            place(&mut self.bin, image, &self.options).ok_or(MergeError::CanvasFull {
+                remaining: 0,
+                requested: 1,
+            })?;
+
+        // Safety: the merger is borrowed mutably, so nothing else reads or writes the canvas.
+        unsafe { paste_placed_converted(&self.canvas, image, &placement, &self.composite) };
+        self.placements.push(placement);
+
+        Ok(())
+    }
+
+    fn try_bulk_push_converted<Q, V>(&mut self, images: &[&V]) -> Result<(), MergeError>
+    where
+        Q: ConvertPixel<P> + Sync,
+        <Q as Pixel>::Subpixel: Send + Sync,
+        V: ImageView<Pixel = Q>,
+    {
+        let (bin, placements) = self.place_bulk(images)?;
+
+        let _batch = self.canvas.batch();
+        (0..images.len()).into_par_iter().for_each(|index| {
+            // Safety: every image is pasted at its own placement, which never overlaps another. The merger is borrowed
+            // mutably, so nothing else reads or writes the canvas.
+            unsafe {
+                paste_placed_converted(
+                    &self.canvas,
+                    images[index],
+                    &placements[index],
+                    &self.composite,
+                );
+            }
+        });
+
+        self.bin = bin;
+        self.placements.extend(placements);
+
+        Ok(())
+    }
+}
+
+/// Returns the padding of the given options, or no padding.
+fn padding_of(options: &PackingOptions) -> Padding {
+    options.padding.unwrap_or(Point { x: 0, y: 0 })
+}
+
+/// The padded dimensions of an image, as (unrotated, rotated).
+type PaddedSize = ((u64, u64), (u64, u64));
+
+/// Returns the padded dimensions of every image, as (unrotated, rotated). The rotated dimensions are the unrotated ones
+/// if rotation is not allowed.
+fn padded_sizes<Q, V>(images: &[&V], padding: Padding, allow_rotation: bool) -> Vec<PaddedSize>
+where
+    Q: Pixel,
+    V: ImageView<Pixel = Q>,
+{
+    images
+        .iter()
+        .map(|image| {
+            let (width, height) = image.view_dimensions();
+            let (width, height) = (width as u64, height as u64);
+            let (padding_x, padding_y) = (padding.x as u64, padding.y as u64);
+
+            let unrotated = (width + padding_x, height + padding_y);
+            if allow_rotation {
+                (unrotated, (height + padding_x, width + padding_y))
+            } else {
+                (unrotated, unrotated)
+            }
+        })
+        .collect()
+}
+
+/// Returns the order images are best packed in: largest side first, then largest area first.
+fn packing_order(sizes: &[PaddedSize]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..sizes.len()).collect();
+    order.sort_by_key(|&index| {
+        let ((w, h), _) = sizes[index];
+        std::cmp::Reverse((w.max(h), w * h))
+    });
+    order
+}
+
+/// Places a single image in the bin.
+fn place<Q, V>(bin: &mut Bin, image: &V, options: &PackingOptions) -> Option<Placement>
+where
+    Q: Pixel,
+    V: ImageView<Pixel = Q>,
+{
+    let (width, height) = image.view_dimensions();
+    let (rect, rotated) = bin.insert(width, height, options.allow_rotation)?;
+    Some(Placement { rect, rotated })
+}
+
+/// Places every image in the bin following the given order, returning the placements in the order of the images.
+fn place_all<Q, V>(
+    bin: &mut Bin,
+    images: &[&V],
+    order: &[usize],
+    options: &PackingOptions,
+) -> Option<Vec<Placement>>
+where
+    Q: Pixel,
+    V: ImageView<Pixel = Q>,
+{
+    let mut placements = vec![None; images.len()];
+    for &index in order {
+        placements[index] = Some(place(bin, images[index], options)?);
+    }
+
+    placements.into_iter().collect()
+}
+
+/// A candidate canvas computed by `try_pack`.
+struct Packed {
+    score: (u64, u32),          // The area of the canvas, then its longest side.
+    bin: Bin,                   // The free space left on the canvas.
+    placements: Vec<Placement>, // Where every image was placed.
+    extent: (u32, u32),         // The dimensions of the area the images cover.
+}
+
+/// Pastes an image onto the canvas at its placement, rotating it first if needed.
+/// # Safety
+/// The same as for [paste_composite](crate::raw::paste_composite), for the area of the placement.
+unsafe fn paste_placed<P, V>(
+    canvas: &ImageCell<P, image::ImageBuffer<P, Vec<P::Subpixel>>>,
+    image: &V,
+    placement: &Placement,
+    composite: &Composite,
+) where
+    P: Pixel + Sync,
+    <P as Pixel>::Subpixel: Send + Sync,
+    V: ImageView<Pixel = P>,
+{
+    let loc = Point {
+        x: placement.rect.x,
+        y: placement.rect.y,
+    };
+
+    if placement.rotated {
+        paste_composite(canvas, &rotate90(image), loc, composite);
+    } else {
+        paste_composite(canvas, image, loc, composite);
+    }
+}
+
+/// Same as `paste_placed`, but converts the image into the pixel type of the canvas as it is pasted.
+/// # Safety
+/// The same as for `paste_placed`.
+unsafe fn paste_placed_converted<P, Q, V>(
+    canvas: &ImageCell<P, image::ImageBuffer<P, Vec<P::Subpixel>>>,
+    image: &V,
+    placement: &Placement,
+    composite: &Composite,
+) where
+    P: Pixel + Sync,
+    <P as Pixel>::Subpixel: Send + Sync,
+    Q: ConvertPixel<P> + Sync,
+    <Q as Pixel>::Subpixel: Send + Sync,
+    V: ImageView<Pixel = Q>,
+{
+    let loc = Point {
+        x: placement.rect.x,
+        y: placement.rect.y,
+    };
+
+    if placement.rotated {
+        paste_converted(canvas, &rotate90(image), loc, composite);
+    } else {
+        paste_converted(canvas, image, loc, composite);
+    }
+}
@@ -0,0 +1,261 @@
+use super::core::Merger;
+use crate::{BufferedImage, MergeError, TryFromWithFormat};
+
+use image::{ImageError, ImageFormat, ImageReader, Pixel};
+use rayon::prelude::*;
+use std::{
+    cmp::Ordering,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// The order the images in a directory are merged in, used by `from_dir` constructors and [image_paths](image_paths).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SortOrder {
+    /// Sorted by file name. This is the default.
+    #[default]
+    Name,
+    /// Sorted by file name, comparing runs of digits by their numeric value so `2.png` comes before `10.png`.
+    Natural,
+    /// Sorted by last modification time, oldest first. Files with the same modification time are sorted by name.
+    Modified,
+    /// The order the file system lists the files in, which is not guaranteed to be stable.
+    Unsorted,
+}
+
+/// Lists the image files in a directory, in the given order. Files are recognized as images by their extension;
+/// subdirectories are not searched.
+/// # Arguments
+/// * `dir` - The directory to list.
+/// * `order` - The order to list the images in.
+/// # Errors
+/// * [MergeError::LoadError](crate::MergeError::LoadError) - If the directory cannot be read.
+pub fn image_paths(dir: impl AsRef<Path>, order: SortOrder) -> Result<Vec<PathBuf>, MergeError> {
+    let dir = dir.as_ref();
+    let load_error = |err| MergeError::LoadError {
+        path: dir.to_path_buf(),
+        error: ImageError::IoError(err),
+    };
+
+    let mut paths = fs::read_dir(dir)
+        .map_err(load_error)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file() && ImageFormat::from_path(path).is_ok())
+        .collect::<Vec<_>>();
+
+    match order {
+        SortOrder::Name => paths.sort(),
+        SortOrder::Natural => paths.sort_by(|a, b| natural_cmp(&file_name(a), &file_name(b))),
+        SortOrder::Modified => {
+            let mut modified = paths
+                .into_iter()
+                .map(|path| {
+                    let modified = fs::metadata(&path)
+                        .and_then(|metadata| metadata.modified())
+                        .map_err(load_error)?;
+                    Ok((modified, path))
+                })
+                .collect::<Result<Vec<_>, MergeError>>()?;
+
+            modified.sort();
+            paths = modified.into_iter().map(|(_, path)| path).collect();
+        }
+        SortOrder::Unsorted => {}
+    }
+
+    Ok(paths)
+}
+
+/// Decodes the image at the given path, sniffing its format from its contents and falling back to its extension, then
+/// converts it to the pixel type `P`. This is how every image pushed by path is loaded.
+/// # Arguments
+/// * `path` - The path of the image to load.
+/// # Errors
+/// * [MergeError::LoadError](crate::MergeError::LoadError) - If the file cannot be read or decoded.
+pub fn load_image<P>(path: impl AsRef<Path>) -> Result<BufferedImage<P>, MergeError>
+where
+    P: Pixel,
+    BufferedImage<P>: TryFromWithFormat<Vec<u8>>,
+{
+    let path = path.as_ref();
+    let load_error = |error| MergeError::LoadError {
+        path: path.to_path_buf(),
+        error,
+    };
+
+    let bytes = fs::read(path).map_err(|err| load_error(ImageError::IoError(err)))?;
+    let format = image::guess_format(&bytes)
+        .or_else(|_| ImageFormat::from_path(path))
+        .map_err(load_error)?;
+
+    BufferedImage::try_from_with_format(bytes, format).map_err(|err| match err {
+        MergeError::DecodeError(error) => load_error(error),
+        err => err,
+    })
+}
+
+/// Reads the dimensions of the image at the given path from its header, without decoding it.
+pub(crate) fn image_dimensions(path: &Path) -> Result<(u32, u32), MergeError> {
+    ImageReader::open(path)
+        .map_err(ImageError::IoError)
+        .and_then(|reader| reader.with_guessed_format().map_err(ImageError::IoError))
+        .and_then(|reader| reader.into_dimensions())
+        .map_err(|error| MergeError::LoadError {
+            path: path.to_path_buf(),
+            error,
+        })
+}
+
+/// An extension to every [Merger](crate::Merger) with a `Vec` canvas that pushes images straight from files. Files are
+/// decoded in parallel, their format sniffed from their contents, and converted to the pixel type of the canvas.
+///
+/// This trait is implemented for every such merger, it only needs to be imported.
+///
+/// # Example
+/// ```no_run
+/// use image_merger::{KnownSizeMerger, PathMerger, Rgba};
+///
+/// let mut merger: KnownSizeMerger<Rgba<u8>, _> = KnownSizeMerger::new((100, 100), 5, 10, None);
+/// merger.bulk_push_paths(&["first.png", "second.jpg"]);
+/// ```
+pub trait PathMerger<P>: Merger<P, Vec<P::Subpixel>>
+where
+    P: Pixel + Send + Sync,
+    <P as Pixel>::Subpixel: Send + Sync,
+    BufferedImage<P>: TryFromWithFormat<Vec<u8>> + Send,
+{
+    /// Decodes the image at the given path and pushes it onto the canvas.
+    /// # Arguments
+    /// * `path` - The path of the image file.
+    /// # Panics
+    /// This function will panic if the image cannot be loaded or pushed onto the canvas. Use `try_push_path` to handle
+    /// the error instead.
+    fn push_path(&mut self, path: impl AsRef<Path>) {
+        if let Err(err) = self.try_push_path(path) {
+            panic!("{}", err);
+        }
+    }
+
+    /// Decodes the images at the given paths in parallel and pushes them onto the canvas, in order. Images are decoded
+    /// and pushed a few at a time, so only a handful of decoded images are held in memory at once.
+    /// # Arguments
+    /// * `paths` - The paths of the image files.
+    /// # Panics
+    /// This function will panic if any image cannot be loaded or pushed onto the canvas. Use `try_bulk_push_paths` to
+    /// handle the error instead.
+    fn bulk_push_paths<Q>(&mut self, paths: &[Q])
+    where
+        Q: AsRef<Path> + Sync,
+    {
+        if let Err(err) = self.try_bulk_push_paths(paths) {
+            panic!("{}", err);
+        }
+    }
+
+    /// Same as `push_path`, but returns a [MergeError](crate::MergeError) instead of panicking.
+    /// # Errors
+    /// * [MergeError::LoadError](crate::MergeError::LoadError) - If the file cannot be read or decoded.
+    /// * Any error returned by `try_push`.
+    fn try_push_path(&mut self, path: impl AsRef<Path>) -> Result<(), MergeError> {
+        let image = load_image(path)?;
+        self.try_push(&image)
+    }
+
+    /// Same as `bulk_push_paths`, but returns a [MergeError](crate::MergeError) instead of panicking. Images are pushed
+    /// in batches, so if an error is returned, the batches before the failing one have already been pushed.
+    /// # Errors
+    /// * [MergeError::LoadError](crate::MergeError::LoadError) - If a file cannot be read or decoded.
+    /// * Any error returned by `try_bulk_push`.
+    fn try_bulk_push_paths<Q>(&mut self, paths: &[Q]) -> Result<(), MergeError>
+    where
+        Q: AsRef<Path> + Sync,
+    {
+        // Decode as many images at once as there are threads to decode them, keeping memory use bounded.
+        let batch_size = rayon::current_num_threads().max(1);
+        for batch in paths.chunks(batch_size) {
+            let images = batch
+                .par_iter()
+                .map(|path| load_image(path))
+                .collect::<Result<Vec<BufferedImage<P>>, MergeError>>()?;
+
+            let images: Vec<&BufferedImage<P>> = images.iter().collect();
+            self.try_bulk_push(&images)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<P, M> PathMerger<P> for M
+where
+    M: Merger<P, Vec<P::Subpixel>>,
+    P: Pixel + Send + Sync,
+    <P as Pixel>::Subpixel: Send + Sync,
+    BufferedImage<P>: TryFromWithFormat<Vec<u8>> + Send,
+{
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Compares two names, treating runs of digits as numbers.
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut a = a.chars().peekable();
+    let mut b = b.chars().peekable();
+
+    loop {
+        match (a.peek().copied(), b.peek().copied()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let take_number = |chars: &mut std::iter::Peekable<std::str::Chars>| {
+                    let mut digits = String::new();
+                    while let Some(c) = chars.next_if(char::is_ascii_digit) {
+                        digits.push(c);
+                    }
+                    digits
+                };
+
+                let (x, y) = (take_number(&mut a), take_number(&mut b));
+                let (x_trimmed, y_trimmed) = (x.trim_start_matches('0'), y.trim_start_matches('0'));
+
+                // Longer numbers are larger, equally long ones compare digit by digit.
+                let ordering = x_trimmed
+                    .len()
+                    .cmp(&y_trimmed.len())
+                    .then_with(|| x_trimmed.cmp(y_trimmed))
+                    .then_with(|| x.len().cmp(&y.len()));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            (Some(x), Some(y)) => {
+                if x != y {
+                    return x.cmp(&y);
+                }
+                a.next();
+                b.next();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_natural_cmp() {
+        let mut names = vec!["img10.png", "img2.png", "img1.png", "img02.png", "a.png"];
+        names.sort_by(|a, b| natural_cmp(a, b));
+        assert_eq!(
+            names,
+            ["a.png", "img1.png", "img2.png", "img02.png", "img10.png"]
+        );
+    }
+}
@@ -0,0 +1,246 @@
+use super::core::{Point, Rect};
+use crate::{
+    cell::ImageCell,
+    functions::{crop, paste_decorated, paste_decorated_converted, resize},
+    Alignment, CellDecoration, Composite, ConvertPixel, ImageView, MergeError, ResizeFilter,
+};
+
+use image::Pixel;
+use std::ops::DerefMut;
+
+/// Decides what a merger does with an image whose dimensions do not match the size of the cells on its canvas.
+/// Images that match the cell size exactly are always pasted as is.
+///
+/// # Example
+/// ```
+/// use image_merger::{KnownSizeMerger, Merger, Image, Rgb, SizePolicy};
+///
+/// let mut merger: KnownSizeMerger<Rgb<u8>, _> = KnownSizeMerger::new((100, 100), 5, 10, None);
+/// merger.set_size_policy(SizePolicy::CenterSmaller);
+///
+/// // This image will be centered inside its 100x100 cell.
+/// merger.push(&Image::new(50, 50));
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SizePolicy {
+    /// Images that do not match the cell size are rejected with a
+    /// [MergeError::DimensionMismatch](crate::MergeError::DimensionMismatch). This is the default.
+    #[default]
+    Reject,
+    /// Images are pasted at the top left corner of their cell. Any part of the image that lies outside of the cell is clipped.
+    Clip,
+    /// Images smaller than the cell are centered inside of it. Images larger than the cell are centered on the cell and
+    /// clipped to its bounds. Mergers with an [Alignment](crate::Alignment) other than the default place images at that
+    /// alignment instead of the center.
+    CenterSmaller,
+    /// Images are resized to exactly fit their cell.
+    ResizeToFit,
+}
+
+/// Describes how an image is fitted into a cell, as decided by a [SizePolicy](SizePolicy).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Fit {
+    /// Paste the `source` area of the image at `offset` from the top left corner of the cell.
+    Crop { offset: Point, source: Rect },
+    /// Resize the image to the dimensions of the cell.
+    Resize,
+}
+
+impl SizePolicy {
+    /// Decides how an image with the given dimensions is fitted into a cell with the given dimensions. This is cheap to
+    /// compute, so mergers can validate every image before pasting any of them.
+    /// # Arguments
+    /// * `dimensions` - The dimensions, (x, y), of the image.
+    /// * `cell` - The dimensions, (x, y), of the cell.
+    /// * `alignment` - Where [CenterSmaller](SizePolicy::CenterSmaller) places the image inside of the cell.
+    pub(crate) fn fit(
+        &self,
+        dimensions: (u32, u32),
+        cell: (u32, u32),
+        alignment: Alignment,
+    ) -> Result<Fit, MergeError> {
+        let whole = Fit::Crop {
+            offset: Point { x: 0, y: 0 },
+            source: Rect {
+                x: 0,
+                y: 0,
+                width: dimensions.0,
+                height: dimensions.1,
+            },
+        };
+
+        if dimensions == cell {
+            return Ok(whole);
+        }
+
+        match self {
+            SizePolicy::Reject => Err(MergeError::DimensionMismatch {
+                expected: cell,
+                actual: dimensions,
+            }),
+            SizePolicy::Clip => Ok(Fit::Crop {
+                offset: Point { x: 0, y: 0 },
+                source: Rect {
+                    x: 0,
+                    y: 0,
+                    width: dimensions.0.min(cell.0),
+                    height: dimensions.1.min(cell.1),
+                },
+            }),
+            SizePolicy::CenterSmaller => {
+                // Along each axis, either the image is aligned in the cell or the cell is aligned on the image.
+                let (offset_x, offset_y) = alignment.offset((
+                    cell.0.saturating_sub(dimensions.0),
+                    cell.1.saturating_sub(dimensions.1),
+                ));
+                let (source_x, source_y) = alignment.offset((
+                    dimensions.0.saturating_sub(cell.0),
+                    dimensions.1.saturating_sub(cell.1),
+                ));
+                let (width, height) = (dimensions.0.min(cell.0), dimensions.1.min(cell.1));
+
+                Ok(Fit::Crop {
+                    offset: Point {
+                        x: offset_x,
+                        y: offset_y,
+                    },
+                    source: Rect {
+                        x: source_x,
+                        y: source_y,
+                        width,
+                        height,
+                    },
+                })
+            }
+            SizePolicy::ResizeToFit => Ok(Fit::Resize),
+        }
+    }
+}
+
+impl Fit {
+    /// Pastes an image onto the canvas according to this fit.
+    /// # Arguments
+    /// * `canvas` - The canvas to paste onto.
+    /// * `image` - The image to paste.
+    /// * `cell` - The area of the cell the image is being pasted into.
+    /// * `composite` - How to combine the image with the canvas.
+    /// * `decoration` - The border and rounded corners drawn around the image.
+    /// * `filter` - The filter used if the image has to be resized.
+    ///
+    /// # Safety
+    /// The same as for [paste_decorated](crate::raw::paste_decorated), for the frame of the cell.
+    pub(crate) unsafe fn paste<P, Container, V>(
+        &self,
+        canvas: &ImageCell<P, image::ImageBuffer<P, Container>>,
+        image: &V,
+        cell: Rect,
+        composite: &Composite,
+        decoration: &CellDecoration<P>,
+        filter: ResizeFilter,
+    ) where
+        P: Pixel + Sync,
+        <P as Pixel>::Subpixel: Send + Sync,
+        Container: DerefMut<Target = [P::Subpixel]> + Sync,
+        V: ImageView<Pixel = P>,
+    {
+        match *self {
+            Fit::Crop { offset, source } => {
+                let loc = Point {
+                    x: cell.x + offset.x,
+                    y: cell.y + offset.y,
+                };
+
+                if (source.width, source.height) == image.view_dimensions() {
+                    paste_decorated(canvas, image, loc, cell, composite, decoration);
+                } else {
+                    paste_decorated(
+                        canvas,
+                        &crop(image, source),
+                        loc,
+                        cell,
+                        composite,
+                        decoration,
+                    );
+                }
+            }
+            Fit::Resize => {
+                let resized = resize(image, cell.width, cell.height, filter);
+                paste_decorated(
+                    canvas,
+                    &resized,
+                    Point {
+                        x: cell.x,
+                        y: cell.y,
+                    },
+                    cell,
+                    composite,
+                    decoration,
+                );
+            }
+        }
+    }
+    /// Same as `paste`, but converts the image into the pixel type of the canvas as it is pasted.
+    /// # Arguments
+    /// * `canvas` - The canvas to paste onto.
+    /// * `image` - The image to convert and paste.
+    /// * `cell` - The area of the cell the image is being pasted into.
+    /// * `composite` - How to combine the image with the canvas.
+    /// * `decoration` - The border and rounded corners drawn around the image.
+    /// * `filter` - The filter used if the image has to be resized.
+    ///
+    /// # Safety
+    /// The same as for `paste`.
+    pub(crate) unsafe fn paste_converted<P, Q, Container, V>(
+        &self,
+        canvas: &ImageCell<P, image::ImageBuffer<P, Container>>,
+        image: &V,
+        cell: Rect,
+        composite: &Composite,
+        decoration: &CellDecoration<P>,
+        filter: ResizeFilter,
+    ) where
+        P: Pixel + Sync,
+        <P as Pixel>::Subpixel: Send + Sync,
+        Q: ConvertPixel<P> + Sync,
+        <Q as Pixel>::Subpixel: Send + Sync,
+        Container: DerefMut<Target = [P::Subpixel]> + Sync,
+        V: ImageView<Pixel = Q>,
+    {
+        match *self {
+            Fit::Crop { offset, source } => {
+                let loc = Point {
+                    x: cell.x + offset.x,
+                    y: cell.y + offset.y,
+                };
+
+                if (source.width, source.height) == image.view_dimensions() {
+                    paste_decorated_converted(canvas, image, loc, cell, composite, decoration);
+                } else {
+                    paste_decorated_converted(
+                        canvas,
+                        &crop(image, source),
+                        loc,
+                        cell,
+                        composite,
+                        decoration,
+                    );
+                }
+            }
+            Fit::Resize => {
+                // Resize before converting, so the image is resampled at its own bit depth.
+                let resized = resize(image, cell.width, cell.height, filter);
+                paste_decorated_converted(
+                    canvas,
+                    &resized,
+                    Point {
+                        x: cell.x,
+                        y: cell.y,
+                    },
+                    cell,
+                    composite,
+                    decoration,
+                );
+            }
+        }
+    }
+}
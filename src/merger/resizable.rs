@@ -1,6 +1,247 @@
-use crate::BufferedImage;
+use super::core::{Point, Rect};
+use crate::{
+    cell::ImageCell,
+    functions::{crop, paste, resize},
+    BufferedImage, Image, MergeError,
+};
 use image::Pixel;
 
+/// The filter used when resizing images. Filters other than [Nearest](ResizeFilter::Nearest) are applied with a parallel,
+/// separable resampler and trade speed for smoother, less aliased results.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ResizeFilter {
+    /// Nearest neighbor sampling. The fastest filter, but produces jagged edges when scaling. This is the default.
+    #[default]
+    Nearest,
+    /// Linear interpolation between neighboring pixels.
+    Bilinear,
+    /// A bicubic Catmull-Rom spline. Sharper than bilinear filtering.
+    CatmullRom,
+    /// The Mitchell-Netravali bicubic filter, balancing sharpness against ringing.
+    Mitchell,
+    /// The Lanczos filter with a window of 3. The sharpest and slowest filter.
+    Lanczos3,
+    /// Box filtering, which averages every source pixel covered by a destination pixel when downscaling.
+    Box,
+}
+
+impl ResizeFilter {
+    /// Returns the support, the radius of the kernel at a scale of one, of this filter.
+    pub(crate) fn support(&self) -> f32 {
+        match self {
+            ResizeFilter::Nearest | ResizeFilter::Box => 0.5,
+            ResizeFilter::Bilinear => 1.0,
+            ResizeFilter::CatmullRom | ResizeFilter::Mitchell => 2.0,
+            ResizeFilter::Lanczos3 => 3.0,
+        }
+    }
+
+    /// Evaluates the filter's kernel at the given distance from the sample's center.
+    pub(crate) fn kernel(&self, x: f32) -> f32 {
+        let x = x.abs();
+        match self {
+            ResizeFilter::Nearest | ResizeFilter::Box => {
+                if x <= 0.5 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            ResizeFilter::Bilinear => (1.0 - x).max(0.0),
+            ResizeFilter::CatmullRom => cubic(x, 0.0, 0.5),
+            ResizeFilter::Mitchell => cubic(x, 1.0 / 3.0, 1.0 / 3.0),
+            ResizeFilter::Lanczos3 => {
+                if x < 3.0 {
+                    sinc(x) * sinc(x / 3.0)
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+/// The family of bicubic filters described by Mitchell and Netravali, parameterized by `b` and `c`.
+fn cubic(x: f32, b: f32, c: f32) -> f32 {
+    let value = if x < 1.0 {
+        (12.0 - 9.0 * b - 6.0 * c) * x.powi(3)
+            + (-18.0 + 12.0 * b + 6.0 * c) * x.powi(2)
+            + (6.0 - 2.0 * b)
+    } else if x < 2.0 {
+        (-b - 6.0 * c) * x.powi(3)
+            + (6.0 * b + 30.0 * c) * x.powi(2)
+            + (-12.0 * b - 48.0 * c) * x
+            + (8.0 * b + 24.0 * c)
+    } else {
+        0.0
+    };
+
+    value / 6.0
+}
+
+/// The normalized sinc function.
+fn sinc(x: f32) -> f32 {
+    if x == 0.0 {
+        1.0
+    } else {
+        let x = x * std::f32::consts::PI;
+        x.sin() / x
+    }
+}
+
+/// Describes where an image is placed inside of a larger area. Defaults to [Center](Alignment::Center).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Alignment {
+    /// Placed in the top left corner of the area.
+    TopLeft,
+    /// Centered horizontally along the top edge of the area.
+    Top,
+    /// Placed in the top right corner of the area.
+    TopRight,
+    /// Centered vertically along the left edge of the area.
+    Left,
+    /// Centered both horizontally and vertically in the area.
+    #[default]
+    Center,
+    /// Centered vertically along the right edge of the area.
+    Right,
+    /// Placed in the bottom left corner of the area.
+    BottomLeft,
+    /// Centered horizontally along the bottom edge of the area.
+    Bottom,
+    /// Placed in the bottom right corner of the area.
+    BottomRight,
+}
+
+impl Alignment {
+    /// Returns the offset, (x, y), of an aligned item given the free space, (x, y), around it.
+    pub(crate) fn offset(&self, free: (u32, u32)) -> (u32, u32) {
+        let (horizontal, vertical) = match self {
+            Alignment::TopLeft => (0, 0),
+            Alignment::Top => (1, 0),
+            Alignment::TopRight => (2, 0),
+            Alignment::Left => (0, 1),
+            Alignment::Center => (1, 1),
+            Alignment::Right => (2, 1),
+            Alignment::BottomLeft => (0, 2),
+            Alignment::Bottom => (1, 2),
+            Alignment::BottomRight => (2, 2),
+        };
+
+        (free.0 * horizontal / 2, free.1 * vertical / 2)
+    }
+}
+
+/// Decides how an image is fitted into the dimensions set on a [ResizableMerger](ResizableMerger) when its aspect ratio
+/// does not match.
+///
+/// # Type Parameters
+/// * `P` - The pixel type of the canvas, used to fill the letterbox of [Contain](FitMode::Contain).
+///
+/// # Example
+/// ```
+/// use image_merger::{Alignment, FitMode, KnownSizeMerger, ResizableMerger, Image, Rgb};
+///
+/// let mut merger: KnownSizeMerger<Rgb<u8>, _> = KnownSizeMerger::new((100, 100), 10, 100, None);
+/// merger.set_fit_mode(FitMode::Contain {
+///     fill: Rgb([255, 255, 255]),
+///     alignment: Alignment::Center,
+/// });
+///
+/// // This image will be resized to 100x50 and centered on a white background.
+/// merger.push_resized(&Image::new(500, 250));
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum FitMode<P> {
+    /// The image is stretched to exactly the merger's dimensions, ignoring its aspect ratio. This is the default.
+    #[default]
+    Stretch,
+    /// The image is scaled to fit within the merger's dimensions, keeping its aspect ratio. The space left around the
+    /// image is filled with `fill`, and the image is placed according to `alignment`.
+    Contain {
+        /// The color of the space left around the scaled image.
+        fill: P,
+        /// Where the scaled image is placed inside of the merger's dimensions.
+        alignment: Alignment,
+    },
+    /// The image is scaled to cover the merger's dimensions, keeping its aspect ratio. The parts of the image that do not
+    /// fit are cropped away, keeping the part of the image given by `gravity`.
+    Cover {
+        /// The part of the scaled image that is kept when the rest is cropped away.
+        gravity: Alignment,
+    },
+}
+
+impl<P> FitMode<P>
+where
+    P: Pixel + Sync,
+    <P as Pixel>::Subpixel: Send + Sync,
+{
+    /// Resizes an image to exactly the given dimensions according to this fit mode.
+    /// # Arguments
+    /// * `image` - The image to resize.
+    /// * `dimensions` - The dimensions, (x, y), of the resized image.
+    /// * `filter` - The filter to resize the image with.
+    pub(crate) fn resize(
+        &self,
+        image: &BufferedImage<P>,
+        dimensions: (u32, u32),
+        filter: ResizeFilter,
+    ) -> BufferedImage<P> {
+        let (width, height) = dimensions;
+        let (image_width, image_height) = image.dimensions();
+        // There is no aspect ratio to keep when either side is empty, and no scaled size to clamp to a zero sized cell.
+        if image_width == 0 || image_height == 0 || width == 0 || height == 0 {
+            return resize(image, width, height, filter);
+        }
+
+        let scale_x = width as f64 / image_width as f64;
+        let scale_y = height as f64 / image_height as f64;
+
+        match *self {
+            FitMode::Stretch => resize(image, width, height, filter),
+            FitMode::Contain { fill, alignment } => {
+                let scale = scale_x.min(scale_y);
+                let scaled_width = ((image_width as f64 * scale).round() as u32).clamp(1, width);
+                let scaled_height = ((image_height as f64 * scale).round() as u32).clamp(1, height);
+
+                let scaled = resize(image, scaled_width, scaled_height, filter);
+                if (scaled_width, scaled_height) == dimensions {
+                    return scaled;
+                }
+
+                let (x, y) = alignment.offset((width - scaled_width, height - scaled_height));
+                let letterbox = ImageCell::new(Image::new_from_pixel(width, height, fill));
+                // Safety: the letterbox was just created, so nothing else reads or writes it.
+                unsafe { paste(&letterbox, &scaled, Point { x, y }) };
+
+                letterbox.into_inner()
+            }
+            FitMode::Cover { gravity } => {
+                // Crop the visible part of the image before resizing it, so no work is wasted on pixels that are cut.
+                let scale = scale_x.max(scale_y);
+                let visible_width = ((width as f64 / scale).round() as u32).clamp(1, image_width);
+                let visible_height =
+                    ((height as f64 / scale).round() as u32).clamp(1, image_height);
+
+                let (x, y) =
+                    gravity.offset((image_width - visible_width, image_height - visible_height));
+                let visible = crop(
+                    image,
+                    Rect {
+                        x,
+                        y,
+                        width: visible_width,
+                        height: visible_height,
+                    },
+                );
+
+                resize(&visible, width, height, filter)
+            }
+        }
+    }
+}
+
 /// A trait that allows a Merger to resize images before pasting them onto the canvas. It allows
 /// any existing merger to resize images before pasting. This is useful for when you have thousands of
 /// images to paste, but you don't want to hold all of them, at full size, in memory at once with a large
@@ -25,10 +266,64 @@ where
     /// Pushes an image onto the canvas after resizing it to the dimensions set on the merger.
     /// # Arguments
     /// * `image` - The image to push onto the canvas. Its pixel type, `P`, must match the canvas.
-    fn push_resized(&mut self, image: &BufferedImage<P>);
+    /// # Panics
+    /// This function will panic if the image cannot be pushed onto the canvas. Use `try_push_resized` to handle the error instead.
+    fn push_resized(&mut self, image: &BufferedImage<P>) {
+        if let Err(err) = self.try_push_resized(image) {
+            panic!("{}", err);
+        }
+    }
 
-    /// Bulk pushes N images onto the canvas after resizing them to the dimensions set on the merger.
+    /// Bulk pushes N images onto the canvas after resizing them to the dimensions set on the merger. Every resized image is
+    /// held in memory until all of them are pasted; use [StreamingMerger](crate::StreamingMerger) to merge more images than
+    /// fit in memory at once.
     /// # Arguments
     /// * `images` - The images to push onto the canvas. Note that the argument type is `&[&Image<...>]`, the func does not need to take ownership of the images, it only needs to read them. The pixel type, `P`, of the images must match the canvas.
-    fn bulk_push_resized(&mut self, images: &[&BufferedImage<P>]);
+    /// # Panics
+    /// This function will panic if the images cannot be pushed onto the canvas. Use `try_bulk_push_resized` to handle the error instead.
+    fn bulk_push_resized(&mut self, images: &[&BufferedImage<P>]) {
+        if let Err(err) = self.try_bulk_push_resized(images) {
+            panic!("{}", err);
+        }
+    }
+
+    /// Same as `push_resized`, but returns a [MergeError](crate::MergeError) instead of panicking when the image cannot be pushed.
+    /// # Arguments
+    /// * `image` - The image to push onto the canvas.
+    fn try_push_resized(&mut self, image: &BufferedImage<P>) -> Result<(), MergeError>;
+
+    /// Same as `bulk_push_resized`, but returns a [MergeError](crate::MergeError) instead of panicking when the images cannot be
+    /// pushed. No images are pasted if an error is returned.
+    /// # Arguments
+    /// * `images` - The images to push onto the canvas.
+    fn try_bulk_push_resized(&mut self, images: &[&BufferedImage<P>]) -> Result<(), MergeError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Alignment;
+    use image::Rgb;
+
+    #[test]
+    fn test_resize_to_empty_cell() {
+        let image = BufferedImage::new_from_pixel(4, 2, Rgb([255u8, 0, 0]));
+        let modes = [
+            FitMode::Stretch,
+            FitMode::Contain {
+                fill: Rgb([0, 0, 0]),
+                alignment: Alignment::default(),
+            },
+            FitMode::Cover {
+                gravity: Alignment::default(),
+            },
+        ];
+
+        for mode in modes {
+            for dimensions in [(0, 2), (4, 0), (0, 0)] {
+                let resized = mode.resize(&image, dimensions, ResizeFilter::Nearest);
+                assert_eq!(resized.dimensions(), dimensions);
+            }
+        }
+    }
 }
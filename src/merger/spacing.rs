@@ -0,0 +1,163 @@
+use super::core::{Padding, Point};
+
+use std::collections::BTreeMap;
+
+/// The space between the cells of a grid and each edge of its canvas.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Margin {
+    /// The space above the first row.
+    pub top: u32,
+    /// The space after the last column.
+    pub right: u32,
+    /// The space below the last row.
+    pub bottom: u32,
+    /// The space before the first column.
+    pub left: u32,
+}
+
+impl Margin {
+    /// Creates a margin of the same size on every edge.
+    pub fn uniform(margin: u32) -> Self {
+        Self {
+            top: margin,
+            right: margin,
+            bottom: margin,
+            left: margin,
+        }
+    }
+}
+
+impl From<u32> for Margin {
+    fn from(margin: u32) -> Self {
+        Self::uniform(margin)
+    }
+}
+
+/// Describes the space around and between the cells of a grid: an outer margin, the gutters between every two columns
+/// and every two rows, and overrides for the gutters after specific columns or rows. Used by the
+/// [KnownSizeMerger](crate::KnownSizeMerger) to size its canvas and place its cells, such as for print layouts that need
+/// a wider gap every few rows.
+///
+/// # Example
+/// ```
+/// use image_merger::{KnownSizeMerger, Margin, MergerBuilder, Point, Rgb, Spacing};
+///
+/// // A 4x4 grid of 100x100 cells, with a wider gap splitting it into two halves both ways.
+/// let spacing = Spacing::default()
+///     .with_margin(Margin { top: 40, right: 20, bottom: 40, left: 20 })
+///     .with_gutter(Point { x: 5, y: 5 })
+///     .with_column_gutter(1, 30)
+///     .with_row_gutter(1, 30);
+///
+/// let merger: KnownSizeMerger<Rgb<u8>, _> = KnownSizeMerger::builder((100, 100))
+///     .with_images_per_row(4)
+///     .with_total_images(16)
+///     .with_spacing(spacing)
+///     .build();
+///
+/// assert_eq!(merger.get_cell(5).map(|cell| (cell.x, cell.y)), Some((125, 145)));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Spacing {
+    /// The space between the cells and the edges of the canvas.
+    pub margin: Margin,
+    /// The space between two columns (x) and between two rows (y), unless overridden.
+    pub gutter: Padding,
+    /// The gutter after a column, keyed by the index of the column.
+    pub column_gutters: BTreeMap<u32, u32>,
+    /// The gutter after a row, keyed by the index of the row.
+    pub row_gutters: BTreeMap<u32, u32>,
+}
+
+impl Default for Spacing {
+    fn default() -> Self {
+        Self {
+            margin: Margin::default(),
+            gutter: Point { x: 0, y: 0 },
+            column_gutters: BTreeMap::new(),
+            row_gutters: BTreeMap::new(),
+        }
+    }
+}
+
+impl From<Option<Padding>> for Spacing {
+    /// Creates a spacing with no margin and the given padding as its gutters.
+    fn from(padding: Option<Padding>) -> Self {
+        Self {
+            gutter: padding.unwrap_or(Point { x: 0, y: 0 }),
+            ..Self::default()
+        }
+    }
+}
+
+impl Spacing {
+    /// Returns this spacing with its outer margin set to the given value.
+    pub fn with_margin(self, margin: impl Into<Margin>) -> Self {
+        Self {
+            margin: margin.into(),
+            ..self
+        }
+    }
+
+    /// Returns this spacing with its gutters between columns (x) and rows (y) set to the given value.
+    pub fn with_gutter(self, gutter: Padding) -> Self {
+        Self { gutter, ..self }
+    }
+
+    /// Returns this spacing with the gutter between `column` and the column after it set to `gutter`.
+    pub fn with_column_gutter(mut self, column: u32, gutter: u32) -> Self {
+        self.column_gutters.insert(column, gutter);
+        self
+    }
+
+    /// Returns this spacing with the gutter between `row` and the row after it set to `gutter`.
+    pub fn with_row_gutter(mut self, row: u32, gutter: u32) -> Self {
+        self.row_gutters.insert(row, gutter);
+        self
+    }
+
+    /// Returns the x coordinate of the left edge of the given column of cells that are `width` pixels wide.
+    pub(crate) fn column_x(&self, column: u32, width: u32) -> u64 {
+        self.margin.left as u64
+            + column as u64 * width as u64
+            + gaps(self.gutter.x, &self.column_gutters, column)
+    }
+
+    /// Returns the y coordinate of the top edge of the given row of cells that are `height` pixels tall.
+    pub(crate) fn row_y(&self, row: u32, height: u32) -> u64 {
+        self.margin.top as u64
+            + row as u64 * height as u64
+            + gaps(self.gutter.y, &self.row_gutters, row)
+    }
+
+    /// Returns the width of a canvas holding `columns` columns of cells that are `width` pixels wide.
+    pub(crate) fn canvas_width(&self, columns: u32, width: u32) -> u64 {
+        self.column_x(columns, width) + self.margin.right as u64
+            - gap_before(columns, self.gutter.x, &self.column_gutters)
+    }
+
+    /// Returns the height of a canvas holding `rows` rows of cells that are `height` pixels tall.
+    pub(crate) fn canvas_height(&self, rows: u32, height: u32) -> u64 {
+        self.row_y(rows, height) + self.margin.bottom as u64
+            - gap_before(rows, self.gutter.y, &self.row_gutters)
+    }
+}
+
+/// Sums the gutters after the first `count` columns or rows.
+fn gaps(gutter: u32, overrides: &BTreeMap<u32, u32>, count: u32) -> u64 {
+    let (overridden, sum) = overrides
+        .range(..count)
+        .fold((0u64, 0u64), |(overridden, sum), (_, gap)| {
+            (overridden + 1, sum + *gap as u64)
+        });
+
+    (count as u64 - overridden) * gutter as u64 + sum
+}
+
+/// Returns the gutter before the given column or row, so it can be taken back off past the last one.
+fn gap_before(index: u32, gutter: u32, overrides: &BTreeMap<u32, u32>) -> u64 {
+    match index.checked_sub(1) {
+        Some(previous) => *overrides.get(&previous).unwrap_or(&gutter) as u64,
+        None => 0,
+    }
+}
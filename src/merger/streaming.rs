@@ -0,0 +1,326 @@
+use super::paths::{image_dimensions, load_image};
+use crate::{BufferedImage, MergeError, TryFromWithFormat};
+
+use image::Pixel;
+use std::{
+    path::{Path, PathBuf},
+    sync::{Condvar, Mutex},
+};
+
+/// A single image fed to a [StreamingMerger](StreamingMerger). Sources are produced lazily on the calling thread and
+/// loaded inside of the pipeline, so an expensive source, such as a file to decode, is only held in memory while it is
+/// being processed.
+///
+/// This trait is implemented for paths, which are decoded like [PathMerger](crate::PathMerger) does, for images already
+/// in memory, and for closures returning an image.
+///
+/// # Type Parameters
+/// * `P` - The pixel type of the loaded image.
+pub trait ImageSource<P: Pixel>: Send {
+    /// Returns the dimensions, (width, height), of the image if they can be found without loading it. Only used to
+    /// reserve memory when the pipeline has a memory budget; sources that return None are charged once they are loaded.
+    fn dimensions_hint(&self) -> Option<(u32, u32)> {
+        None
+    }
+
+    /// Loads the image.
+    /// # Errors
+    /// Any error that prevents the image from being loaded, usually a [MergeError::LoadError](crate::MergeError::LoadError).
+    fn load(self) -> Result<BufferedImage<P>, MergeError>;
+}
+
+impl<P> ImageSource<P> for PathBuf
+where
+    P: Pixel,
+    BufferedImage<P>: TryFromWithFormat<Vec<u8>>,
+{
+    fn dimensions_hint(&self) -> Option<(u32, u32)> {
+        image_dimensions(self).ok()
+    }
+
+    fn load(self) -> Result<BufferedImage<P>, MergeError> {
+        load_image(&self)
+    }
+}
+
+impl<P> ImageSource<P> for &Path
+where
+    P: Pixel,
+    BufferedImage<P>: TryFromWithFormat<Vec<u8>>,
+{
+    fn dimensions_hint(&self) -> Option<(u32, u32)> {
+        image_dimensions(self).ok()
+    }
+
+    fn load(self) -> Result<BufferedImage<P>, MergeError> {
+        load_image(self)
+    }
+}
+
+impl<P> ImageSource<P> for BufferedImage<P>
+where
+    P: Pixel + Send,
+    <P as Pixel>::Subpixel: Send,
+{
+    fn dimensions_hint(&self) -> Option<(u32, u32)> {
+        Some((self.width(), self.height()))
+    }
+
+    fn load(self) -> Result<BufferedImage<P>, MergeError> {
+        Ok(self)
+    }
+}
+
+impl<P, F> ImageSource<P> for F
+where
+    P: Pixel,
+    F: FnOnce() -> Result<BufferedImage<P>, MergeError> + Send,
+{
+    fn load(self) -> Result<BufferedImage<P>, MergeError> {
+        self()
+    }
+}
+
+/// Bounds how much work a [StreamingMerger](StreamingMerger) holds in memory at once.
+///
+/// # Example
+/// ```
+/// use image_merger::StreamOptions;
+///
+/// // Keep at most 16 images, and 256 MiB of decoded and resized pixels, in flight.
+/// let options = StreamOptions {
+///     max_in_flight: 16,
+///     memory_budget: Some(256 * 1024 * 1024),
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StreamOptions {
+    /// The most images being loaded, resized or pasted at once. Treated as 1 if zero.
+    pub max_in_flight: usize,
+    /// The most bytes of loaded and resized images held at once, or None for no limit.
+    pub memory_budget: Option<usize>,
+}
+
+impl Default for StreamOptions {
+    /// Keeps two images in flight per thread of the current thread pool, without a memory budget.
+    fn default() -> Self {
+        Self {
+            max_in_flight: rayon::current_num_threads().max(1) * 2,
+            memory_budget: None,
+        }
+    }
+}
+
+/// A trait that allows a Merger to push images from a stream of [ImageSource](ImageSource)s through a pipeline that
+/// loads, resizes and pastes every image, then drops it. Unlike `bulk_push_resized`, which holds every resized image
+/// until all of them are pasted, only a bounded window of images is in flight at once, so merging tens of thousands of
+/// photos only needs memory for the canvas and the window.
+///
+/// Images are resized to the dimensions set on the merger, like `push_resized`, and pasted in the order of the stream.
+///
+/// # Type Parameters
+/// * `P` - The pixel type of the canvas.
+///
+/// # Example
+/// ```no_run
+/// use image_merger::{KnownSizeMerger, Rgb, StreamOptions, StreamingMerger};
+/// use std::path::PathBuf;
+///
+/// let paths: Vec<PathBuf> = (0..50_000).map(|index| format!("photos/{}.jpg", index).into()).collect();
+///
+/// let mut merger: KnownSizeMerger<Rgb<u8>, _> = KnownSizeMerger::new((64, 64), 250, 50_000, None);
+/// merger.push_stream(paths, &StreamOptions::default());
+/// ```
+pub trait StreamingMerger<P>
+where
+    P: Pixel,
+{
+    /// Pushes every image of a stream onto the canvas, resizing them to the dimensions set on the merger.
+    /// # Arguments
+    /// * `sources` - The images to push, in order.
+    /// * `options` - How much work may be in flight at once.
+    /// # Panics
+    /// This function will panic if an image cannot be loaded or pushed onto the canvas. Use `try_push_stream` to handle
+    /// the error instead.
+    fn push_stream<I>(&mut self, sources: I, options: &StreamOptions)
+    where
+        I: IntoIterator,
+        I::Item: ImageSource<P>,
+    {
+        if let Err(err) = self.try_push_stream(sources, options) {
+            panic!("{}", err);
+        }
+    }
+
+    /// Same as `push_stream`, but returns a [MergeError](crate::MergeError) instead of panicking. If an error is
+    /// returned, the images before the failing one have been pushed, and the cells of any images after it that were
+    /// already in flight are cleared.
+    /// # Errors
+    /// * Any error returned by [ImageSource::load](ImageSource::load).
+    /// * [MergeError::CanvasFull](crate::MergeError::CanvasFull) - If the stream holds more images than fit on the canvas.
+    fn try_push_stream<I>(&mut self, sources: I, options: &StreamOptions) -> Result<(), MergeError>
+    where
+        I: IntoIterator,
+        I::Item: ImageSource<P>;
+}
+
+/// The outcome of a run of the pipeline.
+pub(crate) struct Streamed {
+    pub pushed: u32, // The number of images, from the start of the stream, that were pasted.
+    pub started: u32, // The number of images that were taken from the stream.
+    pub error: Option<MergeError>, // The error of the first image that failed, if any did.
+}
+
+/// Runs every source through the pipeline: each source is loaded and handed to `paste` with its index in the stream,
+/// on the thread pool, while the calling thread keeps at most `options.max_in_flight` sources, and
+/// `options.memory_budget` bytes, in flight. Stops taking sources after the first failure.
+/// # Arguments
+/// * `sources` - The sources to run, which must all fit on the canvas.
+/// * `options` - How much work may be in flight at once.
+/// * `overhead` - The bytes `paste` allocates for every image, on top of the loaded image.
+/// * `paste` - Resizes and pastes a loaded image.
+pub(crate) fn run<P, I, F>(
+    sources: I,
+    options: &StreamOptions,
+    overhead: usize,
+    paste: F,
+) -> Streamed
+where
+    P: Pixel,
+    I: Iterator,
+    I::Item: ImageSource<P>,
+    F: Fn(u32, BufferedImage<P>) + Sync,
+{
+    let window = Window::new(options);
+    let failure: Mutex<Option<(u32, MergeError)>> = Mutex::new(None);
+    let mut started = 0;
+
+    rayon::in_place_scope(|scope| {
+        for source in sources {
+            if failure.lock().unwrap().is_some() {
+                break;
+            }
+
+            // Only look the dimensions up when they are needed, reading a header can cost as much as a small decode.
+            let reserved = overhead
+                + options
+                    .memory_budget
+                    .and_then(|_| source.dimensions_hint())
+                    .map_or(0, |(width, height)| image_bytes::<P>(width, height));
+            window.acquire(reserved);
+
+            let (index, window, failure, paste) = (started, &window, &failure, &paste);
+            scope.spawn(move |_| {
+                let mut charged = reserved;
+                match source.load() {
+                    Ok(image) => {
+                        if charged == overhead && options.memory_budget.is_some() {
+                            let (width, height) = image.dimensions();
+                            charged += image_bytes::<P>(width, height);
+                            window.charge(charged - overhead);
+                        }
+
+                        paste(index, image);
+                    }
+                    Err(err) => {
+                        let mut failure = failure.lock().unwrap();
+                        if failure.as_ref().is_none_or(|(failed, _)| index < *failed) {
+                            *failure = Some((index, err));
+                        }
+                    }
+                }
+
+                window.release(charged);
+            });
+
+            started += 1;
+        }
+    });
+
+    match failure.into_inner().unwrap() {
+        Some((index, err)) => Streamed {
+            pushed: index,
+            started,
+            error: Some(err),
+        },
+        None => Streamed {
+            pushed: started,
+            started,
+            error: None,
+        },
+    }
+}
+
+/// Returns the number of bytes held by an image of the given dimensions.
+pub(crate) fn image_bytes<P: Pixel>(width: u32, height: u32) -> usize {
+    (width as usize)
+        .saturating_mul(height as usize)
+        .saturating_mul(<P as Pixel>::CHANNEL_COUNT as usize)
+        .saturating_mul(std::mem::size_of::<P::Subpixel>())
+}
+
+/// Counts the images, and bytes, in flight, and makes the producer wait while the window is full.
+struct Window {
+    state: Mutex<(usize, usize)>, // The number of images and bytes in flight.
+    released: Condvar,            // Notified whenever an image leaves the window.
+    max_in_flight: usize,
+    memory_budget: usize,
+}
+
+impl Window {
+    fn new(options: &StreamOptions) -> Self {
+        Self {
+            state: Mutex::new((0, 0)),
+            released: Condvar::new(),
+            max_in_flight: options.max_in_flight.max(1),
+            memory_budget: options.memory_budget.unwrap_or(usize::MAX),
+        }
+    }
+
+    /// Whether an image of `bytes` fits in the window. An empty window always admits one image, however large, so a
+    /// single image over budget cannot stall the pipeline.
+    fn admits(&self, (images, used): (usize, usize), bytes: usize) -> bool {
+        images == 0
+            || (images < self.max_in_flight && used.saturating_add(bytes) <= self.memory_budget)
+    }
+
+    /// Waits until an image of `bytes` fits in the window, then adds it.
+    fn acquire(&self, bytes: usize) {
+        let mut state = self.state.lock().unwrap();
+        while !self.admits(*state, bytes) {
+            drop(state);
+
+            // When called from inside the thread pool, the images in flight may be queued behind us, so run them
+            // ourselves instead of blocking the thread they need.
+            if let Some(rayon::Yield::Executed) = rayon::yield_now() {
+                state = self.state.lock().unwrap();
+                continue;
+            }
+
+            // Every image in flight is being worked on by another thread, which notifies us when it leaves the window.
+            state = self
+                .released
+                .wait_while(self.state.lock().unwrap(), |state| {
+                    !self.admits(*state, bytes)
+                })
+                .unwrap();
+        }
+
+        state.0 += 1;
+        state.1 = state.1.saturating_add(bytes);
+    }
+
+    /// Charges an image in flight for more bytes than it reserved.
+    fn charge(&self, bytes: usize) {
+        let mut state = self.state.lock().unwrap();
+        state.1 = state.1.saturating_add(bytes);
+    }
+
+    /// Removes an image, and the bytes it was charged, from the window.
+    fn release(&self, bytes: usize) {
+        let mut state = self.state.lock().unwrap();
+        state.0 -= 1;
+        state.1 = state.1.saturating_sub(bytes);
+        self.released.notify_one();
+    }
+}
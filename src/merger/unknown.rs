@@ -0,0 +1,518 @@
+use super::{
+    background::Background,
+    builder::UnknownSizeMergerBuilder,
+    core::{Merger, Padding, Placement},
+    paths::SortOrder,
+    policy::SizePolicy,
+    streaming::{ImageSource, StreamOptions, StreamingMerger},
+};
+use crate::{
+    Alignment, Atlas, BufferedImage, Composite, ConvertPixel, ConvertingMerger, FitMode, Image,
+    ImageView, KnownSizeMerger, MergeError, ResizableMerger, ResizeFilter, TryFromWithFormat,
+};
+
+use image::Pixel;
+use std::path::Path;
+
+/// An unknown size merger that allows you to paste images onto a canvas without knowing how many images will be pushed
+/// ahead of time. The canvas starts small and grows row by row as images are pushed, reallocating in an amortized fashion,
+/// the same way a `Vec` does. Like the [KnownSizeMerger](crate::KnownSizeMerger), all pushed images must be a uniform size.
+///
+/// Because the canvas may grow past the number of rows it needs, use `shrink_to_fit` to trim unused trailing rows. The
+/// canvas returned by `into_canvas` is always trimmed.
+///
+/// # Type Parameters
+/// * `P` - The pixel type of the underlying image.
+///
+/// # Example
+/// ```
+/// use image_merger::{Merger, UnknownSizeMerger, Image, Rgb};
+///
+/// let mut merger: UnknownSizeMerger<Rgb<u8>> = UnknownSizeMerger::new((100, 100), 5, None);
+/// let image = Image::new(100, 100);
+/// for _ in 0..12 {
+///     merger.push(&image);
+/// }
+///
+/// let canvas = merger.into_canvas();
+/// assert_eq!(canvas.height(), 300);
+/// ```
+pub struct UnknownSizeMerger<P>
+where
+    P: Pixel,
+    <P as Pixel>::Subpixel: Sync,
+{
+    inner: KnownSizeMerger<P, Vec<P::Subpixel>>,
+}
+
+impl<P> UnknownSizeMerger<P>
+where
+    P: Pixel + Sync,
+    <P as Pixel>::Subpixel: Send + Sync,
+{
+    /// Constructs a new UnknownSizeMerger with a canvas that can hold a single row of images.
+    ///
+    /// # Arguments
+    /// * `image_dimensions` - The dimensions of the images being pasted (images must be a uniform size)
+    /// * `images_per_row` - The number of images per row.
+    /// * `padding` - The padding between images, or None for no padding.
+    ///
+    /// # Panics
+    /// This function will panic if the layout is invalid or the canvas is too large. Use `try_new` to handle these cases.
+    pub fn new(
+        image_dimensions: (u32, u32),
+        images_per_row: u32,
+        padding: Option<Padding>,
+    ) -> Self {
+        Self::with_capacity(image_dimensions, images_per_row, images_per_row, padding)
+    }
+
+    /// Same as `new`, but returns a [MergeError](crate::MergeError) instead of panicking when the merger cannot be created.
+    pub fn try_new(
+        image_dimensions: (u32, u32),
+        images_per_row: u32,
+        padding: Option<Padding>,
+    ) -> Result<Self, MergeError> {
+        Self::try_with_capacity(image_dimensions, images_per_row, images_per_row, padding)
+    }
+
+    /// Constructs a new UnknownSizeMerger with a canvas that can hold at least `capacity` images before it has to grow.
+    ///
+    /// # Arguments
+    /// * `image_dimensions` - The dimensions of the images being pasted (images must be a uniform size)
+    /// * `images_per_row` - The number of images per row.
+    /// * `capacity` - The number of images the canvas should be able to hold before growing.
+    /// * `padding` - The padding between images, or None for no padding.
+    ///
+    /// # Panics
+    /// This function will panic if the layout is invalid or the canvas is too large. Use `try_with_capacity` to handle these cases.
+    pub fn with_capacity(
+        image_dimensions: (u32, u32),
+        images_per_row: u32,
+        capacity: u32,
+        padding: Option<Padding>,
+    ) -> Self {
+        Self::try_with_capacity(image_dimensions, images_per_row, capacity, padding)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Same as `with_capacity`, but returns a [MergeError](crate::MergeError) instead of panicking when the merger cannot be created.
+    pub fn try_with_capacity(
+        image_dimensions: (u32, u32),
+        images_per_row: u32,
+        capacity: u32,
+        padding: Option<Padding>,
+    ) -> Result<Self, MergeError> {
+        // The known size merger always needs at least one row to build its canvas.
+        let capacity = capacity.max(1);
+
+        Ok(Self {
+            inner: KnownSizeMerger::try_new(image_dimensions, images_per_row, capacity, padding)?,
+        })
+    }
+
+    /// Returns a builder for a merger of images with the given dimensions, which can set options such as the background
+    /// that the constructors do not take.
+    /// # Arguments
+    /// * `image_dimensions` - The dimensions of the images being pasted (images must be a uniform size)
+    pub fn builder(image_dimensions: (u32, u32)) -> UnknownSizeMergerBuilder<P> {
+        UnknownSizeMergerBuilder::new(image_dimensions)
+    }
+
+    /// Wraps a merger whose canvas the unknown size merger grows from.
+    pub(crate) fn from_inner(inner: KnownSizeMerger<P, Vec<P::Subpixel>>) -> Self {
+        Self { inner }
+    }
+
+    /// Returns the number of images that have been pasted to the canvas.
+    pub fn get_num_images(&self) -> u32 {
+        self.inner.get_num_images()
+    }
+
+    /// Returns the dimensions, (x, y), of the images being pasted to the canvas.
+    pub fn get_image_dimensions(&self) -> (u32, u32) {
+        self.inner.get_image_dimensions()
+    }
+
+    /// Returns where every image pushed so far was placed on the canvas, in the order they were pushed.
+    pub fn get_placements(&self) -> Vec<Placement> {
+        self.inner.get_placements()
+    }
+
+    /// Returns an [Atlas](crate::Atlas) of every image pushed so far. The atlas describes the trimmed canvas, as returned
+    /// by `into_canvas`, rather than the current one.
+    pub fn get_atlas(&self) -> Atlas {
+        let mut atlas = self.inner.get_atlas();
+        atlas.height = atlas
+            .entries
+            .iter()
+            .map(|entry| entry.placement.rect.y + entry.placement.rect.height)
+            .max()
+            .unwrap_or(0);
+
+        atlas
+    }
+
+    /// Returns what the canvas shows where no image has been pasted, which new rows and cleared cells are drawn with.
+    pub fn get_background(&self) -> Option<&Background<P>> {
+        self.inner.get_background()
+    }
+
+    /// Returns the number of images the canvas can hold before it has to grow.
+    pub fn get_capacity(&self) -> u32 {
+        self.inner.capacity()
+    }
+
+    /// Returns the policy used for images that do not match the image dimensions of the merger.
+    pub fn get_size_policy(&self) -> SizePolicy {
+        self.inner.get_size_policy()
+    }
+
+    /// Sets the policy used for images that do not match the image dimensions of the merger. By default, such images are
+    /// rejected.
+    /// # Arguments
+    /// * `size_policy` - The policy to use for subsequently pushed images.
+    pub fn set_size_policy(&mut self, size_policy: SizePolicy) {
+        self.inner.set_size_policy(size_policy);
+    }
+
+    /// Returns where images smaller than their cell are placed inside of it.
+    pub fn get_alignment(&self) -> Alignment {
+        self.inner.get_alignment()
+    }
+
+    /// Sets where images smaller than their cell are placed inside of it by the
+    /// [CenterSmaller](crate::SizePolicy::CenterSmaller) size policy. By default, they are centered.
+    /// # Arguments
+    /// * `alignment` - The alignment to use for subsequently pushed images.
+    pub fn set_alignment(&mut self, alignment: Alignment) {
+        self.inner.set_alignment(alignment);
+    }
+
+    /// Returns how pushed images are combined with the canvas.
+    pub fn get_composite(&self) -> Composite {
+        self.inner.get_composite()
+    }
+
+    /// Sets how pushed images are combined with the canvas. By default, pushed images replace the canvas beneath them.
+    /// # Arguments
+    /// * `composite` - The composite to use for subsequently pushed images.
+    pub fn set_composite(&mut self, composite: Composite) {
+        self.inner.set_composite(composite);
+    }
+
+    /// Returns the filter used when images have to be resized.
+    pub fn get_resize_filter(&self) -> ResizeFilter {
+        self.inner.get_resize_filter()
+    }
+
+    /// Sets the filter used when images have to be resized, either by `push_resized` or by the
+    /// [ResizeToFit](crate::SizePolicy::ResizeToFit) size policy. By default, nearest neighbor sampling is used.
+    /// # Arguments
+    /// * `resize_filter` - The filter to use for subsequently resized images.
+    pub fn set_resize_filter(&mut self, resize_filter: ResizeFilter) {
+        self.inner.set_resize_filter(resize_filter);
+    }
+
+    /// Returns how images pushed with `push_resized` are fitted into the image dimensions.
+    pub fn get_fit_mode(&self) -> FitMode<P> {
+        self.inner.get_fit_mode()
+    }
+
+    /// Sets how images pushed with `push_resized` are fitted into the image dimensions. By default, images are stretched.
+    /// # Arguments
+    /// * `fit_mode` - The fit mode to use for subsequently resized images.
+    pub fn set_fit_mode(&mut self, fit_mode: FitMode<P>) {
+        self.inner.set_fit_mode(fit_mode);
+    }
+
+    /// Reserves space on the canvas for at least `additional` more images. The canvas grows by at least double its
+    /// current number of rows, so repeatedly pushing images only reallocates the canvas a logarithmic number of times.
+    ///
+    /// # Arguments
+    /// * `additional` - The number of images that will be pushed onto the canvas.
+    ///
+    /// # Panics
+    /// This function will panic if the grown canvas would be too large. Use `try_reserve` to handle this case.
+    pub fn reserve(&mut self, additional: u32) {
+        if let Err(err) = self.try_reserve(additional) {
+            panic!("{}", err);
+        }
+    }
+
+    /// Same as `reserve`, but returns a [MergeError](crate::MergeError) instead of panicking when the canvas cannot grow.
+    pub fn try_reserve(&mut self, additional: u32) -> Result<(), MergeError> {
+        let required = self
+            .get_num_images()
+            .checked_add(additional)
+            .ok_or(MergeError::CanvasTooLarge)?;
+        if required <= self.get_capacity() {
+            return Ok(());
+        }
+
+        let images_per_row = self.inner.images_per_row();
+        let current_rows = self.get_capacity() / images_per_row;
+        let required_rows = required.div_ceil(images_per_row);
+
+        // Prefer doubling, but fall back to exactly what is required if the doubled canvas is too large.
+        let doubled_rows = required_rows.max(current_rows.saturating_mul(2));
+        self.inner
+            .resize_rows(doubled_rows)
+            .or_else(|_| self.inner.resize_rows(required_rows))
+    }
+
+    /// Trims the rows at the end of the canvas that do not hold any images.
+    pub fn shrink_to_fit(&mut self) {
+        let used_rows = self
+            .inner
+            .last_occupied()
+            .map_or(0, |index| index / self.inner.images_per_row() + 1);
+
+        // Shrinking can never produce a canvas that is too large.
+        self.inner.resize_rows(used_rows).unwrap();
+    }
+
+    /// Returns true if the slot at the given index holds an image. Slots outside of the canvas never do.
+    pub fn is_occupied(&self, index: u32) -> bool {
+        self.inner.is_occupied(index)
+    }
+
+    /// Replaces the image in the slot at the given index, or fills the slot if it is empty, the same way
+    /// [KnownSizeMerger::replace_image](crate::KnownSizeMerger::replace_image) does.
+    /// # Panics
+    /// This function will panic if the image cannot be put in the slot. Use `try_replace_image` to handle the error
+    /// instead.
+    pub fn replace_image<V>(&mut self, index: u32, image: &V)
+    where
+        V: ImageView<Pixel = P>,
+        <P as Pixel>::Subpixel: Send,
+    {
+        self.inner.replace_image(index, image);
+    }
+
+    /// Same as `replace_image`, but returns a [MergeError](crate::MergeError) instead of panicking.
+    pub fn try_replace_image<V>(&mut self, index: u32, image: &V) -> Result<(), MergeError>
+    where
+        V: ImageView<Pixel = P>,
+        <P as Pixel>::Subpixel: Send,
+    {
+        self.inner.try_replace_image(index, image)
+    }
+
+    /// Swaps the images in the slots at the given indices, along with whether they hold an image at all.
+    /// # Panics
+    /// This function will panic if either index lies outside of the canvas. Use `try_swap_images` to handle the error
+    /// instead.
+    pub fn swap_images(&mut self, a: u32, b: u32) {
+        self.inner.swap_images(a, b);
+    }
+
+    /// Same as `swap_images`, but returns a [MergeError](crate::MergeError) instead of panicking.
+    pub fn try_swap_images(&mut self, a: u32, b: u32) -> Result<(), MergeError> {
+        self.inner.try_swap_images(a, b)
+    }
+
+    /// Fills the slot at the given index with a single color and marks it as free, so the next push fills it.
+    /// # Panics
+    /// This function will panic if the index lies outside of the canvas. Use `try_clear_cell` to handle the error
+    /// instead.
+    pub fn clear_cell(&mut self, index: u32, fill: P) {
+        self.inner.clear_cell(index, fill);
+    }
+
+    /// Same as `clear_cell`, but returns a [MergeError](crate::MergeError) instead of panicking.
+    pub fn try_clear_cell(&mut self, index: u32, fill: P) -> Result<(), MergeError> {
+        self.inner.try_clear_cell(index, fill)
+    }
+
+    /// Inserts an image at the given index, shifting the slot and the slots after it one slot forward up to the first
+    /// free slot. The canvas grows if there is no free slot after the index.
+    /// # Panics
+    /// This function will panic if the image cannot be inserted. Use `try_insert_at` to handle the error instead.
+    pub fn insert_at<V>(&mut self, index: u32, image: &V)
+    where
+        V: ImageView<Pixel = P>,
+        <P as Pixel>::Subpixel: Send,
+    {
+        if let Err(err) = self.try_insert_at(index, image) {
+            panic!("{}", err);
+        }
+    }
+
+    /// Same as `insert_at`, but returns a [MergeError](crate::MergeError) instead of panicking.
+    /// # Errors
+    /// * [MergeError::CellOutOfBounds](crate::MergeError::CellOutOfBounds) - If the index lies outside of the canvas.
+    /// * [MergeError::CanvasTooLarge](crate::MergeError::CanvasTooLarge) - If the canvas cannot grow.
+    /// * Any error `try_push` returns for the image.
+    pub fn try_insert_at<V>(&mut self, index: u32, image: &V) -> Result<(), MergeError>
+    where
+        V: ImageView<Pixel = P>,
+        <P as Pixel>::Subpixel: Send,
+    {
+        // Shifting needs a free slot at or after the index, so grow the canvas by a slot past its capacity if there is
+        // none.
+        let capacity = self.get_capacity();
+        if index < capacity && (index..capacity).all(|slot| self.is_occupied(slot)) {
+            self.try_reserve(capacity - self.get_num_images() + 1)?;
+        }
+
+        self.inner.try_insert_at(index, image)
+    }
+
+    /// Removes the image in the slot at the given index, filling the slot with the background and marking it as free.
+    /// Indexing starts at 0 and works left to right, top to bottom.
+    /// # Arguments
+    /// * `index` - The index of the image to remove.
+    /// # Panics
+    /// This function will panic if the index lies outside of the canvas. Use `try_remove_image` to handle the error
+    /// instead.
+    pub fn remove_image(&mut self, index: u32) {
+        self.inner.remove_image(index);
+    }
+
+    /// Same as `remove_image`, but returns a [MergeError](crate::MergeError) instead of panicking.
+    pub fn try_remove_image(&mut self, index: u32) -> Result<(), MergeError> {
+        self.inner.try_remove_image(index)
+    }
+}
+
+impl<P> UnknownSizeMerger<P>
+where
+    P: Pixel + Send + Sync,
+    <P as Pixel>::Subpixel: Send + Sync,
+    BufferedImage<P>: TryFromWithFormat<Vec<u8>> + Send,
+{
+    /// Constructs a new UnknownSizeMerger holding every image in a directory, with the same behavior as
+    /// [KnownSizeMerger::from_dir](crate::KnownSizeMerger::from_dir). More images can be pushed afterwards.
+    ///
+    /// # Arguments
+    /// * `dir` - The directory to merge the images of, see [image_paths](crate::image_paths).
+    /// * `images_per_row` - The number of images per row.
+    /// * `padding` - The padding between images, or None for no padding.
+    /// * `order` - The order the images are merged in.
+    pub fn from_dir(
+        dir: impl AsRef<Path>,
+        images_per_row: u32,
+        padding: Option<Padding>,
+        order: SortOrder,
+    ) -> Result<Self, MergeError> {
+        Ok(Self {
+            inner: KnownSizeMerger::from_dir(dir, images_per_row, padding, order)?,
+        })
+    }
+}
+
+impl<P> Merger<P, Vec<P::Subpixel>> for UnknownSizeMerger<P>
+where
+    P: Pixel + Send + Sync,
+    <P as Pixel>::Subpixel: Send + Sync,
+{
+    fn get_canvas(&self) -> &Image<P, image::ImageBuffer<P, Vec<P::Subpixel>>> {
+        self.inner.get_canvas()
+    }
+
+    fn into_canvas(mut self) -> Image<P, image::ImageBuffer<P, Vec<P::Subpixel>>> {
+        self.shrink_to_fit();
+        self.inner.into_canvas()
+    }
+
+    fn try_push<V>(&mut self, image: &V) -> Result<(), MergeError>
+    where
+        V: ImageView<Pixel = P>,
+    {
+        self.inner.fit_all([image.view_dimensions()])?;
+        self.try_reserve(1)?;
+        self.inner.try_push(image)
+    }
+
+    fn try_bulk_push<V>(&mut self, images: &[&V]) -> Result<(), MergeError>
+    where
+        V: ImageView<Pixel = P>,
+    {
+        // Validate the images before growing, so a failed push leaves the canvas as it was.
+        let requested = count(images.len())?;
+        self.inner
+            .fit_all(images.iter().map(|image| image.view_dimensions()))?;
+        self.try_reserve(requested)?;
+        self.inner.try_bulk_push(images)
+    }
+}
+
+impl<P> ResizableMerger<P> for UnknownSizeMerger<P>
+where
+    P: Pixel + Sync + Send,
+    <P as Pixel>::Subpixel: Sync + Send,
+{
+    fn try_push_resized(&mut self, image: &BufferedImage<P>) -> Result<(), MergeError> {
+        // Resize before growing, the push then validates the image before reserving a slot for it.
+        let resized = self.inner.resize_all(&[image]);
+        self.try_push(&resized[0])
+    }
+
+    fn try_bulk_push_resized(&mut self, images: &[&BufferedImage<P>]) -> Result<(), MergeError> {
+        count(images.len())?;
+
+        let resized = self.inner.resize_all(images);
+        let resized: Vec<&BufferedImage<P>> = resized.iter().collect();
+        self.try_bulk_push(&resized)
+    }
+}
+
+impl<P> ConvertingMerger<P> for UnknownSizeMerger<P>
+where
+    P: Pixel + Sync + Send,
+    <P as Pixel>::Subpixel: Sync + Send,
+{
+    fn try_push_converted<Q, V>(&mut self, image: &V) -> Result<(), MergeError>
+    where
+        Q: ConvertPixel<P> + Sync,
+        <Q as Pixel>::Subpixel: Send + Sync,
+        V: ImageView<Pixel = Q>,
+    {
+        self.inner.fit_all([image.view_dimensions()])?;
+        self.try_reserve(1)?;
+        self.inner.try_push_converted(image)
+    }
+
+    fn try_bulk_push_converted<Q, V>(&mut self, images: &[&V]) -> Result<(), MergeError>
+    where
+        Q: ConvertPixel<P> + Sync,
+        <Q as Pixel>::Subpixel: Send + Sync,
+        V: ImageView<Pixel = Q>,
+    {
+        // Validate the images before growing, so a failed push leaves the canvas as it was.
+        let requested = count(images.len())?;
+        self.inner
+            .fit_all(images.iter().map(|image| image.view_dimensions()))?;
+        self.try_reserve(requested)?;
+        self.inner.try_bulk_push_converted(images)
+    }
+}
+
+impl<P> StreamingMerger<P> for UnknownSizeMerger<P>
+where
+    P: Pixel + Send + Sync,
+    <P as Pixel>::Subpixel: Send + Sync,
+{
+    fn try_push_stream<I>(&mut self, sources: I, options: &StreamOptions) -> Result<(), MergeError>
+    where
+        I: IntoIterator,
+        I::Item: ImageSource<P>,
+    {
+        // Grow the canvas between runs of the pipeline, it cannot be reallocated while images are being pasted.
+        let mut sources = sources.into_iter().peekable();
+        while sources.peek().is_some() {
+            let (len, _) = sources.size_hint();
+            self.try_reserve(count(len.max(1))?)?;
+            self.inner.stream_into(&mut sources, options)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Converts the number of images of a bulk push to a count of slots to reserve, a canvas cannot hold more than
+/// `u32::MAX` images.
+fn count(len: usize) -> Result<u32, MergeError> {
+    u32::try_from(len).map_err(|_| MergeError::CanvasTooLarge)
+}
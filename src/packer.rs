@@ -0,0 +1,551 @@
+//! Rectangle bin packing algorithms used by the [PackingMerger](crate::PackingMerger). Every bin packs rectangles online,
+//! one at a time, into a fixed area with its origin at the top left corner.
+use crate::{
+    merger::{Padding, Rect},
+    PackingAlgorithm,
+};
+
+/// A bin that rectangles can be packed into, using one of the supported [PackingAlgorithm](crate::PackingAlgorithm)s.
+/// Every rectangle is padded on its right and bottom, so bins should be created with the padding added to their
+/// dimensions.
+#[derive(Debug, Clone)]
+pub(crate) struct Bin {
+    space: Space,     // The free space left in the bin.
+    padding: Padding, // The padding added to every rectangle.
+}
+
+/// The free space of a bin, as tracked by each algorithm.
+#[derive(Debug, Clone)]
+enum Space {
+    MaxRects(MaxRectsBin),
+    Skyline(SkylineBin),
+    Guillotine(GuillotineBin),
+}
+
+impl Bin {
+    /// Creates a new, empty bin with the given dimensions.
+    pub(crate) fn new(
+        algorithm: PackingAlgorithm,
+        width: u32,
+        height: u32,
+        padding: Padding,
+    ) -> Self {
+        let space = match algorithm {
+            PackingAlgorithm::MaxRects => Space::MaxRects(MaxRectsBin::new(width, height)),
+            PackingAlgorithm::Skyline => Space::Skyline(SkylineBin::new(width, height)),
+            PackingAlgorithm::Guillotine => Space::Guillotine(GuillotineBin::new(width, height)),
+        };
+
+        Self { space, padding }
+    }
+
+    /// Packs a rectangle with the given dimensions into the bin.
+    /// # Returns
+    /// * `Some` - The area the rectangle was placed at, and whether it was rotated by 90 degrees to fit. The area's
+    ///   dimensions are those of the rotated rectangle, without the padding.
+    /// * `None` - If there is no space left for the rectangle.
+    pub(crate) fn insert(
+        &mut self,
+        width: u32,
+        height: u32,
+        allow_rotation: bool,
+    ) -> Option<(Rect, bool)> {
+        if width.checked_add(self.padding.x)? == 0 || height.checked_add(self.padding.y)? == 0 {
+            // Empty rectangles take up no space and can go anywhere.
+            return Some((
+                Rect {
+                    x: 0,
+                    y: 0,
+                    width,
+                    height,
+                },
+                false,
+            ));
+        }
+
+        let orientations = orientations(width, height, self.padding, allow_rotation);
+        let (rect, rotated) = match &mut self.space {
+            Space::MaxRects(bin) => bin.insert(orientations),
+            Space::Skyline(bin) => bin.insert(orientations),
+            Space::Guillotine(bin) => bin.insert(orientations),
+        }?;
+
+        Some((
+            Rect {
+                width: rect.width - self.padding.x,
+                height: rect.height - self.padding.y,
+                ..rect
+            },
+            rotated,
+        ))
+    }
+
+    /// Shrinks the bin to the given dimensions, discarding the free space that lies outside of them. Used once a bin
+    /// has been packed into a generously sized area to trim it down to the area that was actually used.
+    pub(crate) fn shrink(&mut self, width: u32, height: u32) {
+        match &mut self.space {
+            Space::MaxRects(bin) => {
+                clip_free_rects(&mut bin.free, width, height);
+            }
+            Space::Skyline(bin) => bin.shrink(width, height),
+            Space::Guillotine(bin) => {
+                clip_free_rects(&mut bin.free, width, height);
+            }
+        }
+    }
+}
+
+/// The candidate orientations, (width, height, rotated), of a padded rectangle.
+type Orientations = [Option<(u32, u32, bool)>; 2];
+
+/// Returns the candidate orientations of a rectangle. The padding is added after rotating, so it always lies on the
+/// right and bottom of the rectangle.
+fn orientations(width: u32, height: u32, padding: Padding, allow_rotation: bool) -> Orientations {
+    let padded = |width: u32, height: u32| {
+        Some((
+            width.checked_add(padding.x)?,
+            height.checked_add(padding.y)?,
+        ))
+    };
+
+    [
+        padded(width, height).map(|(w, h)| (w, h, false)),
+        padded(height, width)
+            .filter(|_| allow_rotation && width != height)
+            .map(|(w, h)| (w, h, true)),
+    ]
+}
+
+/// Clips every free rectangle to the given dimensions, dropping the ones that end up empty.
+fn clip_free_rects(free: &mut Vec<Rect>, width: u32, height: u32) {
+    free.retain_mut(|rect| {
+        rect.width = rect.width.min(width.saturating_sub(rect.x));
+        rect.height = rect.height.min(height.saturating_sub(rect.y));
+        rect.width > 0 && rect.height > 0
+    });
+}
+
+#[inline(always)]
+fn right(rect: &Rect) -> u32 {
+    rect.x + rect.width
+}
+
+#[inline(always)]
+fn bottom(rect: &Rect) -> u32 {
+    rect.y + rect.height
+}
+
+#[inline(always)]
+fn contains(outer: &Rect, inner: &Rect) -> bool {
+    inner.x >= outer.x
+        && inner.y >= outer.y
+        && right(inner) <= right(outer)
+        && bottom(inner) <= bottom(outer)
+}
+
+#[inline(always)]
+pub(crate) fn intersects(a: &Rect, b: &Rect) -> bool {
+    a.x < right(b) && b.x < right(a) && a.y < bottom(b) && b.y < bottom(a)
+}
+
+/// The MaxRects algorithm, using the best short side fit heuristic. Keeps track of every maximal free rectangle, which
+/// makes it the most compact, and slowest, of the packers.
+#[derive(Debug, Clone)]
+pub(crate) struct MaxRectsBin {
+    free: Vec<Rect>,
+}
+
+impl MaxRectsBin {
+    fn new(width: u32, height: u32) -> Self {
+        Self {
+            free: vec![Rect {
+                x: 0,
+                y: 0,
+                width,
+                height,
+            }],
+        }
+    }
+
+    fn insert(&mut self, orientations: Orientations) -> Option<(Rect, bool)> {
+        // Pick the free rectangle that leaves the smallest leftover along its shorter side.
+        let (placed, rotated) = self
+            .free
+            .iter()
+            .flat_map(|free| {
+                orientations
+                    .into_iter()
+                    .flatten()
+                    .filter(move |(w, h, _)| *w <= free.width && *h <= free.height)
+                    .map(move |(w, h, rotated)| {
+                        let leftover_x = free.width - w;
+                        let leftover_y = free.height - h;
+                        let score = (leftover_x.min(leftover_y), leftover_x.max(leftover_y));
+                        (
+                            score,
+                            Rect {
+                                x: free.x,
+                                y: free.y,
+                                width: w,
+                                height: h,
+                            },
+                            rotated,
+                        )
+                    })
+            })
+            .min_by_key(|(score, _, _)| *score)
+            .map(|(_, rect, rotated)| (rect, rotated))?;
+
+        self.place(&placed);
+        Some((placed, rotated))
+    }
+
+    /// Splits every free rectangle overlapping the placed one into the maximal rectangles around it.
+    fn place(&mut self, placed: &Rect) {
+        let mut split = Vec::new();
+        self.free.retain(|free| {
+            if !intersects(free, placed) {
+                return true;
+            }
+
+            if placed.x > free.x {
+                split.push(Rect {
+                    width: placed.x - free.x,
+                    ..*free
+                });
+            }
+            if right(placed) < right(free) {
+                split.push(Rect {
+                    x: right(placed),
+                    width: right(free) - right(placed),
+                    ..*free
+                });
+            }
+            if placed.y > free.y {
+                split.push(Rect {
+                    height: placed.y - free.y,
+                    ..*free
+                });
+            }
+            if bottom(placed) < bottom(free) {
+                split.push(Rect {
+                    y: bottom(placed),
+                    height: bottom(free) - bottom(placed),
+                    ..*free
+                });
+            }
+
+            false
+        });
+
+        self.free.extend(split);
+
+        // Remove every free rectangle that is contained within another one.
+        let mut index = 0;
+        while index < self.free.len() {
+            let rect = self.free[index];
+            let redundant = self.free.iter().enumerate().any(|(other, free)| {
+                other != index && contains(free, &rect) && (free != &rect || other < index)
+            });
+
+            if redundant {
+                self.free.swap_remove(index);
+            } else {
+                index += 1;
+            }
+        }
+    }
+}
+
+/// A segment of the skyline, starting at `x` and spanning `width` pixels at a height of `y`.
+#[derive(Debug, Clone, Copy)]
+struct Segment {
+    x: u32,
+    y: u32,
+    width: u32,
+}
+
+/// The Skyline algorithm, using the bottom left heuristic. Only tracks the top edge of the packed rectangles, which
+/// makes it fast, but it cannot fill the gaps that are left below that edge.
+#[derive(Debug, Clone)]
+pub(crate) struct SkylineBin {
+    width: u32,
+    height: u32,
+    skyline: Vec<Segment>,
+}
+
+impl SkylineBin {
+    fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            skyline: vec![Segment { x: 0, y: 0, width }],
+        }
+    }
+
+    /// Returns the height a rectangle of the given width would rest at if placed at the start of the given segment.
+    fn fit(&self, index: usize, width: u32, height: u32) -> Option<u32> {
+        let x = self.skyline[index].x;
+        if x + width > self.width {
+            return None;
+        }
+
+        let mut y = 0;
+        let mut covered = 0;
+        for segment in &self.skyline[index..] {
+            if covered >= width {
+                break;
+            }
+
+            y = y.max(segment.y);
+            covered += segment.width;
+        }
+
+        (y + height <= self.height).then_some(y)
+    }
+
+    fn insert(&mut self, orientations: Orientations) -> Option<(Rect, bool)> {
+        let mut best: Option<((u32, u32), usize, Rect, bool)> = None;
+        for index in 0..self.skyline.len() {
+            for (w, h, rotated) in orientations.into_iter().flatten() {
+                if let Some(y) = self.fit(index, w, h) {
+                    let x = self.skyline[index].x;
+                    let score = (y + h, x);
+                    if best.as_ref().is_none_or(|(best, ..)| score < *best) {
+                        let rect = Rect {
+                            x,
+                            y,
+                            width: w,
+                            height: h,
+                        };
+                        best = Some((score, index, rect, rotated));
+                    }
+                }
+            }
+        }
+
+        let (_, index, placed, rotated) = best?;
+        self.place(index, &placed);
+        Some((placed, rotated))
+    }
+
+    /// Raises the skyline over the placed rectangle.
+    fn place(&mut self, index: usize, placed: &Rect) {
+        self.skyline.insert(
+            index,
+            Segment {
+                x: placed.x,
+                y: bottom(placed),
+                width: placed.width,
+            },
+        );
+
+        // Shrink or remove the segments that are now covered by the new one.
+        let end = right(placed);
+        let next = index + 1;
+        while next < self.skyline.len() {
+            let segment = self.skyline[next];
+            if segment.x >= end {
+                break;
+            }
+
+            let segment_end = segment.x + segment.width;
+            if segment_end <= end {
+                self.skyline.remove(next);
+            } else {
+                self.skyline[next].x = end;
+                self.skyline[next].width = segment_end - end;
+                break;
+            }
+        }
+
+        // Merge neighboring segments at the same height.
+        let mut index = 0;
+        while index + 1 < self.skyline.len() {
+            if self.skyline[index].y == self.skyline[index + 1].y {
+                self.skyline[index].width += self.skyline[index + 1].width;
+                self.skyline.remove(index + 1);
+            } else {
+                index += 1;
+            }
+        }
+    }
+
+    fn shrink(&mut self, width: u32, height: u32) {
+        self.width = self.width.min(width);
+        self.height = self.height.min(height);
+
+        self.skyline.retain_mut(|segment| {
+            segment.width = segment.width.min(width.saturating_sub(segment.x));
+            segment.width > 0
+        });
+    }
+}
+
+/// The Guillotine algorithm, using the best area fit heuristic and splitting along the shorter leftover axis. Every
+/// placement cuts its free rectangle in two, which is fast and keeps the free rectangles disjoint.
+#[derive(Debug, Clone)]
+pub(crate) struct GuillotineBin {
+    free: Vec<Rect>,
+}
+
+impl GuillotineBin {
+    fn new(width: u32, height: u32) -> Self {
+        Self {
+            free: vec![Rect {
+                x: 0,
+                y: 0,
+                width,
+                height,
+            }],
+        }
+    }
+
+    fn insert(&mut self, orientations: Orientations) -> Option<(Rect, bool)> {
+        let (index, w, h, rotated) = self
+            .free
+            .iter()
+            .enumerate()
+            .flat_map(|(index, free)| {
+                orientations
+                    .into_iter()
+                    .flatten()
+                    .filter(move |(w, h, _)| *w <= free.width && *h <= free.height)
+                    .map(move |(w, h, rotated)| {
+                        let leftover = free.width as u64 * free.height as u64 - w as u64 * h as u64;
+                        (leftover, index, w, h, rotated)
+                    })
+            })
+            .min_by_key(|(leftover, ..)| *leftover)
+            .map(|(_, index, w, h, rotated)| (index, w, h, rotated))?;
+
+        let free = self.free.swap_remove(index);
+        let placed = Rect {
+            x: free.x,
+            y: free.y,
+            width: w,
+            height: h,
+        };
+
+        let leftover_x = free.width - w;
+        let leftover_y = free.height - h;
+
+        // Split along the shorter leftover axis, giving the larger free rectangle the full length of the cut.
+        let (right, below) = if leftover_x < leftover_y {
+            (
+                Rect {
+                    x: free.x + w,
+                    y: free.y,
+                    width: leftover_x,
+                    height: h,
+                },
+                Rect {
+                    x: free.x,
+                    y: free.y + h,
+                    width: free.width,
+                    height: leftover_y,
+                },
+            )
+        } else {
+            (
+                Rect {
+                    x: free.x + w,
+                    y: free.y,
+                    width: leftover_x,
+                    height: free.height,
+                },
+                Rect {
+                    x: free.x,
+                    y: free.y + h,
+                    width: w,
+                    height: leftover_y,
+                },
+            )
+        };
+
+        self.free.extend(
+            [right, below]
+                .into_iter()
+                .filter(|rect| rect.width > 0 && rect.height > 0),
+        );
+
+        Some((placed, rotated))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_disjoint(placed: &[Rect], width: u32, height: u32) {
+        for (index, a) in placed.iter().enumerate() {
+            assert!(
+                right(a) <= width && bottom(a) <= height,
+                "{:?} is out of bounds",
+                a
+            );
+            for b in &placed[index + 1..] {
+                assert!(!intersects(a, b), "{:?} overlaps {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_bins_pack_disjoint_rects() {
+        let sizes: Vec<(u32, u32)> = (0..60)
+            .map(|i| (5 + (i * 7) % 23, 3 + (i * 11) % 17))
+            .collect();
+
+        for algorithm in [
+            PackingAlgorithm::MaxRects,
+            PackingAlgorithm::Skyline,
+            PackingAlgorithm::Guillotine,
+        ] {
+            for allow_rotation in [false, true] {
+                let mut bin = Bin::new(algorithm, 128, 128, Padding { x: 0, y: 0 });
+                let placed: Vec<Rect> = sizes
+                    .iter()
+                    .map(|&(w, h)| {
+                        let (rect, rotated) = bin
+                            .insert(w, h, allow_rotation)
+                            .unwrap_or_else(|| panic!("{:?} could not fit {}x{}", algorithm, w, h));
+                        if rotated {
+                            assert_eq!((rect.width, rect.height), (h, w));
+                        } else {
+                            assert_eq!((rect.width, rect.height), (w, h));
+                        }
+                        rect
+                    })
+                    .collect();
+
+                assert_disjoint(&placed, 128, 128);
+            }
+        }
+    }
+
+    #[test]
+    fn test_bins_fill_exactly() {
+        for algorithm in [
+            PackingAlgorithm::MaxRects,
+            PackingAlgorithm::Skyline,
+            PackingAlgorithm::Guillotine,
+        ] {
+            let mut bin = Bin::new(algorithm, 40, 40, Padding { x: 0, y: 0 });
+            for _ in 0..16 {
+                assert!(bin.insert(10, 10, false).is_some());
+            }
+
+            assert!(bin.insert(1, 1, true).is_none());
+        }
+    }
+
+    #[test]
+    fn test_rotation_allows_fit() {
+        let mut bin = Bin::new(PackingAlgorithm::MaxRects, 10, 30, Padding { x: 0, y: 0 });
+        assert!(bin.insert(30, 10, false).is_none());
+
+        let (rect, rotated) = bin.insert(30, 10, true).unwrap();
+        assert!(rotated);
+        assert_eq!((rect.width, rect.height), (10, 30));
+    }
+}
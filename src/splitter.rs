@@ -0,0 +1,314 @@
+use crate::{
+    functions::{crop, rotate270},
+    merger::{grid_cell, total_rows, Padding, Placement, Spacing},
+    Atlas, BufferedImage, Image, MergeError,
+};
+
+use image::Pixel;
+use rayon::prelude::*;
+use std::ops::DerefMut;
+
+/// A splitter that cuts a merged canvas back into its individual images, the inverse of a merger. The layout of the
+/// canvas can be given with the same grid parameters as a [KnownSizeMerger](crate::KnownSizeMerger), read from an
+/// [Atlas](crate::Atlas), or detected from the uniform padding between the images.
+///
+/// # Example
+/// ```
+/// use image_merger::{KnownSizeMerger, Merger, Image, Rgb, Splitter};
+///
+/// let mut merger: KnownSizeMerger<Rgb<u8>, _> = KnownSizeMerger::new((100, 100), 5, 10, None);
+/// let image = Image::new(100, 100);
+/// merger.bulk_push(&[&image, &image, &image]);
+///
+/// let splitter = Splitter::new((100, 100), 5, 3, None);
+/// let images = splitter.split(merger.get_canvas());
+/// assert_eq!(images.len(), 3);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Splitter {
+    placements: Vec<Placement>, // Where every image is on the canvas, in order.
+}
+
+impl Splitter {
+    /// Constructs a new Splitter for a canvas laid out as a grid, as created by a
+    /// [KnownSizeMerger](crate::KnownSizeMerger) with the same arguments.
+    /// # Arguments
+    /// * `image_dimensions` - The dimensions of the images on the canvas (images must be a uniform size)
+    /// * `images_per_row` - The number of images per row.
+    /// * `total_images` - The total number of images on the canvas.
+    /// * `padding` - The padding between images, or None for no padding.
+    ///
+    /// # Panics
+    /// This function will panic if the layout is invalid. Use `try_new` to handle this case.
+    pub fn new(
+        image_dimensions: (u32, u32),
+        images_per_row: u32,
+        total_images: u32,
+        padding: Option<Padding>,
+    ) -> Self {
+        Self::try_new(image_dimensions, images_per_row, total_images, padding)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Same as `new`, but returns a [MergeError](crate::MergeError) instead of panicking when the layout is invalid.
+    ///
+    /// # Errors
+    /// * [MergeError::InvalidLayout](crate::MergeError::InvalidLayout) - If `images_per_row` or `total_images` is zero.
+    pub fn try_new(
+        image_dimensions: (u32, u32),
+        images_per_row: u32,
+        total_images: u32,
+        padding: Option<Padding>,
+    ) -> Result<Self, MergeError> {
+        total_rows(images_per_row, total_images)?;
+        let spacing = Spacing::from(padding);
+
+        let placements = (0..total_images)
+            .map(|index| Placement {
+                rect: grid_cell(index, image_dimensions, images_per_row, &spacing),
+                rotated: false,
+            })
+            .collect();
+
+        Ok(Self { placements })
+    }
+
+    /// Constructs a new Splitter from the placements of an [Atlas](crate::Atlas), such as one parsed with
+    /// [Atlas::from_json](crate::Atlas::from_json). Rotated images are rotated back when the canvas is split.
+    pub fn from_atlas(atlas: &Atlas) -> Self {
+        Self {
+            placements: atlas.entries.iter().map(|entry| entry.placement).collect(),
+        }
+    }
+
+    /// Detects the grid of a canvas from the padding between its images. The padding must be a uniform color, and
+    /// trailing cells that are entirely that color are considered empty and left out.
+    ///
+    /// The grid can only be detected along an axis with padding: without padding between columns, the canvas is
+    /// assumed to have a single column, and likewise for rows.
+    /// # Arguments
+    /// * `canvas` - The canvas to detect the grid of.
+    /// * `background` - The color of the padding, or None to use the color of the first uniformly colored column or row.
+    ///
+    /// # Errors
+    /// * [MergeError::InvalidLayout](crate::MergeError::InvalidLayout) - If no uniform padding could be found, or it is
+    ///   not laid out as a grid.
+    pub fn detect<P, Container>(
+        canvas: &Image<P, image::ImageBuffer<P, Container>>,
+        background: Option<P>,
+    ) -> Result<Self, MergeError>
+    where
+        P: Pixel + PartialEq + Sync,
+        <P as Pixel>::Subpixel: Sync,
+        Container: DerefMut<Target = [P::Subpixel]> + Sync,
+    {
+        let (width, height) = canvas.dimensions();
+        if width == 0 || height == 0 {
+            return Err(MergeError::InvalidLayout("the canvas is empty"));
+        }
+
+        let background = match background {
+            Some(background) => background,
+            None => uniform_line_color(canvas).ok_or(MergeError::InvalidLayout(
+                "the canvas has no uniform padding to detect a grid from",
+            ))?,
+        };
+
+        let columns: Vec<bool> = (0..width)
+            .into_par_iter()
+            .map(|x| (0..height).all(|y| *canvas.get_pixel(x, y) == background))
+            .collect();
+        let rows: Vec<bool> = (0..height)
+            .into_par_iter()
+            .map(|y| (0..width).all(|x| *canvas.get_pixel(x, y) == background))
+            .collect();
+
+        let (cell_width, padding_x, images_per_row) = detect_axis(&columns);
+        let (cell_height, padding_y, total_rows) = detect_axis(&rows);
+        if padding_x == 0 && padding_y == 0 {
+            return Err(MergeError::InvalidLayout(
+                "the canvas has no uniform padding to detect a grid from",
+            ));
+        }
+
+        let padding = Padding {
+            x: padding_x,
+            y: padding_y,
+        };
+
+        // Drop the trailing cells that hold nothing but the background.
+        let mut total_images = images_per_row * total_rows;
+        while total_images > 1 {
+            let cell = grid_cell(
+                total_images - 1,
+                (cell_width, cell_height),
+                images_per_row,
+                &Spacing::from(Some(padding)),
+            );
+            let empty = (cell.y..cell.y + cell.height).all(|y| {
+                (cell.x..cell.x + cell.width).all(|x| *canvas.get_pixel(x, y) == background)
+            });
+
+            if !empty {
+                break;
+            }
+            total_images -= 1;
+        }
+
+        Self::try_new(
+            (cell_width, cell_height),
+            images_per_row,
+            total_images,
+            Some(padding),
+        )
+    }
+
+    /// Returns where every image is on the canvas, in order.
+    pub fn get_placements(&self) -> &[Placement] {
+        &self.placements
+    }
+
+    /// Returns the number of images the canvas is split into.
+    pub fn get_num_images(&self) -> u32 {
+        self.placements.len() as u32
+    }
+
+    /// Splits the canvas into its individual images, extracting them in parallel.
+    /// # Arguments
+    /// * `canvas` - The canvas to split.
+    ///
+    /// # Panics
+    /// This function will panic if an image lies outside of the canvas. Use `try_split` to handle this case.
+    pub fn split<P, Container>(
+        &self,
+        canvas: &Image<P, image::ImageBuffer<P, Container>>,
+    ) -> Vec<BufferedImage<P>>
+    where
+        P: Pixel + Send + Sync,
+        <P as Pixel>::Subpixel: Send + Sync,
+        Container: DerefMut<Target = [P::Subpixel]> + Sync,
+    {
+        self.try_split(canvas)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Same as `split`, but returns a [MergeError](crate::MergeError) instead of panicking when the canvas cannot be split.
+    ///
+    /// # Errors
+    /// * [MergeError::DimensionMismatch](crate::MergeError::DimensionMismatch) - If an image lies outside of the canvas.
+    ///   The expected dimensions are the smallest canvas that holds every image.
+    pub fn try_split<P, Container>(
+        &self,
+        canvas: &Image<P, image::ImageBuffer<P, Container>>,
+    ) -> Result<Vec<BufferedImage<P>>, MergeError>
+    where
+        P: Pixel + Send + Sync,
+        <P as Pixel>::Subpixel: Send + Sync,
+        Container: DerefMut<Target = [P::Subpixel]> + Sync,
+    {
+        let expected = self
+            .placements
+            .iter()
+            .fold((0, 0), |(width, height), placement| {
+                let rect = placement.rect;
+                (
+                    width.max(rect.x as u64 + rect.width as u64),
+                    height.max(rect.y as u64 + rect.height as u64),
+                )
+            });
+
+        let actual = canvas.dimensions();
+        if expected.0 > actual.0 as u64 || expected.1 > actual.1 as u64 {
+            return Err(MergeError::DimensionMismatch {
+                expected: (
+                    expected.0.min(u32::MAX as u64) as u32,
+                    expected.1.min(u32::MAX as u64) as u32,
+                ),
+                actual,
+            });
+        }
+
+        Ok(self
+            .placements
+            .par_iter()
+            .map(|placement| {
+                let image = crop(canvas, placement.rect);
+                if placement.rotated {
+                    rotate270(&image)
+                } else {
+                    image
+                }
+            })
+            .collect())
+    }
+}
+
+/// Returns the color of the first column, or else row, of the canvas that is a single uniform color.
+fn uniform_line_color<P, Container>(
+    canvas: &Image<P, image::ImageBuffer<P, Container>>,
+) -> Option<P>
+where
+    P: Pixel + PartialEq + Sync,
+    <P as Pixel>::Subpixel: Sync,
+    Container: DerefMut<Target = [P::Subpixel]> + Sync,
+{
+    let (width, height) = canvas.dimensions();
+
+    let column = (0..width).into_par_iter().find_first(|&x| {
+        let first = canvas.get_pixel(x, 0);
+        (1..height).all(|y| canvas.get_pixel(x, y) == first)
+    });
+    if let Some(x) = column {
+        return Some(*canvas.get_pixel(x, 0));
+    }
+
+    (0..height)
+        .into_par_iter()
+        .find_first(|&y| {
+            let first = canvas.get_pixel(0, y);
+            (1..width).all(|x| canvas.get_pixel(x, y) == first)
+        })
+        .map(|y| *canvas.get_pixel(0, y))
+}
+
+/// Detects the grid along one axis from which lines of the canvas are entirely background. Every run of background
+/// lines is tried as the first gap between cells, and the first one that repeats evenly across the canvas wins.
+/// # Returns
+/// The size of a cell, the padding between cells, and the number of cells along the axis. If no run of background
+/// lines repeats evenly, the whole axis is a single cell with no padding.
+fn detect_axis(background: &[bool]) -> (u32, u32, u32) {
+    let length = background.len() as u32;
+
+    let mut start = 0;
+    while start < length {
+        if !background[start as usize] {
+            start += 1;
+            continue;
+        }
+
+        let end = (start..length)
+            .find(|&index| !background[index as usize])
+            .unwrap_or(length);
+        let (cell, padding) = (start, end - start);
+        let pitch = cell + padding;
+
+        // The canvas holds `count` cells with a gap between each of them, and every gap is entirely background.
+        if start > 0 && end < length && (length + padding).is_multiple_of(pitch) {
+            let count = (length + padding) / pitch;
+            let gaps_are_background = (1..count).all(|index| {
+                let gap = index * pitch - padding;
+                background[gap as usize..(gap + padding) as usize]
+                    .iter()
+                    .all(|&line| line)
+            });
+
+            if gaps_are_background {
+                return (cell, padding, count);
+            }
+        }
+
+        start = end;
+    }
+
+    (length, 0, 1)
+}
@@ -0,0 +1,533 @@
+use crate::{cell::RegionMut, MergeError};
+
+use image::Pixel;
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    fmt::{self, Debug, Formatter},
+};
+
+/// The glyphs of the embedded font, 8x8 pixels each, for the printable ASCII characters followed by an ellipsis. Every
+/// byte is a row of a glyph with bit 0 as its leftmost pixel. Based on the public domain font8x8 by Daniel Hepper.
+#[rustfmt::skip]
+const FONT8X8: [u8; 96 * 8] = [
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // ' '
+    0x18, 0x3C, 0x3C, 0x18, 0x18, 0x00, 0x18, 0x00, // '!'
+    0x36, 0x36, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // '"'
+    0x36, 0x36, 0x7F, 0x36, 0x7F, 0x36, 0x36, 0x00, // '#'
+    0x0C, 0x3E, 0x03, 0x1E, 0x30, 0x1F, 0x0C, 0x00, // '$'
+    0x00, 0x63, 0x33, 0x18, 0x0C, 0x66, 0x63, 0x00, // '%'
+    0x1C, 0x36, 0x1C, 0x6E, 0x3B, 0x33, 0x6E, 0x00, // '&'
+    0x06, 0x06, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00, // '''
+    0x18, 0x0C, 0x06, 0x06, 0x06, 0x0C, 0x18, 0x00, // '('
+    0x06, 0x0C, 0x18, 0x18, 0x18, 0x0C, 0x06, 0x00, // ')'
+    0x00, 0x66, 0x3C, 0xFF, 0x3C, 0x66, 0x00, 0x00, // '*'
+    0x00, 0x0C, 0x0C, 0x3F, 0x0C, 0x0C, 0x00, 0x00, // '+'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C, 0x06, // ','
+    0x00, 0x00, 0x00, 0x3F, 0x00, 0x00, 0x00, 0x00, // '-'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C, 0x00, // '.'
+    0x60, 0x30, 0x18, 0x0C, 0x06, 0x03, 0x01, 0x00, // '/'
+    0x3E, 0x63, 0x73, 0x7B, 0x6F, 0x67, 0x3E, 0x00, // '0'
+    0x0C, 0x0E, 0x0C, 0x0C, 0x0C, 0x0C, 0x3F, 0x00, // '1'
+    0x1E, 0x33, 0x30, 0x1C, 0x06, 0x33, 0x3F, 0x00, // '2'
+    0x1E, 0x33, 0x30, 0x1C, 0x30, 0x33, 0x1E, 0x00, // '3'
+    0x38, 0x3C, 0x36, 0x33, 0x7F, 0x30, 0x78, 0x00, // '4'
+    0x3F, 0x03, 0x1F, 0x30, 0x30, 0x33, 0x1E, 0x00, // '5'
+    0x1C, 0x06, 0x03, 0x1F, 0x33, 0x33, 0x1E, 0x00, // '6'
+    0x3F, 0x33, 0x30, 0x18, 0x0C, 0x0C, 0x0C, 0x00, // '7'
+    0x1E, 0x33, 0x33, 0x1E, 0x33, 0x33, 0x1E, 0x00, // '8'
+    0x1E, 0x33, 0x33, 0x3E, 0x30, 0x18, 0x0E, 0x00, // '9'
+    0x00, 0x0C, 0x0C, 0x00, 0x00, 0x0C, 0x0C, 0x00, // ':'
+    0x00, 0x0C, 0x0C, 0x00, 0x00, 0x0C, 0x0C, 0x06, // ';'
+    0x18, 0x0C, 0x06, 0x03, 0x06, 0x0C, 0x18, 0x00, // '<'
+    0x00, 0x00, 0x3F, 0x00, 0x00, 0x3F, 0x00, 0x00, // '='
+    0x06, 0x0C, 0x18, 0x30, 0x18, 0x0C, 0x06, 0x00, // '>'
+    0x1E, 0x33, 0x30, 0x18, 0x0C, 0x00, 0x0C, 0x00, // '?'
+    0x3E, 0x63, 0x7B, 0x7B, 0x7B, 0x03, 0x1E, 0x00, // '@'
+    0x0C, 0x1E, 0x33, 0x33, 0x3F, 0x33, 0x33, 0x00, // 'A'
+    0x3F, 0x66, 0x66, 0x3E, 0x66, 0x66, 0x3F, 0x00, // 'B'
+    0x3C, 0x66, 0x03, 0x03, 0x03, 0x66, 0x3C, 0x00, // 'C'
+    0x1F, 0x36, 0x66, 0x66, 0x66, 0x36, 0x1F, 0x00, // 'D'
+    0x7F, 0x46, 0x16, 0x1E, 0x16, 0x46, 0x7F, 0x00, // 'E'
+    0x7F, 0x46, 0x16, 0x1E, 0x16, 0x06, 0x0F, 0x00, // 'F'
+    0x3C, 0x66, 0x03, 0x03, 0x73, 0x66, 0x7C, 0x00, // 'G'
+    0x33, 0x33, 0x33, 0x3F, 0x33, 0x33, 0x33, 0x00, // 'H'
+    0x1E, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x1E, 0x00, // 'I'
+    0x78, 0x30, 0x30, 0x30, 0x33, 0x33, 0x1E, 0x00, // 'J'
+    0x67, 0x66, 0x36, 0x1E, 0x36, 0x66, 0x67, 0x00, // 'K'
+    0x0F, 0x06, 0x06, 0x06, 0x46, 0x66, 0x7F, 0x00, // 'L'
+    0x63, 0x77, 0x7F, 0x7F, 0x6B, 0x63, 0x63, 0x00, // 'M'
+    0x63, 0x67, 0x6F, 0x7B, 0x73, 0x63, 0x63, 0x00, // 'N'
+    0x1C, 0x36, 0x63, 0x63, 0x63, 0x36, 0x1C, 0x00, // 'O'
+    0x3F, 0x66, 0x66, 0x3E, 0x06, 0x06, 0x0F, 0x00, // 'P'
+    0x1E, 0x33, 0x33, 0x33, 0x3B, 0x1E, 0x38, 0x00, // 'Q'
+    0x3F, 0x66, 0x66, 0x3E, 0x36, 0x66, 0x67, 0x00, // 'R'
+    0x1E, 0x33, 0x07, 0x0E, 0x38, 0x33, 0x1E, 0x00, // 'S'
+    0x3F, 0x2D, 0x0C, 0x0C, 0x0C, 0x0C, 0x1E, 0x00, // 'T'
+    0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x3F, 0x00, // 'U'
+    0x33, 0x33, 0x33, 0x33, 0x33, 0x1E, 0x0C, 0x00, // 'V'
+    0x63, 0x63, 0x63, 0x6B, 0x7F, 0x77, 0x63, 0x00, // 'W'
+    0x63, 0x63, 0x36, 0x1C, 0x1C, 0x36, 0x63, 0x00, // 'X'
+    0x33, 0x33, 0x33, 0x1E, 0x0C, 0x0C, 0x1E, 0x00, // 'Y'
+    0x7F, 0x63, 0x31, 0x18, 0x4C, 0x66, 0x7F, 0x00, // 'Z'
+    0x1E, 0x06, 0x06, 0x06, 0x06, 0x06, 0x1E, 0x00, // '['
+    0x03, 0x06, 0x0C, 0x18, 0x30, 0x60, 0x40, 0x00, // '\'
+    0x1E, 0x18, 0x18, 0x18, 0x18, 0x18, 0x1E, 0x00, // ']'
+    0x08, 0x1C, 0x36, 0x63, 0x00, 0x00, 0x00, 0x00, // '^'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xFF, // '_'
+    0x0C, 0x0C, 0x18, 0x00, 0x00, 0x00, 0x00, 0x00, // '`'
+    0x00, 0x00, 0x1E, 0x30, 0x3E, 0x33, 0x6E, 0x00, // 'a'
+    0x07, 0x06, 0x06, 0x3E, 0x66, 0x66, 0x3B, 0x00, // 'b'
+    0x00, 0x00, 0x1E, 0x33, 0x03, 0x33, 0x1E, 0x00, // 'c'
+    0x38, 0x30, 0x30, 0x3E, 0x33, 0x33, 0x6E, 0x00, // 'd'
+    0x00, 0x00, 0x1E, 0x33, 0x3F, 0x03, 0x1E, 0x00, // 'e'
+    0x1C, 0x36, 0x06, 0x0F, 0x06, 0x06, 0x0F, 0x00, // 'f'
+    0x00, 0x00, 0x6E, 0x33, 0x33, 0x3E, 0x30, 0x1F, // 'g'
+    0x07, 0x06, 0x36, 0x6E, 0x66, 0x66, 0x67, 0x00, // 'h'
+    0x0C, 0x00, 0x0E, 0x0C, 0x0C, 0x0C, 0x1E, 0x00, // 'i'
+    0x30, 0x00, 0x30, 0x30, 0x30, 0x33, 0x33, 0x1E, // 'j'
+    0x07, 0x06, 0x66, 0x36, 0x1E, 0x36, 0x67, 0x00, // 'k'
+    0x0E, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x1E, 0x00, // 'l'
+    0x00, 0x00, 0x33, 0x7F, 0x7F, 0x6B, 0x63, 0x00, // 'm'
+    0x00, 0x00, 0x1F, 0x33, 0x33, 0x33, 0x33, 0x00, // 'n'
+    0x00, 0x00, 0x1E, 0x33, 0x33, 0x33, 0x1E, 0x00, // 'o'
+    0x00, 0x00, 0x3B, 0x66, 0x66, 0x3E, 0x06, 0x0F, // 'p'
+    0x00, 0x00, 0x6E, 0x33, 0x33, 0x3E, 0x30, 0x78, // 'q'
+    0x00, 0x00, 0x3B, 0x6E, 0x66, 0x06, 0x0F, 0x00, // 'r'
+    0x00, 0x00, 0x3E, 0x03, 0x1E, 0x30, 0x1F, 0x00, // 's'
+    0x08, 0x0C, 0x3E, 0x0C, 0x0C, 0x2C, 0x18, 0x00, // 't'
+    0x00, 0x00, 0x33, 0x33, 0x33, 0x33, 0x6E, 0x00, // 'u'
+    0x00, 0x00, 0x33, 0x33, 0x33, 0x1E, 0x0C, 0x00, // 'v'
+    0x00, 0x00, 0x63, 0x6B, 0x7F, 0x7F, 0x36, 0x00, // 'w'
+    0x00, 0x00, 0x63, 0x36, 0x1C, 0x36, 0x63, 0x00, // 'x'
+    0x00, 0x00, 0x33, 0x33, 0x33, 0x3E, 0x30, 0x1F, // 'y'
+    0x00, 0x00, 0x3F, 0x19, 0x0C, 0x26, 0x3F, 0x00, // 'z'
+    0x38, 0x0C, 0x0C, 0x07, 0x0C, 0x0C, 0x38, 0x00, // '{'
+    0x18, 0x18, 0x18, 0x00, 0x18, 0x18, 0x18, 0x00, // '|'
+    0x07, 0x0C, 0x0C, 0x38, 0x0C, 0x0C, 0x07, 0x00, // '}'
+    0x6E, 0x3B, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // '~'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0xDB, 0xDB, 0x00, // '…'
+];
+
+/// The glyphs of the embedded font, flipped so bit 7 is the leftmost pixel of every row, the same as a PSF font.
+static EMBEDDED: [u8; 96 * 8] = leftmost_first(FONT8X8);
+
+/// The character of the last glyph of the embedded font.
+const ELLIPSIS: char = '…';
+
+const fn leftmost_first<const N: usize>(rows: [u8; N]) -> [u8; N] {
+    let mut flipped = [0; N];
+    let mut index = 0;
+    while index < N {
+        flipped[index] = rows[index].reverse_bits();
+        index += 1;
+    }
+
+    flipped
+}
+
+/// A monospaced bitmap font that text is drawn with, such as the captions of a [KnownSizeMerger](crate::KnownSizeMerger).
+/// The default font is an 8x8 font embedded in the crate, covering printable ASCII, so drawing text needs no system fonts.
+/// Other fonts can be loaded from the PSF files used by the Linux console. Characters the font has no glyph for are
+/// drawn as `?`.
+///
+/// # Example
+/// ```
+/// use image_merger::BitmapFont;
+///
+/// let font = BitmapFont::default();
+/// assert_eq!(font.get_glyph_size(), (8, 8));
+/// assert!(font.has_glyph('A'));
+/// assert!(!font.has_glyph('é'));
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct BitmapFont {
+    width: u32,                     // The width of every glyph, in pixels.
+    height: u32,                    // The height of every glyph, in pixels.
+    glyphs: Cow<'static, [u8]>, // The rows of every glyph, with bit 7 of each byte as its leftmost pixel.
+    characters: HashMap<char, u32>, // The index of the glyph drawn for every character the font covers.
+}
+
+impl Debug for BitmapFont {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        // The glyphs are not worth printing, only how many there are.
+        f.debug_struct("BitmapFont")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .field("glyphs", &self.glyph_count())
+            .finish()
+    }
+}
+
+impl Default for BitmapFont {
+    fn default() -> Self {
+        let characters = (' '..='~')
+            .chain([ELLIPSIS])
+            .zip(0..)
+            .collect::<HashMap<_, _>>();
+
+        Self {
+            width: 8,
+            height: 8,
+            glyphs: Cow::Borrowed(&EMBEDDED),
+            characters,
+        }
+    }
+}
+
+impl BitmapFont {
+    /// Loads a font from the contents of a PSF file, either version 1 or 2. If the font has a unicode table, its glyphs
+    /// are drawn for the characters the table lists, otherwise the glyph at every index is drawn for the character with
+    /// the same code point.
+    /// # Arguments
+    /// * `bytes` - The contents of the PSF file.
+    ///
+    /// # Errors
+    /// * [MergeError::InvalidFont](crate::MergeError::InvalidFont) - If the bytes are not a PSF font, or are cut short.
+    pub fn from_psf(bytes: &[u8]) -> Result<Self, MergeError> {
+        match bytes {
+            [0x36, 0x04, ..] => Self::from_psf1(bytes),
+            [0x72, 0xb5, 0x4a, 0x86, ..] => Self::from_psf2(bytes),
+            _ => Err(MergeError::InvalidFont("the data is not a PSF font")),
+        }
+    }
+
+    fn from_psf1(bytes: &[u8]) -> Result<Self, MergeError> {
+        let (mode, height) = match bytes {
+            [_, _, mode, height, ..] => (*mode, *height as u32),
+            _ => return Err(TRUNCATED),
+        };
+        let count = if mode & 0x01 != 0 { 512 } else { 256 };
+        let glyphs = glyph_data(bytes, 4, count, height as usize)?;
+
+        // Every entry of the table is a list of UCS-2 characters, then an optional list of sequences that start with
+        // 0xFFFE, and ends with 0xFFFF.
+        let characters = if mode & 0x06 != 0 {
+            let table = &bytes[4 + glyphs.len()..];
+            let mut characters = HashMap::new();
+            let mut entries = table
+                .chunks_exact(2)
+                .map(|pair| u16::from_le_bytes([pair[0], pair[1]]));
+            for glyph in 0..count as u32 {
+                let mut in_sequence = false;
+                for entry in entries.by_ref() {
+                    match entry {
+                        0xFFFF => break,
+                        0xFFFE => in_sequence = true,
+                        _ if in_sequence => {}
+                        _ => {
+                            if let Some(character) = char::from_u32(entry as u32) {
+                                characters.entry(character).or_insert(glyph);
+                            }
+                        }
+                    }
+                }
+            }
+
+            characters
+        } else {
+            identity(count as u32)
+        };
+
+        Self::new(8, height, glyphs.to_vec(), characters)
+    }
+
+    fn from_psf2(bytes: &[u8]) -> Result<Self, MergeError> {
+        let field = |index: usize| {
+            bytes
+                .get(4 + index * 4..8 + index * 4)
+                .map(|field| u32::from_le_bytes([field[0], field[1], field[2], field[3]]))
+                .ok_or(TRUNCATED)
+        };
+        let (header_size, flags, count) = (field(1)? as usize, field(2)?, field(3)?);
+        let (glyph_size, height, width) = (field(4)? as usize, field(5)?, field(6)?);
+        if glyph_size != height as usize * width.div_ceil(8) as usize {
+            return Err(MergeError::InvalidFont(
+                "the size of a glyph does not match its dimensions",
+            ));
+        }
+        let glyphs = glyph_data(bytes, header_size, count as usize, glyph_size)?;
+
+        // Every entry of the table is a UTF-8 string of characters, then an optional list of sequences that start with
+        // 0xFE, and ends with 0xFF.
+        let characters = if flags & 0x01 != 0 {
+            let table = &bytes[header_size + glyphs.len()..];
+            let mut characters = HashMap::new();
+            for (entry, glyph) in table
+                .split(|&byte| byte == 0xFF)
+                .take(count as usize)
+                .zip(0..)
+            {
+                let singles = entry.split(|&byte| byte == 0xFE).next().unwrap_or_default();
+                for character in String::from_utf8_lossy(singles).chars() {
+                    if character != char::REPLACEMENT_CHARACTER {
+                        characters.entry(character).or_insert(glyph);
+                    }
+                }
+            }
+
+            characters
+        } else {
+            identity(count)
+        };
+
+        Self::new(width, height, glyphs.to_vec(), characters)
+    }
+
+    fn new(
+        width: u32,
+        height: u32,
+        glyphs: Vec<u8>,
+        characters: HashMap<char, u32>,
+    ) -> Result<Self, MergeError> {
+        if width == 0 || height == 0 {
+            return Err(MergeError::InvalidFont(
+                "glyphs must be at least a pixel in size",
+            ));
+        }
+
+        Ok(Self {
+            width,
+            height,
+            glyphs: Cow::Owned(glyphs),
+            characters,
+        })
+    }
+
+    /// Returns the dimensions, (width, height), of every glyph of the font, in pixels.
+    pub fn get_glyph_size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Returns true if the font has a glyph for the given character.
+    pub fn has_glyph(&self, character: char) -> bool {
+        self.characters.contains_key(&character)
+    }
+
+    /// Returns the number of glyphs in the font.
+    fn glyph_count(&self) -> usize {
+        self.glyphs.len() / self.glyph_bytes()
+    }
+
+    /// Returns the number of bytes every glyph takes up.
+    fn glyph_bytes(&self) -> usize {
+        self.height as usize * self.width.div_ceil(8) as usize
+    }
+
+    /// Returns the index of the glyph drawn for a character, falling back to the glyph of `?`.
+    fn glyph(&self, character: char) -> Option<u32> {
+        self.characters
+            .get(&character)
+            .or_else(|| self.characters.get(&'?'))
+            .copied()
+    }
+
+    /// Returns true if the pixel at the given coordinates of a glyph is set.
+    fn is_set(&self, glyph: u32, x: u32, y: u32) -> bool {
+        let row =
+            glyph as usize * self.glyph_bytes() + y as usize * self.width.div_ceil(8) as usize;
+        self.glyphs[row + x as usize / 8] & (0x80 >> (x % 8)) != 0
+    }
+}
+
+const TRUNCATED: MergeError = MergeError::InvalidFont("the font data is cut short");
+
+/// Returns the glyphs of a PSF font, `count` glyphs of `size` bytes starting at `offset`.
+fn glyph_data(bytes: &[u8], offset: usize, count: usize, size: usize) -> Result<&[u8], MergeError> {
+    count
+        .checked_mul(size)
+        .and_then(|len| bytes.get(offset..offset.checked_add(len)?))
+        .ok_or(TRUNCATED)
+}
+
+/// Maps the first `count` code points to the glyphs with the same index.
+fn identity(count: u32) -> HashMap<char, u32> {
+    (0..count)
+        .filter_map(|glyph| char::from_u32(glyph).map(|character| (character, glyph)))
+        .collect()
+}
+
+/// Where a line of text is placed between the edges of its band.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TextAlign {
+    /// The text starts at the left edge of the band, after its padding.
+    Left,
+    /// The text is centered between the edges of the band.
+    #[default]
+    Center,
+    /// The text ends at the right edge of the band, before its padding.
+    Right,
+}
+
+/// Describes how a line of text is drawn, such as the captions below every cell of a
+/// [KnownSizeMerger](crate::KnownSizeMerger) or the title above its grid. Text is drawn in a band as tall as the font,
+/// scaled up, with padding on every side. Text too long for its band is cut off, ending with an ellipsis by default.
+///
+/// # Type Parameters
+/// * `P` - The pixel type of the canvas.
+///
+/// # Example
+/// ```
+/// use image_merger::{KnownSizeMerger, Merger, MergerBuilder, Rgb, TextAlign, TextStyle};
+///
+/// let style = TextStyle::new(Rgb([0, 0, 0]))
+///     .with_scale(2)
+///     .with_align(TextAlign::Left);
+///
+/// let mut merger: KnownSizeMerger<Rgb<u8>, _> = KnownSizeMerger::builder((100, 100))
+///     .with_images_per_row(5)
+///     .with_total_images(10)
+///     .with_background(Rgb([255, 255, 255]))
+///     .with_captions(style.clone())
+///     .with_title("Contact sheet", style)
+///     .build();
+///
+/// merger.push(&image_merger::BufferedImage::new_from_pixel(100, 100, Rgb([255, 0, 0])));
+/// merger.set_caption(0, "first.png");
+///
+/// // The title band and the caption band below every row are 16 pixels of text with 2 pixels of padding each way.
+/// assert_eq!(merger.get_canvas().dimensions(), (500, 20 + 2 * (100 + 20)));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextStyle<P: Pixel> {
+    /// The font the text is drawn with.
+    pub font: BitmapFont,
+    /// The color of the text.
+    pub color: P,
+    /// How many pixels wide and tall every pixel of the font is drawn.
+    pub scale: u32,
+    /// Where the text is placed between the edges of its band.
+    pub align: TextAlign,
+    /// The space between the text and every edge of its band, in pixels.
+    pub padding: u32,
+    /// Whether text that is too long ends with an ellipsis, rather than just being cut off.
+    pub ellipsis: bool,
+}
+
+impl<P: Pixel> TextStyle<P> {
+    /// Creates a style drawing centered text in the given color with the embedded font, at its own size, with 2 pixels of
+    /// padding.
+    pub fn new(color: P) -> Self {
+        Self {
+            font: BitmapFont::default(),
+            color,
+            scale: 1,
+            align: TextAlign::default(),
+            padding: 2,
+            ellipsis: true,
+        }
+    }
+
+    /// Returns this style with its font set to the given value.
+    pub fn with_font(self, font: BitmapFont) -> Self {
+        Self { font, ..self }
+    }
+
+    /// Returns this style with its scale set to the given value, which must be at least 1.
+    pub fn with_scale(self, scale: u32) -> Self {
+        Self { scale, ..self }
+    }
+
+    /// Returns this style with its alignment set to the given value.
+    pub fn with_align(self, align: TextAlign) -> Self {
+        Self { align, ..self }
+    }
+
+    /// Returns this style with its padding set to the given value.
+    pub fn with_padding(self, padding: u32) -> Self {
+        Self { padding, ..self }
+    }
+
+    /// Returns this style with text that is too long ending with an ellipsis or not.
+    pub fn with_ellipsis(self, ellipsis: bool) -> Self {
+        Self { ellipsis, ..self }
+    }
+
+    /// Returns the height of the band text is drawn in, or None if it overflows.
+    pub(crate) fn band_height(&self) -> Option<u32> {
+        self.font
+            .height
+            .checked_mul(self.scale)?
+            .checked_add(self.padding.checked_mul(2)?)
+    }
+
+    /// Returns how far apart the characters of the text are drawn, the width of a glyph scaled up, or None if it
+    /// overflows.
+    pub(crate) fn advance(&self) -> Option<u32> {
+        self.font.width.checked_mul(self.scale)
+    }
+
+    /// Returns an error message if text cannot be drawn with the style.
+    pub(crate) fn validate(&self) -> Result<(), &'static str> {
+        if self.scale == 0 {
+            return Err("text must be drawn at a scale of at least 1");
+        }
+
+        Ok(())
+    }
+
+    /// Returns the characters of the text that fit in a band of the given width, ending with an ellipsis if the text is
+    /// cut off and the style asks for one.
+    fn fit(&self, text: &str, width: u32) -> Vec<char> {
+        // Can always unwrap here because the builder has already validated the style.
+        let advance = self.advance().unwrap();
+        let room = (width.saturating_sub(2 * self.padding) / advance) as usize;
+
+        let mut characters: Vec<char> = text.chars().collect();
+        if characters.len() <= room {
+            return characters;
+        }
+
+        let ellipsis: &[char] = match (self.ellipsis, self.font.has_glyph(ELLIPSIS)) {
+            (false, _) => &[],
+            (true, true) => &[ELLIPSIS],
+            (true, false) => &['.', '.', '.'],
+        };
+        if ellipsis.len() > room {
+            characters.truncate(room);
+        } else {
+            characters.truncate(room - ellipsis.len());
+            characters.extend_from_slice(ellipsis);
+        }
+
+        characters
+    }
+
+    /// Draws a line of text onto a region, which is the band the text is placed in. Only the pixels of the glyphs are
+    /// written, the rest of the band is left as it is.
+    pub(crate) fn draw(&self, region: &mut RegionMut<'_, P>, text: &str) {
+        let (width, height) = region.dimensions();
+        let characters = self.fit(text, width);
+
+        // Can always unwrap here because the builder has already validated the style.
+        let advance = self.advance().unwrap();
+        let free = width.saturating_sub(2 * self.padding + characters.len() as u32 * advance);
+        let left = self.padding
+            + match self.align {
+                TextAlign::Left => 0,
+                TextAlign::Center => free / 2,
+                TextAlign::Right => free,
+            };
+
+        for (index, character) in characters.into_iter().enumerate() {
+            let Some(glyph) = self.font.glyph(character) else {
+                continue;
+            };
+            let origin = left + index as u32 * advance;
+
+            for y in 0..self.font.height * self.scale {
+                let row = self.padding + y;
+                if row >= height {
+                    break;
+                }
+
+                for x in 0..advance {
+                    let column = origin + x;
+                    if column < width && self.font.is_set(glyph, x / self.scale, y / self.scale) {
+                        region.put_pixel(column, row, self.color);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use crate::Image;
+
+use image::{GenericImage, GenericImageView, ImageBuffer, Pixel, SubImage};
+use std::ops::Deref;
+
+/// An image that can be pushed onto a canvas, or handed to the functions of the [raw](crate::raw) module, without first
+/// being copied into an [Image](crate::Image) of the same type as the canvas.
+///
+/// This trait is implemented for [Image](crate::Image)s and `ImageBuffer`s with any container, including borrowed
+/// `&[u8]` buffers, and for `SubImage` views of them. These lend their pixels straight out of their buffer, so pasting
+/// them copies whole rows at a time. Any other `GenericImageView` can be pushed by wrapping it in a
+/// [GenericView](GenericView), which reads it one pixel at a time.
+///
+/// # Example
+/// ```
+/// use image::GenericImageView;
+/// use image_merger::{BufferedImage, ImageBuffer, KnownSizeMerger, Merger, Rgb};
+///
+/// let sheet: BufferedImage<Rgb<u8>> = BufferedImage::new(200, 100);
+/// let bytes = vec![0u8; 100 * 100 * 3];
+/// let borrowed: ImageBuffer<Rgb<u8>, &[u8]> = ImageBuffer::from_raw(100, 100, &bytes[..]).unwrap();
+///
+/// let mut merger: KnownSizeMerger<Rgb<u8>, _> = KnownSizeMerger::new((100, 100), 2, 4, None);
+/// merger.push(&sheet.view(100, 0, 100, 100));
+/// merger.push(&borrowed);
+/// ```
+pub trait ImageView: Sync {
+    /// The pixel type of the image.
+    type Pixel: Pixel;
+
+    /// Returns the dimensions, (width, height), of the image.
+    fn view_dimensions(&self) -> (u32, u32);
+
+    /// Returns the pixel at (x, y).
+    /// # Panics
+    /// May panic if (x, y) lies outside of the image.
+    fn view_pixel(&self, x: u32, y: u32) -> Self::Pixel;
+
+    /// Returns the subpixels of the image, if it lies in a single buffer. Images that return None are read with
+    /// `view_pixel` instead.
+    fn samples(&self) -> Option<Samples<'_, <Self::Pixel as Pixel>::Subpixel>> {
+        None
+    }
+}
+
+/// The subpixels of an image that lies in a single buffer, as returned by [ImageView::samples](ImageView::samples).
+/// Row `y` of the image starts at `data[y * stride]`, and holds its pixels one after the other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Samples<'a, T> {
+    /// The subpixels of the image, starting at its top left pixel.
+    pub data: &'a [T],
+    /// The number of subpixels between the starts of two consecutive rows.
+    pub stride: usize,
+}
+
+impl<'a, T> Samples<'a, T> {
+    /// Returns the `len` subpixels at the start of row `y`.
+    pub(crate) fn row(&self, y: usize, len: usize) -> &'a [T] {
+        &self.data[y * self.stride..y * self.stride + len]
+    }
+}
+
+/// Borrows any `GenericImageView` so it can be used as an [ImageView](ImageView). The view is read one pixel at a time,
+/// so prefer pushing `ImageBuffer`s, or `SubImage`s of them, directly when possible.
+///
+/// # Example
+/// ```
+/// use image::DynamicImage;
+/// use image_merger::{GenericView, KnownSizeMerger, Merger, Rgba};
+///
+/// let image = DynamicImage::new_rgb8(50, 50);
+///
+/// let mut merger: KnownSizeMerger<Rgba<u8>, _> = KnownSizeMerger::new((50, 50), 2, 2, None);
+/// merger.push(&GenericView(&image));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct GenericView<'a, V: ?Sized>(pub &'a V);
+
+impl<V> ImageView for GenericView<'_, V>
+where
+    V: GenericImageView + Sync + ?Sized,
+{
+    type Pixel = V::Pixel;
+
+    fn view_dimensions(&self) -> (u32, u32) {
+        self.0.dimensions()
+    }
+
+    fn view_pixel(&self, x: u32, y: u32) -> V::Pixel {
+        self.0.get_pixel(x, y)
+    }
+}
+
+impl<P, Container> ImageView for ImageBuffer<P, Container>
+where
+    P: Pixel + Sync,
+    Container: Deref<Target = [P::Subpixel]> + Sync,
+{
+    type Pixel = P;
+
+    fn view_dimensions(&self) -> (u32, u32) {
+        self.dimensions()
+    }
+
+    fn view_pixel(&self, x: u32, y: u32) -> P {
+        *self.get_pixel(x, y)
+    }
+
+    fn samples(&self) -> Option<Samples<'_, P::Subpixel>> {
+        Some(Samples {
+            data: self.as_raw(),
+            stride: self.width() as usize * <P as Pixel>::CHANNEL_COUNT as usize,
+        })
+    }
+}
+
+impl<P, U> ImageView for Image<P, U>
+where
+    P: Pixel,
+    U: GenericImage<Pixel = P> + ImageView<Pixel = P>,
+{
+    type Pixel = P;
+
+    fn view_dimensions(&self) -> (u32, u32) {
+        (**self).view_dimensions()
+    }
+
+    fn view_pixel(&self, x: u32, y: u32) -> P {
+        (**self).view_pixel(x, y)
+    }
+
+    fn samples(&self) -> Option<Samples<'_, P::Subpixel>> {
+        (**self).samples()
+    }
+}
+
+impl<P, I> ImageView for SubImage<I>
+where
+    P: Pixel,
+    I: Deref + Sync,
+    I::Target: GenericImageView<Pixel = P> + ImageView<Pixel = P>,
+{
+    type Pixel = P;
+
+    fn view_dimensions(&self) -> (u32, u32) {
+        GenericImageView::dimensions(&**self)
+    }
+
+    fn view_pixel(&self, x: u32, y: u32) -> P {
+        let (offset_x, offset_y) = self.offsets();
+        self.inner().view_pixel(offset_x + x, offset_y + y)
+    }
+
+    fn samples(&self) -> Option<Samples<'_, P::Subpixel>> {
+        let (offset_x, offset_y) = self.offsets();
+        self.inner().samples().map(|samples| Samples {
+            data: &samples.data[offset_y as usize * samples.stride
+                + offset_x as usize * <P as Pixel>::CHANNEL_COUNT as usize..],
+            stride: samples.stride,
+        })
+    }
+}
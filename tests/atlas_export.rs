@@ -0,0 +1,125 @@
+use image_merger::*;
+use serde_json::Value;
+
+fn generate_known_merger() -> KnownSizeMerger<Rgb<u8>, Vec<u8>> {
+    let mut merger: KnownSizeMerger<Rgb<u8>, _> =
+        KnownSizeMerger::new((10, 20), 3, 6, Some(Point { x: 2, y: 4 }));
+    let image = Image::new(10, 20);
+    merger.bulk_push(&[&image, &image, &image, &image]);
+    merger
+}
+
+#[test]
+fn test_known_size_placements() {
+    let merger = generate_known_merger();
+    let placements = merger.get_placements();
+    assert_eq!(placements.len(), 4);
+
+    for (index, placement) in placements.iter().enumerate() {
+        let (column, row) = (index as u32 % 3, index as u32 / 3);
+        assert_eq!(
+            placement.rect,
+            Rect {
+                x: column * 12,
+                y: row * 24,
+                width: 10,
+                height: 20
+            }
+        );
+        assert!(!placement.rotated);
+        assert_eq!(merger.get_cell(index as u32), Some(placement.rect));
+    }
+
+    assert!(merger.get_cell(6).is_none());
+}
+
+#[test]
+fn test_unknown_size_atlas_is_trimmed() {
+    let mut merger: UnknownSizeMerger<Rgb<u8>> = UnknownSizeMerger::new((10, 10), 2, None);
+    let image = Image::new(10, 10);
+    merger.bulk_push(&[&image, &image, &image]);
+    merger.reserve(20);
+
+    let atlas = merger.get_atlas();
+    assert_eq!((atlas.width, atlas.height), (20, 20));
+    assert_eq!(
+        (atlas.width, atlas.height),
+        merger.into_canvas().dimensions()
+    );
+}
+
+#[test]
+fn test_atlas_json() {
+    let atlas = generate_known_merger()
+        .get_atlas()
+        .with_names(["a", "b", "c"]);
+    let json: Value = serde_json::from_str(&atlas.to_json()).unwrap();
+
+    assert_eq!(json["width"], 34);
+    assert_eq!(json["height"], 44);
+
+    let images = json["images"].as_array().unwrap();
+    assert_eq!(images.len(), 4);
+    assert_eq!(images[1]["name"], "b");
+    assert_eq!(images[1]["x"], 12);
+    assert_eq!(images[3]["name"], "3");
+    assert_eq!(images[3]["y"], 24);
+    assert_eq!(images[3]["rotated"], false);
+}
+
+#[test]
+fn test_texture_packer_formats() {
+    let wide: BufferedImage<Rgba<u8>> = BufferedImage::new(30, 10);
+    let mut merger: PackingMerger<Rgba<u8>> = PackingMerger::new(
+        (10, 30),
+        PackingOptions {
+            allow_rotation: true,
+            ..Default::default()
+        },
+    );
+    merger.push(&wide);
+
+    let atlas = merger.get_atlas().with_names(["wide.png"]);
+
+    let hash: Value = serde_json::from_str(&atlas.to_texture_packer_hash("atlas.png")).unwrap();
+    let frame = &hash["frames"]["wide.png"];
+    assert_eq!(frame["rotated"], true);
+    // TexturePacker stores the unrotated dimensions of rotated frames.
+    assert_eq!(frame["frame"]["w"], 30);
+    assert_eq!(frame["frame"]["h"], 10);
+    assert_eq!(frame["sourceSize"]["w"], 30);
+    assert_eq!(hash["meta"]["image"], "atlas.png");
+    assert_eq!(hash["meta"]["size"]["w"], 10);
+    assert_eq!(hash["meta"]["size"]["h"], 30);
+
+    let array: Value = serde_json::from_str(&atlas.to_texture_packer_array("atlas.png")).unwrap();
+    let frames = array["frames"].as_array().unwrap();
+    assert_eq!(frames.len(), 1);
+    assert_eq!(frames[0]["filename"], "wide.png");
+    assert_eq!(frames[0]["frame"], frame["frame"]);
+}
+
+#[test]
+fn test_css_sprites() {
+    let atlas = generate_known_merger()
+        .get_atlas()
+        .with_names(["icon one", "icon.two"]);
+    let css = atlas.to_css("sprites.png", "icon");
+
+    assert!(css.contains(".icon {"));
+    assert!(css.contains("url('sprites.png')"));
+    assert!(css.contains(
+        ".icon-icon-one {\n  background-position: 0 0;\n  width: 10px;\n  height: 20px;\n}"
+    ));
+    assert!(css.contains(".icon-icon-two {\n  background-position: -12px 0;"));
+    assert!(css.contains(".icon-3 {\n  background-position: 0 -24px;"));
+}
+
+#[test]
+fn test_css_escapes_image_url() {
+    let css = generate_known_merger()
+        .get_atlas()
+        .to_css("it's\\a (sprite)\n.png", "icon");
+
+    assert!(css.contains("url('it\\'s\\\\a (sprite)\\a .png');"));
+}
@@ -0,0 +1,159 @@
+use image_merger::*;
+
+fn checkerboard() -> Background<Rgb<u8>> {
+    Background::Checkerboard {
+        first: Rgb([40, 40, 40]),
+        second: Rgb([220, 220, 220]),
+        size: 3,
+    }
+}
+
+fn merger(background: Background<Rgb<u8>>) -> KnownSizeMerger<Rgb<u8>, Vec<u8>> {
+    KnownSizeMerger::builder((8, 8))
+        .with_images_per_row(3)
+        .with_total_images(6)
+        .with_padding(Point { x: 2, y: 3 })
+        .with_margin(4)
+        .with_background(background)
+        .build()
+}
+
+#[test]
+fn test_background_fills_margin_and_gutters() {
+    let merger = merger(checkerboard());
+    let canvas = merger.get_canvas();
+
+    for (x, y) in [(0, 0), (3, 3), (12, 5), (13, 20), (canvas.width() - 1, 7)] {
+        assert_eq!(
+            *canvas.get_pixel(x, y),
+            checkerboard().pixel_at(x, y, canvas.dimensions())
+        );
+    }
+}
+
+#[test]
+fn test_remove_image_restores_background() {
+    let image = BufferedImage::new_from_pixel(8, 8, Rgb([255, 0, 0]));
+    let fresh = merger(checkerboard());
+
+    let mut merger = merger(checkerboard());
+    merger.bulk_push(&[&image, &image, &image]);
+    merger.remove_image(0);
+    merger.remove_image(2);
+
+    let cell = merger.get_cell(1).unwrap();
+    assert_eq!(
+        *merger.get_canvas().get_pixel(cell.x, cell.y),
+        Rgb([255, 0, 0])
+    );
+
+    merger.remove_image(1);
+    assert_eq!(**merger.get_canvas(), **fresh.get_canvas());
+}
+
+#[test]
+fn test_replace_image_draws_background_around_smaller_image() {
+    let background = Background::LinearGradient {
+        start: Rgb([0, 0, 0]),
+        end: Rgb([255, 128, 0]),
+        angle: 30.0,
+    };
+    let large = BufferedImage::new_from_pixel(8, 8, Rgb([0, 0, 255]));
+    let small = BufferedImage::new_from_pixel(4, 4, Rgb([0, 255, 0]));
+
+    let mut merger = merger(background.clone());
+    merger.set_size_policy(SizePolicy::CenterSmaller);
+    merger.push(&large);
+    merger.replace_image(0, &small);
+
+    let canvas = merger.get_canvas();
+    let cell = merger.get_cell(0).unwrap();
+    assert_eq!(
+        *canvas.get_pixel(cell.x, cell.y),
+        background.pixel_at(cell.x, cell.y, canvas.dimensions())
+    );
+    assert_eq!(*canvas.get_pixel(cell.x + 4, cell.y + 4), Rgb([0, 255, 0]));
+}
+
+#[test]
+fn test_linear_gradient_spans_canvas() {
+    let background = Background::LinearGradient {
+        start: Rgb([0, 0, 0]),
+        end: Rgb([200, 100, 0]),
+        angle: 0.0,
+    };
+
+    assert_eq!(background.pixel_at(0, 0, (100, 10)), Rgb([1, 1, 0]));
+    assert_eq!(background.pixel_at(0, 9, (100, 10)), Rgb([1, 1, 0]));
+    assert_eq!(background.pixel_at(49, 5, (100, 10)), Rgb([99, 50, 0]));
+    assert_eq!(background.pixel_at(99, 5, (100, 10)), Rgb([199, 100, 0]));
+
+    let vertical = Background::LinearGradient {
+        start: Luma([0u8]),
+        end: Luma([100]),
+        angle: 90.0,
+    };
+    assert_eq!(vertical.pixel_at(7, 0, (10, 100)), Luma([1]));
+    assert_eq!(vertical.pixel_at(7, 99, (10, 100)), Luma([100]));
+}
+
+#[test]
+fn test_radial_gradient_and_pattern() {
+    let radial = Background::RadialGradient {
+        inner: Luma([255u8]),
+        outer: Luma([0]),
+    };
+    assert_eq!(radial.pixel_at(50, 50, (100, 100)), Luma([252]));
+    assert_eq!(radial.pixel_at(0, 0, (100, 100)), Luma([3]));
+
+    let tile = BufferedImage::new_from_pixel(2, 3, Luma([7u8]));
+    let mut pattern = (*tile).clone();
+    pattern.put_pixel(1, 2, Luma([9]));
+    let pattern = Background::Pattern(pattern.into());
+    assert_eq!(pattern.pixel_at(5, 8, (20, 20)), Luma([9]));
+    assert_eq!(pattern.pixel_at(4, 8, (20, 20)), Luma([7]));
+}
+
+#[test]
+fn test_invalid_background() {
+    let empty = Background::Checkerboard {
+        first: Rgb([0, 0, 0]),
+        second: Rgb([255, 255, 255]),
+        size: 0,
+    };
+    let result = KnownSizeMerger::builder((8, 8))
+        .with_images_per_row(2)
+        .with_total_images(2)
+        .with_background(empty)
+        .try_build();
+
+    assert!(matches!(result, Err(MergeError::InvalidLayout(_))));
+}
+
+#[test]
+fn test_unknown_size_merger_background_grows_without_seams() {
+    let gradient = Background::LinearGradient {
+        start: Rgb([0, 0, 0]),
+        end: Rgb([255, 255, 255]),
+        angle: 90.0,
+    };
+    let mut merger: UnknownSizeMerger<Rgb<u8>> = UnknownSizeMerger::builder((10, 10))
+        .with_images_per_row(1)
+        .with_capacity(2)
+        .with_margin(2)
+        .with_background(gradient.clone())
+        .build();
+    let built = merger.get_canvas().dimensions();
+    assert_eq!(built, (14, 24));
+
+    for _ in 0..5 {
+        merger.push(&BufferedImage::new_from_pixel(10, 10, Rgb([255, 0, 0])));
+    }
+
+    // The left margin runs down the whole canvas, continuing the gradient of the canvas the merger was built with.
+    let canvas = merger.into_canvas();
+    assert_eq!(canvas.height(), 54);
+    for y in 0..canvas.height() {
+        assert_eq!(*canvas.get_pixel(0, y), gradient.pixel_at(0, y, built));
+    }
+}
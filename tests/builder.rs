@@ -0,0 +1,159 @@
+use image_merger::*;
+
+type RgbImageBuffer = BufferedImage<Rgb<u8>>;
+
+#[test]
+fn test_builder_matches_new() {
+    let image: RgbImageBuffer = Image::new_from_pixel(10, 10, Rgb([200, 100, 50]));
+
+    let mut built: KnownSizeMerger<Rgb<u8>, _> = KnownSizeMerger::builder((10, 10))
+        .with_images_per_row(3)
+        .with_total_images(5)
+        .with_padding(Point { x: 2, y: 3 })
+        .build();
+    let mut expected: KnownSizeMerger<Rgb<u8>, _> =
+        KnownSizeMerger::new((10, 10), 3, 5, Some(Point { x: 2, y: 3 }));
+
+    built.bulk_push(&[&image, &image, &image, &image]);
+    expected.bulk_push(&[&image, &image, &image, &image]);
+
+    assert_eq!(**built.get_canvas(), **expected.get_canvas());
+    assert_eq!(built.get_placements(), expected.get_placements());
+}
+
+#[test]
+fn test_builder_margin_and_background() {
+    let image: RgbImageBuffer = Image::new_from_pixel(10, 10, Rgb([1, 2, 3]));
+
+    let mut merger: KnownSizeMerger<Rgb<u8>, _> = KnownSizeMerger::builder((10, 10))
+        .with_images_per_row(2)
+        .with_total_images(4)
+        .with_padding(Point { x: 2, y: 2 })
+        .with_margin(5)
+        .with_background(Rgb([255, 255, 255]))
+        .build();
+    merger.push(&image);
+
+    assert_eq!(merger.get_spacing().margin, Margin::uniform(5));
+    assert_eq!(merger.get_canvas().dimensions(), (32, 32));
+    assert_eq!(
+        merger.get_cell(3),
+        Some(Rect {
+            x: 17,
+            y: 17,
+            width: 10,
+            height: 10
+        })
+    );
+
+    let canvas = merger.get_canvas();
+    assert_eq!(*canvas.get_pixel(4, 4), Rgb([255, 255, 255]));
+    assert_eq!(*canvas.get_pixel(5, 5), Rgb([1, 2, 3]));
+    assert_eq!(*canvas.get_pixel(14, 14), Rgb([1, 2, 3]));
+    assert_eq!(*canvas.get_pixel(15, 5), Rgb([255, 255, 255]));
+    assert_eq!(*canvas.get_pixel(31, 31), Rgb([255, 255, 255]));
+}
+
+#[test]
+fn test_builder_alignment() {
+    let image: RgbImageBuffer = Image::new_from_pixel(4, 2, Rgb([9, 9, 9]));
+
+    let mut merger: KnownSizeMerger<Rgb<u8>, _> = KnownSizeMerger::builder((10, 10))
+        .with_images_per_row(1)
+        .with_total_images(1)
+        .with_size_policy(SizePolicy::CenterSmaller)
+        .with_alignment(Alignment::BottomRight)
+        .build();
+    merger.push(&image);
+
+    let canvas = merger.get_canvas();
+    assert_eq!(*canvas.get_pixel(6, 8), Rgb([9, 9, 9]));
+    assert_eq!(*canvas.get_pixel(9, 9), Rgb([9, 9, 9]));
+    assert_eq!(*canvas.get_pixel(5, 9), Rgb([0, 0, 0]));
+    assert_eq!(*canvas.get_pixel(9, 7), Rgb([0, 0, 0]));
+}
+
+#[test]
+fn test_builder_options_reach_merger() {
+    let fit_mode = FitMode::Contain {
+        fill: Rgb([1, 1, 1]),
+        alignment: Alignment::Top,
+    };
+    let composite = Composite::default().with_opacity(0.5);
+
+    let merger: KnownSizeMerger<Rgb<u8>, _> = KnownSizeMerger::builder((10, 10))
+        .with_images_per_row(1)
+        .with_total_images(1)
+        .with_resize_filter(ResizeFilter::Bilinear)
+        .with_fit_mode(fit_mode)
+        .with_composite(composite)
+        .with_background(Rgb([7, 7, 7]))
+        .build();
+
+    assert_eq!(merger.get_resize_filter(), ResizeFilter::Bilinear);
+    assert_eq!(merger.get_fit_mode(), fit_mode);
+    assert_eq!(merger.get_composite(), composite);
+    assert!(matches!(
+        merger.get_background(),
+        Some(Background::Solid(Rgb([7, 7, 7])))
+    ));
+}
+
+#[test]
+fn test_builder_from_raw() {
+    let container = vec![3u8; 24 * 12 * 3];
+
+    let mut merger: KnownSizeMerger<Rgb<u8>, _> = KnownSizeMerger::builder((10, 10))
+        .with_images_per_row(2)
+        .with_total_images(2)
+        .with_margin(1)
+        .with_padding(Point { x: 2, y: 0 })
+        .build_from_raw(container.clone());
+    assert_eq!(*merger.get_canvas().get_pixel(0, 0), Rgb([3, 3, 3]));
+    assert!(merger.get_background().is_none());
+
+    // Without a background, clearing a cell never paints over the pixels of the container.
+    merger.remove_image(1);
+    assert_eq!(merger.get_canvas().as_raw(), &container);
+
+    let merger: KnownSizeMerger<Rgb<u8>, _> = KnownSizeMergerBuilder::new((10, 10))
+        .with_images_per_row(2)
+        .with_total_images(2)
+        .with_margin(1)
+        .with_padding(Point { x: 2, y: 0 })
+        .with_background(Rgb([0, 0, 255]))
+        .build_from_raw(container);
+    assert_eq!(*merger.get_canvas().get_pixel(23, 11), Rgb([0, 0, 255]));
+}
+
+#[test]
+fn test_builder_validation() {
+    let missing_layout = KnownSizeMerger::<Rgb<u8>, _>::builder((10, 10)).try_build();
+    assert!(matches!(missing_layout, Err(MergeError::InvalidLayout(_))));
+
+    let empty_images = KnownSizeMerger::<Rgb<u8>, _>::builder((0, 10))
+        .with_images_per_row(1)
+        .with_total_images(1)
+        .try_build();
+    assert!(matches!(empty_images, Err(MergeError::InvalidLayout(_))));
+
+    let huge_margin = KnownSizeMerger::<Rgb<u8>, _>::builder((10, 10))
+        .with_images_per_row(1)
+        .with_total_images(1)
+        .with_margin(u32::MAX / 2)
+        .try_build();
+    assert!(matches!(huge_margin, Err(MergeError::CanvasTooLarge)));
+
+    let small_buffer = KnownSizeMergerBuilder::<Rgb<u8>>::new((10, 10))
+        .with_images_per_row(1)
+        .with_total_images(1)
+        .with_margin(1)
+        .try_build_from_raw(vec![0u8; 10 * 10 * 3]);
+    assert!(matches!(
+        small_buffer,
+        Err(MergeError::BufferTooSmall {
+            required: 432,
+            actual: 300
+        })
+    ));
+}
@@ -0,0 +1,309 @@
+use image_merger::*;
+
+const BLACK: Rgb<u8> = Rgb([0, 0, 0]);
+const WHITE: Rgb<u8> = Rgb([255, 255, 255]);
+
+fn merger(
+    image_dimensions: (u32, u32),
+    style: TextStyle<Rgb<u8>>,
+) -> KnownSizeMerger<Rgb<u8>, Vec<u8>> {
+    KnownSizeMerger::builder(image_dimensions)
+        .with_images_per_row(2)
+        .with_total_images(4)
+        .with_padding(Point { x: 2, y: 2 })
+        .with_margin(1)
+        .with_background(WHITE)
+        .with_captions(style)
+        .build()
+}
+
+/// Returns true if the pixel at the given coordinates of the caption band of a slot is black.
+fn is_set(merger: &KnownSizeMerger<Rgb<u8>, Vec<u8>>, index: u32, x: u32, y: u32) -> bool {
+    let band = merger.get_caption_band(index).unwrap();
+    *merger.get_canvas().get_pixel(band.x + x, band.y + y) == BLACK
+}
+
+#[test]
+fn test_captions_reserve_band_below_every_row() {
+    let merger = merger((10, 10), TextStyle::new(BLACK));
+
+    // Every caption band is 8 pixels of text with 2 pixels of padding above and below it.
+    assert_eq!(merger.get_canvas().dimensions(), (24, 48));
+    assert_eq!(merger.get_cell(2).map(|cell| cell.y), Some(25));
+    assert_eq!(
+        merger.get_caption_band(1),
+        Some(Rect {
+            x: 13,
+            y: 11,
+            width: 10,
+            height: 12
+        })
+    );
+    assert_eq!(merger.get_caption_band(4), None);
+}
+
+#[test]
+fn test_caption_is_drawn_in_band() {
+    let mut merger = merger(
+        (40, 10),
+        TextStyle::new(BLACK)
+            .with_align(TextAlign::Left)
+            .with_padding(0),
+    );
+    merger.push(&BufferedImage::new_from_pixel(40, 10, Rgb([255, 0, 0])));
+    merger.set_caption(0, "A");
+
+    // The top row of an `A` covers its third and fourth pixels.
+    assert!(!is_set(&merger, 0, 1, 0));
+    assert!(is_set(&merger, 0, 2, 0));
+    assert!(is_set(&merger, 0, 3, 0));
+    assert!(!is_set(&merger, 0, 4, 0));
+
+    // Setting the caption again replaces it, rather than drawing over it.
+    merger.set_caption(0, " ");
+    assert!(!is_set(&merger, 0, 2, 0));
+}
+
+#[test]
+fn test_long_caption_is_truncated() {
+    let style = TextStyle::new(BLACK)
+        .with_align(TextAlign::Left)
+        .with_padding(0);
+    let mut ellipsis = merger((40, 10), style.clone());
+    let mut clipped = merger((40, 10), style.with_ellipsis(false));
+
+    // Only five characters fit, so the fifth becomes an ellipsis, or an `e` if the text is just cut off.
+    ellipsis.set_caption(0, "abcdefgh");
+    clipped.set_caption(0, "abcdefgh");
+
+    assert!(is_set(&ellipsis, 0, 32, 6));
+    assert!(!is_set(&ellipsis, 0, 34, 6));
+    assert!(!is_set(&clipped, 0, 32, 6));
+    assert!(is_set(&clipped, 0, 33, 6));
+}
+
+#[test]
+fn test_caption_scale_and_alignment() {
+    let merger_with = |align| {
+        let mut merger = merger(
+            (40, 10),
+            TextStyle::new(BLACK)
+                .with_scale(2)
+                .with_align(align)
+                .with_padding(0),
+        );
+        merger.set_caption(0, "I");
+        merger
+    };
+
+    // The top row of an `I` covers its second to fifth pixels, drawn twice as large.
+    let right = merger_with(TextAlign::Right);
+    assert_eq!(right.get_caption_band(0).map(|band| band.height), Some(16));
+    assert!(!is_set(&right, 0, 25, 0));
+    assert!(is_set(&right, 0, 26, 0));
+    assert!(is_set(&right, 0, 33, 1));
+    assert!(!is_set(&right, 0, 34, 0));
+
+    let center = merger_with(TextAlign::Center);
+    assert!(is_set(&center, 0, 14, 0));
+    assert!(!is_set(&center, 0, 13, 0));
+}
+
+#[test]
+fn test_remove_image_clears_caption() {
+    let fresh = merger((10, 10), TextStyle::new(BLACK).with_padding(0));
+
+    let mut merger = merger((10, 10), TextStyle::new(BLACK).with_padding(0));
+    merger.push(&BufferedImage::new_from_pixel(10, 10, Rgb([255, 0, 0])));
+    merger.set_caption(0, "X");
+    merger.remove_image(0);
+
+    assert_eq!(**merger.get_canvas(), **fresh.get_canvas());
+}
+
+#[test]
+fn test_clear_cell_clears_frame_and_caption() {
+    let decorated = || -> KnownSizeMerger<Rgb<u8>, Vec<u8>> {
+        KnownSizeMerger::builder((10, 10))
+            .with_images_per_row(2)
+            .with_total_images(4)
+            .with_padding(Point { x: 4, y: 2 })
+            .with_margin(2)
+            .with_background(WHITE)
+            .with_decoration(CellDecoration::default().with_border(
+                2,
+                BLACK,
+                BorderPlacement::Outside,
+            ))
+            .with_captions(TextStyle::new(BLACK).with_padding(0))
+            .build()
+    };
+    let fresh = decorated();
+
+    let mut merger = decorated();
+    merger.push(&BufferedImage::new_from_pixel(10, 10, Rgb([255, 0, 0])));
+    merger.set_caption(0, "X");
+    merger.clear_cell(0, Rgb([0, 0, 255]));
+
+    // Only the cell keeps the fill, the border around it and the caption below it are back to the background.
+    let cell = merger.get_cell(0).unwrap();
+    let (fresh, canvas) = (fresh.get_canvas(), merger.get_canvas());
+    for (x, y, pixel) in canvas.enumerate_pixels() {
+        if x >= cell.x && x < cell.x + cell.width && y >= cell.y && y < cell.y + cell.height {
+            assert_eq!(*pixel, Rgb([0, 0, 255]));
+        } else {
+            assert_eq!(pixel, fresh.get_pixel(x, y));
+        }
+    }
+    assert!(!merger.is_occupied(0));
+}
+
+#[test]
+fn test_swap_images_moves_captions() {
+    let mut merger = merger(
+        (16, 10),
+        TextStyle::new(BLACK)
+            .with_align(TextAlign::Left)
+            .with_padding(0),
+    );
+    merger.set_caption(0, "|");
+    merger.set_caption(3, "-");
+    merger.swap_images(0, 3);
+
+    // A `|` covers the middle of its top row, a `-` only covers its fourth row.
+    assert!(is_set(&merger, 3, 3, 0));
+    assert!(!is_set(&merger, 0, 3, 0));
+    assert!(is_set(&merger, 0, 0, 3));
+}
+
+#[test]
+fn test_title_is_drawn_above_grid() {
+    let merger: KnownSizeMerger<Rgb<u8>, _> = KnownSizeMerger::builder((10, 10))
+        .with_images_per_row(3)
+        .with_total_images(3)
+        .with_margin(4)
+        .with_background(WHITE)
+        .with_title("T", TextStyle::new(BLACK).with_align(TextAlign::Left))
+        .build();
+
+    assert_eq!(merger.get_canvas().dimensions(), (38, 12 + 4 + 10 + 4));
+    assert_eq!(merger.get_cell(0).map(|cell| cell.y), Some(16));
+    assert_eq!(merger.get_spacing().margin.top, 16);
+
+    // The top row of a `T` spans its first six pixels, inside the left margin and the padding of the band.
+    let canvas = merger.get_canvas();
+    assert_eq!(*canvas.get_pixel(6, 2), BLACK);
+    assert_eq!(*canvas.get_pixel(11, 2), BLACK);
+    assert_eq!(*canvas.get_pixel(12, 2), WHITE);
+    assert_eq!(*canvas.get_pixel(5, 2), WHITE);
+}
+
+#[test]
+fn test_captions_and_title_sit_outside_frames() {
+    let style = TextStyle::new(BLACK).with_align(TextAlign::Left);
+    let mut merger: KnownSizeMerger<Rgb<u8>, _> = KnownSizeMerger::builder((10, 10))
+        .with_images_per_row(2)
+        .with_total_images(4)
+        .with_padding(Point { x: 4, y: 2 })
+        .with_margin(2)
+        .with_background(WHITE)
+        .with_decoration(CellDecoration::default().with_border(2, BLACK, BorderPlacement::Outside))
+        .with_captions(style.clone().with_padding(0))
+        .with_title("T", style)
+        .build();
+
+    // The bottom of every frame lies between its image and its caption band, only the top reaches into the gutter.
+    assert_eq!(
+        merger.get_canvas().dimensions(),
+        (28, 12 + 2 + 2 * 20 + 2 + 2)
+    );
+    assert_eq!(merger.get_cell(0).map(|cell| cell.y), Some(14));
+    assert_eq!(merger.get_caption_band(0).map(|band| band.y), Some(26));
+    assert_eq!(merger.get_cell(2).map(|cell| cell.y), Some(36));
+
+    merger.set_caption(0, "|");
+    let before = (**merger.get_canvas()).clone();
+    merger.push(&BufferedImage::new_from_pixel(10, 10, Rgb([255, 0, 0])));
+
+    let canvas = merger.get_canvas();
+    for x in 0..14 {
+        assert_eq!(*canvas.get_pixel(x, 12), BLACK);
+        assert_eq!(*canvas.get_pixel(x, 25), BLACK);
+    }
+
+    // Neither the title above the grid nor the caption below the image is drawn over by the frame.
+    for y in (0..12).chain(26..34) {
+        for x in 0..28 {
+            assert_eq!(canvas.get_pixel(x, y), before.get_pixel(x, y));
+        }
+    }
+    assert!(is_set(&merger, 0, 3, 0));
+}
+
+#[test]
+fn test_psf_fonts() {
+    // A PSF2 font of two 3x2 glyphs, with a unicode table mapping `x` to the first and `y` and `z` to the second.
+    let mut psf = vec![0x72, 0xb5, 0x4a, 0x86];
+    for field in [0u32, 32, 1, 2, 2, 2, 3] {
+        psf.extend_from_slice(&field.to_le_bytes());
+    }
+    psf.extend_from_slice(&[0b1010_0000, 0b0100_0000, 0b1110_0000, 0b0000_0000]);
+    psf.extend_from_slice(b"x\xffyz\xfe\xff");
+
+    let font = BitmapFont::from_psf(&psf).unwrap();
+    assert_eq!(font.get_glyph_size(), (3, 2));
+    assert!(font.has_glyph('x') && font.has_glyph('z'));
+    assert!(!font.has_glyph('\u{0}'));
+
+    let mut merger = merger(
+        (10, 10),
+        TextStyle::new(BLACK)
+            .with_font(font.clone())
+            .with_align(TextAlign::Left)
+            .with_padding(0),
+    );
+    assert_eq!(merger.get_caption_band(0).map(|band| band.height), Some(2));
+
+    merger.set_caption(0, "xz");
+    assert!(is_set(&merger, 0, 0, 0));
+    assert!(!is_set(&merger, 0, 1, 0));
+    assert!(is_set(&merger, 0, 1, 1));
+    assert!(is_set(&merger, 0, 4, 0));
+    assert!(!is_set(&merger, 0, 4, 1));
+
+    // The glyphs are wider than they are tall, so they overflow across before the band does.
+    let result = KnownSizeMerger::<Rgb<u8>, Vec<u8>>::builder((10, 10))
+        .with_captions(
+            TextStyle::new(BLACK)
+                .with_font(font)
+                .with_scale(u32::MAX / 2)
+                .with_padding(0),
+        )
+        .try_build();
+    assert!(matches!(result, Err(MergeError::CanvasTooLarge)));
+
+    assert!(matches!(
+        BitmapFont::from_psf(&psf[..34]),
+        Err(MergeError::InvalidFont(_))
+    ));
+    assert!(matches!(
+        BitmapFont::from_psf(b"not a font"),
+        Err(MergeError::InvalidFont(_))
+    ));
+}
+
+#[test]
+fn test_invalid_captions() {
+    let result = KnownSizeMerger::builder((10, 10))
+        .with_images_per_row(2)
+        .with_total_images(2)
+        .with_captions(TextStyle::new(BLACK).with_scale(0))
+        .try_build();
+    assert!(matches!(result, Err(MergeError::InvalidLayout(_))));
+
+    let mut merger: KnownSizeMerger<Rgb<u8>, _> = KnownSizeMerger::new((10, 10), 2, 2, None);
+    assert!(matches!(
+        merger.try_set_caption(0, "a"),
+        Err(MergeError::InvalidLayout(_))
+    ));
+}
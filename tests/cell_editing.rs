@@ -0,0 +1,193 @@
+use image_merger::*;
+
+type RgbImageBuffer = BufferedImage<Rgb<u8>>;
+
+const WHITE: Rgb<u8> = Rgb([255, 255, 255]);
+
+fn solid(value: u8) -> RgbImageBuffer {
+    Image::new_from_pixel(4, 4, Rgb([value, value, value]))
+}
+
+/// A padded, margined 3x2 grid on a white canvas.
+fn merger() -> KnownSizeMerger<Rgb<u8>, Vec<u8>> {
+    KnownSizeMerger::builder((4, 4))
+        .with_images_per_row(3)
+        .with_total_images(6)
+        .with_padding(Point { x: 2, y: 3 })
+        .with_margin(1)
+        .with_background(WHITE)
+        .build()
+}
+
+/// Returns the color at the center of every cell, or None for cells that are entirely background.
+fn cells(merger: &KnownSizeMerger<Rgb<u8>, Vec<u8>>) -> Vec<Option<u8>> {
+    (0..6)
+        .map(|index| {
+            let cell = merger.get_cell(index).unwrap();
+            let pixel = *merger.get_canvas().get_pixel(cell.x + 2, cell.y + 2);
+            (pixel != WHITE).then_some(pixel[0])
+        })
+        .collect()
+}
+
+#[test]
+fn test_remove_image_respects_padding() {
+    let mut merger = merger();
+    merger.bulk_push(&[&solid(10), &solid(20), &solid(30), &solid(40)]);
+    merger.remove_image(3);
+
+    assert_eq!(
+        cells(&merger),
+        vec![Some(10), Some(20), Some(30), None, None, None]
+    );
+    assert_eq!(merger.get_num_images(), 3);
+    assert!(!merger.is_occupied(3));
+
+    // The padding and margin around the removed cell are untouched.
+    let cell = merger.get_cell(3).unwrap();
+    assert_eq!(*merger.get_canvas().get_pixel(cell.x, cell.y - 1), WHITE);
+    assert_eq!(
+        *merger.get_canvas().get_pixel(cell.x, cell.y - 4),
+        Rgb([10, 10, 10])
+    );
+}
+
+#[test]
+fn test_push_fills_first_free_slot() {
+    let mut merger = merger();
+    merger.bulk_push(&[&solid(10), &solid(20), &solid(30), &solid(40)]);
+    merger.remove_image(1);
+    merger.clear_cell(2, WHITE);
+
+    merger.push(&solid(50));
+    assert_eq!(
+        cells(&merger),
+        vec![Some(10), Some(50), None, Some(40), None, None]
+    );
+
+    merger.bulk_push(&[&solid(60), &solid(70)]);
+    assert_eq!(
+        cells(&merger),
+        vec![Some(10), Some(50), Some(60), Some(40), Some(70), None]
+    );
+    assert!(matches!(
+        merger.try_bulk_push(&[&solid(80), &solid(90)]),
+        Err(MergeError::CanvasFull {
+            remaining: 1,
+            requested: 2
+        })
+    ));
+    assert_eq!(
+        merger
+            .get_placements()
+            .iter()
+            .map(|placement| placement.rect)
+            .collect::<Vec<_>>(),
+        (0..5)
+            .map(|index| merger.get_cell(index).unwrap())
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_replace_image() {
+    let mut merger = merger();
+    merger.set_size_policy(SizePolicy::CenterSmaller);
+    merger.bulk_push(&[&solid(10), &solid(20)]);
+
+    let small: RgbImageBuffer = Image::new_from_pixel(2, 2, Rgb([99, 99, 99]));
+    merger.replace_image(0, &small);
+    merger.replace_image(4, &solid(40));
+
+    let cell = merger.get_cell(0).unwrap();
+    let canvas = merger.get_canvas();
+    assert_eq!(*canvas.get_pixel(cell.x, cell.y), WHITE);
+    assert_eq!(*canvas.get_pixel(cell.x + 1, cell.y + 1), Rgb([99, 99, 99]));
+    assert_eq!(
+        cells(&merger),
+        vec![Some(99), Some(20), None, None, Some(40), None]
+    );
+    assert_eq!(merger.get_num_images(), 3);
+
+    assert!(matches!(
+        merger.try_replace_image(6, &solid(1)),
+        Err(MergeError::CellOutOfBounds {
+            index: 6,
+            capacity: 6
+        })
+    ));
+}
+
+#[test]
+fn test_swap_images() {
+    let mut merger = merger();
+    merger.bulk_push(&[&solid(10), &solid(20), &solid(30)]);
+
+    merger.swap_images(0, 2);
+    merger.swap_images(1, 5);
+    merger.swap_images(3, 3);
+
+    assert_eq!(
+        cells(&merger),
+        vec![Some(30), None, Some(10), None, None, Some(20)]
+    );
+    assert!(merger.is_occupied(5) && !merger.is_occupied(1));
+    assert!(merger.try_swap_images(0, 9).is_err());
+
+    merger.push(&solid(40));
+    assert_eq!(cells(&merger)[1], Some(40));
+}
+
+#[test]
+fn test_insert_at_shifts_to_first_free_slot() {
+    let mut merger = merger();
+    merger.bulk_push(&[&solid(10), &solid(20), &solid(30), &solid(40)]);
+    merger.remove_image(2);
+
+    merger.insert_at(0, &solid(50));
+    assert_eq!(
+        cells(&merger),
+        vec![Some(50), Some(10), Some(20), Some(40), None, None]
+    );
+
+    merger.insert_at(3, &solid(60));
+    assert_eq!(
+        cells(&merger),
+        vec![Some(50), Some(10), Some(20), Some(60), Some(40), None]
+    );
+
+    merger.insert_at(5, &solid(70));
+    assert!(matches!(
+        merger.try_insert_at(0, &solid(80)),
+        Err(MergeError::CanvasFull { .. })
+    ));
+    assert_eq!(merger.get_num_images(), 6);
+}
+
+#[test]
+fn test_unknown_size_insert_at_grows() {
+    let mut merger: UnknownSizeMerger<Rgb<u8>> =
+        UnknownSizeMerger::new((4, 4), 2, Some(Point { x: 1, y: 1 }));
+    merger.bulk_push(&[&solid(10), &solid(20)]);
+
+    merger.insert_at(0, &solid(30));
+    merger.remove_image(1);
+    merger.push(&solid(40));
+
+    let canvas = merger.into_canvas();
+    assert_eq!(canvas.dimensions(), (9, 9));
+    assert_eq!(*canvas.get_pixel(0, 0), Rgb([30, 30, 30]));
+    assert_eq!(*canvas.get_pixel(5, 0), Rgb([40, 40, 40]));
+    assert_eq!(*canvas.get_pixel(0, 5), Rgb([20, 20, 20]));
+}
+
+#[test]
+fn test_unknown_size_shrink_keeps_trailing_images() {
+    let mut merger: UnknownSizeMerger<Rgb<u8>> = UnknownSizeMerger::new((4, 4), 1, None);
+    merger.bulk_push(&[&solid(10), &solid(20), &solid(30)]);
+    merger.remove_image(1);
+
+    let canvas = merger.into_canvas();
+    assert_eq!(canvas.dimensions(), (4, 12));
+    assert_eq!(*canvas.get_pixel(0, 8), Rgb([30, 30, 30]));
+}
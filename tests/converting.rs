@@ -0,0 +1,190 @@
+use image::{buffer::ConvertBuffer, Primitive};
+use image_merger::*;
+
+type RgbaImageBuffer = BufferedImage<Rgba<u8>>;
+
+/// Converts an image up front the way the image crate does, to compare against converting while pasting.
+fn converted<Q, P>(image: &BufferedImage<Q>) -> BufferedImage<P>
+where
+    Q: Pixel,
+    P: Pixel,
+    ImageBuffer<Q, Vec<Q::Subpixel>>: ConvertBuffer<ImageBuffer<P, Vec<P::Subpixel>>>,
+{
+    let buffer: ImageBuffer<P, Vec<P::Subpixel>> = (**image).convert();
+    let (width, height) = buffer.dimensions();
+    Image::new_from_raw(width, height, buffer.into_raw()).unwrap()
+}
+
+fn gradient<Q: Pixel>(width: u32, height: u32, pixel: impl Fn(u32, u32) -> Q) -> BufferedImage<Q> {
+    let mut image = BufferedImage::<Q>::new(width, height);
+    for y in 0..height {
+        for x in 0..width {
+            image.put_pixel(x, y, pixel(x, y));
+        }
+    }
+
+    image
+}
+
+#[test]
+fn test_push_converted_adds_alpha() {
+    let image = gradient(10, 10, |x, y| Rgb([x as u8 * 20, y as u8 * 20, 7]));
+
+    let mut merger: KnownSizeMerger<Rgba<u8>, _> =
+        KnownSizeMerger::new((10, 10), 2, 2, Some(Point { x: 3, y: 0 }));
+    merger.push_converted(&image);
+    merger.push_converted(&image);
+
+    let canvas = merger.into_canvas();
+    assert_eq!(*canvas.get_pixel(4, 5), Rgba([80, 100, 7, 255]));
+    assert_eq!(*canvas.get_pixel(17, 5), Rgba([80, 100, 7, 255]));
+    assert_eq!(*canvas.get_pixel(11, 5), Rgba([0, 0, 0, 0]));
+}
+
+#[test]
+fn test_bulk_push_converted_scales_bit_depth() {
+    let wide = gradient(16, 8, |x, y| Luma([(x * 4000 + y * 257) as u16]));
+    let float = gradient(16, 8, |x, y| {
+        Rgb([x as f32 / 15.0, y as f32 / 7.0, (x + y) as f32 / 22.0])
+    });
+
+    let mut merger: KnownSizeMerger<Rgb<u8>, _> = KnownSizeMerger::new((16, 8), 2, 4, None);
+    merger.bulk_push_converted(&[&wide, &wide]);
+    merger.bulk_push_converted(&[&float, &float]);
+
+    let mut expected: KnownSizeMerger<Rgb<u8>, _> = KnownSizeMerger::new((16, 8), 2, 4, None);
+    let (wide, float) = (converted(&wide), converted(&float));
+    expected.bulk_push(&[&wide, &wide, &float, &float]);
+
+    assert_eq!(**merger.get_canvas(), **expected.get_canvas());
+    assert_eq!(*merger.get_canvas().get_pixel(0, 0), Rgb([0, 0, 0]));
+    assert_eq!(*merger.get_canvas().get_pixel(15, 15), Rgb([255, 255, 255]));
+}
+
+#[test]
+fn test_push_converted_follows_policy_and_composite() {
+    let small = gradient(6, 4, |x, y| {
+        Rgb([x as u16 * 10_000, y as u16 * 20_000, 65_535])
+    });
+    let large = gradient(30, 30, |x, y| Rgb([x as u16 * 2000, y as u16 * 2000, 0]));
+    let composite = Composite::default().with_opacity(0.5);
+
+    for policy in [SizePolicy::CenterSmaller, SizePolicy::ResizeToFit] {
+        let mut merger: KnownSizeMerger<Rgba<u8>, _> = KnownSizeMerger::new((12, 12), 2, 2, None);
+        merger.set_size_policy(policy);
+        merger.set_composite(composite);
+        merger.push_converted(&small);
+        merger.push_converted(&large);
+
+        let mut expected: KnownSizeMerger<Rgba<u8>, _> = KnownSizeMerger::new((12, 12), 2, 2, None);
+        expected.set_size_policy(policy);
+        expected.set_composite(composite);
+        expected.push(&converted(&small));
+        expected.push(&converted(&large));
+
+        assert_eq!(**merger.get_canvas(), **expected.get_canvas());
+    }
+}
+
+#[test]
+fn test_try_bulk_push_converted_canvas_full() {
+    let image = gradient(4, 4, |_, _| Luma([u16::MAX]));
+
+    let mut merger: KnownSizeMerger<Rgba<u8>, _> = KnownSizeMerger::new((4, 4), 2, 2, None);
+    assert!(matches!(
+        merger.try_bulk_push_converted(&[&image, &image, &image]),
+        Err(MergeError::CanvasFull {
+            remaining: 2,
+            requested: 3
+        })
+    ));
+    assert_eq!(merger.get_num_images(), 0);
+    assert_eq!(*merger.get_canvas().get_pixel(0, 0), Rgba([0, 0, 0, 0]));
+}
+
+#[test]
+fn test_unknown_size_push_converted() {
+    let image = gradient(8, 8, |x, _| Rgb([x as u8, 1, 2]));
+
+    let mut merger: UnknownSizeMerger<Rgba<u8>> = UnknownSizeMerger::new((8, 8), 2, None);
+    for _ in 0..5 {
+        merger.push_converted(&image);
+    }
+
+    let canvas = merger.into_canvas();
+    assert_eq!(canvas.dimensions(), (16, 24));
+    assert_eq!(*canvas.get_pixel(5, 17), Rgba([5, 1, 2, 255]));
+}
+
+#[test]
+fn test_packing_bulk_push_converted_matches_bulk_push() {
+    let images: Vec<BufferedImage<LumaA<u16>>> = (0..6)
+        .map(|index| {
+            gradient(10 + index * 7, 40 - index * 5, |x, y| {
+                LumaA([(x * 1000 + y * 500) as u16, 65_535])
+            })
+        })
+        .collect();
+    let expected_images: Vec<RgbaImageBuffer> = images.iter().map(converted).collect();
+
+    let options = PackingOptions {
+        allow_rotation: true,
+        padding: Some(Point { x: 1, y: 1 }),
+        ..PackingOptions::default()
+    };
+
+    let mut merger: PackingMerger<Rgba<u8>> = PackingMerger::new((128, 128), options);
+    merger.bulk_push_converted(&images.iter().collect::<Vec<_>>());
+
+    let mut expected: PackingMerger<Rgba<u8>> = PackingMerger::new((128, 128), options);
+    expected.bulk_push(&expected_images.iter().collect::<Vec<_>>());
+
+    assert_eq!(merger.get_placements(), expected.get_placements());
+    assert_eq!(**merger.get_canvas(), **expected.get_canvas());
+}
+
+/// Checks that converting the whole pixels of a row of subpixels writes the same subpixels as the image crate, whatever the row held before.
+fn assert_converts_like_image<Q, P>(subpixels: &[Q::Subpixel])
+where
+    Q: ConvertPixel<P>,
+    P: Pixel,
+    P::Subpixel: std::fmt::Debug,
+    ImageBuffer<Q, Vec<Q::Subpixel>>: ConvertBuffer<ImageBuffer<P, Vec<P::Subpixel>>>,
+{
+    // Drop any subpixels past the last whole pixel.
+    let width = subpixels.len() / Q::CHANNEL_COUNT as usize;
+    let subpixels = &subpixels[..width * Q::CHANNEL_COUNT as usize];
+    let row = ImageBuffer::<Q, _>::from_raw(width as u32, 1, subpixels.to_vec()).unwrap();
+    let expected: ImageBuffer<P, Vec<P::Subpixel>> = row.convert();
+
+    for fill in [
+        P::Subpixel::DEFAULT_MIN_VALUE,
+        P::Subpixel::DEFAULT_MAX_VALUE,
+    ] {
+        let mut converted = vec![fill; expected.len()];
+        Q::convert_row(subpixels, &mut converted);
+        assert_eq!(converted, expected.as_raw().as_slice());
+    }
+}
+
+#[test]
+fn test_convert_row_matches_image_crate() {
+    let bytes: Vec<u8> = (0..=255).collect();
+    let shorts: Vec<u16> = (0..=u16::MAX).step_by(7).chain([u16::MAX]).collect();
+    let floats = [-0.5, 0.0, 0.001, 0.25, 0.5, 0.999, 1.0, 1.5];
+
+    macro_rules! assert_all {
+        ($subpixels:expr, [$($from:ident),*] => $to:tt) => {
+            $(assert_all!($subpixels, $from => $to);)*
+        };
+        ($subpixels:expr, $from:ident => [$($to:ty),*]) => {
+            $(
+                assert_converts_like_image::<$from<_>, $to>(&$subpixels);
+            )*
+        };
+    }
+
+    assert_all!(bytes, [Luma, LumaA, Rgb, Rgba] => [Luma<u8>, LumaA<u16>, Rgb<f32>, Rgba<u8>, Rgba<u16>]);
+    assert_all!(shorts, [Luma, LumaA, Rgb, Rgba] => [Luma<u8>, LumaA<u8>, Rgb<u8>, Rgba<u16>, Rgba<f32>]);
+    assert_all!(floats, [Rgb, Rgba] => [Luma<u8>, LumaA<f32>, Rgb<u16>, Rgba<u8>]);
+}
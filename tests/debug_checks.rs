@@ -0,0 +1,118 @@
+#![cfg(feature = "debug-checks")]
+
+use image_merger::{
+    raw::{paste, paste_decorated, ImageCell},
+    *,
+};
+
+// The raw pastes in these tests all run on the test's own thread, one after another, so none of them race.
+type RgbImageBuffer = BufferedImage<Rgb<u8>>;
+
+fn test_image(index: u32, width: u32, height: u32) -> RgbImageBuffer {
+    RgbImageBuffer::new_from_pixel(width, height, Rgb([index as u8 * 10, 100, 200]))
+}
+
+#[test]
+fn test_bulk_push_does_not_overlap() {
+    let images: Vec<RgbImageBuffer> = (0..10).map(|index| test_image(index, 16, 16)).collect();
+    let images: Vec<&RgbImageBuffer> = images.iter().collect();
+
+    let mut merger: KnownSizeMerger<Rgb<u8>, _> =
+        KnownSizeMerger::new((16, 16), 4, 12, Some(Point { x: 3, y: 2 }));
+    merger.bulk_push(&images);
+    merger.bulk_push(&images[..2]);
+
+    assert_eq!(merger.get_num_images(), 12);
+    for (index, placement) in merger.get_placements().iter().enumerate() {
+        let rect = placement.rect;
+        assert_eq!(
+            *merger.get_canvas().get_pixel(rect.x, rect.y),
+            Rgb([(index % 10) as u8 * 10, 100, 200])
+        );
+    }
+}
+
+#[test]
+fn test_sequential_overlapping_pastes_are_allowed() {
+    let cell = ImageCell::new(RgbImageBuffer::new(32, 32));
+    unsafe {
+        paste(&cell, &test_image(1, 16, 16), Point { x: 0, y: 0 });
+    }
+    unsafe {
+        paste(&cell, &test_image(2, 16, 16), Point { x: 8, y: 8 });
+    }
+
+    let canvas = cell.into_inner();
+    assert_eq!(*canvas.get_pixel(8, 8), Rgb([20, 100, 200]));
+}
+
+#[test]
+#[should_panic(expected = "overlapping writes")]
+fn test_overlapping_pastes_in_batch() {
+    let cell = ImageCell::new(RgbImageBuffer::new(32, 32));
+    let _batch = cell.batch();
+    unsafe {
+        paste(&cell, &test_image(1, 16, 16), Point { x: 0, y: 0 });
+    }
+    unsafe {
+        paste(&cell, &test_image(2, 16, 16), Point { x: 15, y: 15 });
+    }
+}
+
+#[test]
+#[should_panic(expected = "overlapping writes")]
+fn test_paste_over_handout() {
+    let cell = ImageCell::new(RgbImageBuffer::new(32, 32));
+    let _handout = unsafe { cell.request_handout(4, 4) };
+    unsafe {
+        paste(&cell, &test_image(1, 8, 8), Point { x: 0, y: 0 });
+    }
+}
+
+#[test]
+fn test_paste_is_clipped() {
+    // Only the part of the image that lies on the canvas is claimed, so a clipped paste is not reported.
+    let cell = ImageCell::new(RgbImageBuffer::new(32, 32));
+    unsafe {
+        paste(&cell, &test_image(1, 16, 16), Point { x: 20, y: 0 });
+    }
+
+    let canvas = cell.into_inner();
+    assert_eq!(*canvas.get_pixel(31, 15), Rgb([10, 100, 200]));
+    assert_eq!(*canvas.get_pixel(19, 0), Rgb([0, 0, 0]));
+}
+
+#[test]
+fn test_decorated_paste_is_clipped() {
+    // The border of a cell on the edge of the canvas reaches past it, which is clipped rather than reported.
+    let cell = ImageCell::new(RgbImageBuffer::new(20, 20));
+    let decoration =
+        CellDecoration::default().with_border(2, Rgb([0, 0, 0]), BorderPlacement::Outside);
+    let area = Rect {
+        x: 6,
+        y: 6,
+        width: 16,
+        height: 16,
+    };
+    unsafe {
+        paste_decorated(
+            &cell,
+            &test_image(1, 16, 16),
+            Point { x: 6, y: 6 },
+            area,
+            &Composite::default(),
+            &decoration,
+        );
+    }
+
+    let canvas = cell.into_inner();
+    assert_eq!(*canvas.get_pixel(4, 4), Rgb([0, 0, 0]));
+    assert_eq!(*canvas.get_pixel(19, 19), Rgb([10, 100, 200]));
+}
+
+#[test]
+#[should_panic(expected = "out of bounds")]
+fn test_handout_out_of_bounds() {
+    let cell = ImageCell::new(RgbImageBuffer::new(32, 32));
+    let _handout = unsafe { cell.request_handout(32, 0) };
+}
@@ -0,0 +1,182 @@
+use image::buffer::ConvertBuffer;
+use image_merger::*;
+
+const RED: Rgb<u8> = Rgb([255, 0, 0]);
+const BLACK: Rgb<u8> = Rgb([0, 0, 0]);
+const WHITE: Rgb<u8> = Rgb([255, 255, 255]);
+
+fn merger(decoration: CellDecoration<Rgb<u8>>) -> KnownSizeMerger<Rgb<u8>, Vec<u8>> {
+    KnownSizeMerger::builder((10, 10))
+        .with_images_per_row(2)
+        .with_total_images(4)
+        .with_padding(Point { x: 4, y: 6 })
+        .with_margin(2)
+        .with_background(WHITE)
+        .with_decoration(decoration)
+        .build()
+}
+
+fn red() -> BufferedImage<Rgb<u8>> {
+    BufferedImage::new_from_pixel(10, 10, RED)
+}
+
+#[test]
+fn test_inside_border_covers_image_edges() {
+    let mut merger =
+        merger(CellDecoration::default().with_border(2, BLACK, BorderPlacement::Inside));
+    merger.push(&red());
+
+    let canvas = merger.get_canvas();
+    assert_eq!(*canvas.get_pixel(1, 1), WHITE);
+    assert_eq!(*canvas.get_pixel(2, 2), BLACK);
+    assert_eq!(*canvas.get_pixel(3, 7), BLACK);
+    assert_eq!(*canvas.get_pixel(4, 4), RED);
+    assert_eq!(*canvas.get_pixel(9, 9), RED);
+    assert_eq!(*canvas.get_pixel(10, 9), BLACK);
+}
+
+#[test]
+fn test_outside_border_fills_gutters() {
+    let mut merger =
+        merger(CellDecoration::default().with_border(2, BLACK, BorderPlacement::Outside));
+    merger.bulk_push(&[&red(), &red()]);
+
+    let canvas = merger.get_canvas();
+    assert_eq!(*canvas.get_pixel(0, 0), BLACK);
+    assert_eq!(*canvas.get_pixel(2, 2), RED);
+    assert_eq!(*canvas.get_pixel(11, 11), RED);
+    for x in 12..16 {
+        assert_eq!(*canvas.get_pixel(x, 5), BLACK);
+    }
+
+    // The gutter below the first row is only half covered, since the second row is still empty.
+    assert_eq!(*canvas.get_pixel(5, 13), BLACK);
+    assert_eq!(*canvas.get_pixel(5, 14), WHITE);
+}
+
+#[test]
+fn test_rounded_corners_are_anti_aliased() {
+    let mut merger = merger(CellDecoration::default().with_corner_radius(4));
+    merger.push(&red());
+
+    let canvas = merger.get_canvas();
+    assert_eq!(*canvas.get_pixel(2, 2), WHITE);
+    assert_eq!(*canvas.get_pixel(11, 11), WHITE);
+    assert_eq!(*canvas.get_pixel(6, 2), RED);
+    assert_eq!(*canvas.get_pixel(4, 4), RED);
+
+    // The edge of the corner is mixed between the image and the background beneath it.
+    let Rgb([r, g, b]) = *canvas.get_pixel(3, 3);
+    assert_eq!(r, 255);
+    assert!(g > 0 && g < 255);
+    assert_eq!(g, b);
+}
+
+#[test]
+fn test_bulk_push_matches_push() {
+    let decoration = CellDecoration::default()
+        .with_border(1, BLACK, BorderPlacement::Outside)
+        .with_corner_radius(3);
+    let images: Vec<BufferedImage<Rgb<u8>>> = (0..4)
+        .map(|index| BufferedImage::new_from_pixel(10, 10, Rgb([index * 60, 100, 0])))
+        .collect();
+
+    let mut merger = merger(decoration);
+    merger.bulk_push(&images.iter().collect::<Vec<_>>());
+
+    let mut expected = self::merger(decoration);
+    for image in &images {
+        expected.push(image);
+    }
+
+    assert_eq!(**merger.get_canvas(), **expected.get_canvas());
+}
+
+#[test]
+fn test_remove_image_clears_outside_border() {
+    let decoration = CellDecoration::default()
+        .with_border(2, BLACK, BorderPlacement::Outside)
+        .with_corner_radius(5);
+    let fresh = merger(decoration);
+
+    let mut merger = merger(decoration);
+    merger.bulk_push(&[&red(), &red(), &red()]);
+    merger.remove_image(1);
+    merger.remove_image(0);
+    merger.remove_image(2);
+
+    assert_eq!(**merger.get_canvas(), **fresh.get_canvas());
+}
+
+#[test]
+fn test_push_converted_matches_push() {
+    let decoration = CellDecoration::default()
+        .with_border(1, BLACK, BorderPlacement::Inside)
+        .with_corner_radius(4);
+    let image: BufferedImage<Rgba<u16>> =
+        BufferedImage::new_from_pixel(10, 10, Rgba([65_535, 30_000, 0, 65_535]));
+    let converted: ImageBuffer<Rgb<u8>, Vec<u8>> = (*image).convert();
+
+    let mut merger = merger(decoration);
+    merger.push_converted(&image);
+
+    let mut expected = self::merger(decoration);
+    expected.push(&converted);
+
+    assert_eq!(**merger.get_canvas(), **expected.get_canvas());
+}
+
+#[test]
+fn test_outside_border_must_fit_between_cells() {
+    let decoration = CellDecoration::default().with_border(3, BLACK, BorderPlacement::Outside);
+    let builder = KnownSizeMerger::<Rgb<u8>, _>::builder((10, 10))
+        .with_images_per_row(2)
+        .with_total_images(2)
+        .with_margin(3)
+        .with_decoration(decoration);
+
+    assert!(matches!(
+        builder
+            .clone()
+            .with_padding(Point { x: 5, y: 0 })
+            .try_build(),
+        Err(MergeError::InvalidLayout(_))
+    ));
+
+    // A single row has no gutter between rows to fit.
+    assert!(builder
+        .with_padding(Point { x: 6, y: 0 })
+        .try_build()
+        .is_ok());
+}
+
+#[test]
+fn test_swap_and_insert_move_outside_border() {
+    const BLUE: Rgb<u8> = Rgb([0, 0, 255]);
+    const GREEN: Rgb<u8> = Rgb([0, 255, 0]);
+    let solid = |color| BufferedImage::new_from_pixel(10, 10, color);
+    let (red, blue, green) = (solid(RED), solid(BLUE), solid(GREEN));
+
+    // The borders of neighbouring columns share the gutter between them, while the rows are apart.
+    let decoration = CellDecoration::default().with_border(2, BLACK, BorderPlacement::Outside);
+    let mut expected = merger(decoration);
+    expected.bulk_push(&[&green, &red, &blue]);
+
+    let mut swapped = merger(decoration);
+    swapped.bulk_push(&[&red, &blue, &green]);
+    swapped.swap_images(0, 1);
+    swapped.swap_images(0, 2);
+    assert_eq!(**swapped.get_canvas(), **expected.get_canvas());
+
+    let mut inserted = merger(decoration);
+    inserted.bulk_push(&[&red, &blue]);
+    inserted.insert_at(0, &green);
+    assert_eq!(**inserted.get_canvas(), **expected.get_canvas());
+
+    // Swapping with an empty slot leaves no border behind, even where it was shared with a neighbour.
+    let mut expected = merger(decoration);
+    expected.bulk_push(&[&red, &red, &blue, &green]);
+    expected.remove_image(0);
+    swapped.swap_images(0, 3);
+    assert_eq!(**swapped.get_canvas(), **expected.get_canvas());
+}
@@ -38,7 +38,7 @@ fn merge_images_slow(
     padding_y: u32,
 ) -> RgbaImageBuffer {
     // Cieling division for total rows.
-    let total_rows = (total_images + images_per_row - 1) / images_per_row;
+    let total_rows = total_images.div_ceil(images_per_row);
 
     let test_square = generate_test_square();
 
@@ -257,3 +257,292 @@ fn test_resizable_bulk_known_size_merger() {
     merger.bulk_push_resized(&vec![&Image::from(test_square); TOTAL_IMAGES as usize]);
     assert!(merger.get_num_images() == TOTAL_IMAGES);
 }
+
+#[test]
+fn test_try_push_canvas_full() {
+    let test_square = generate_test_square();
+
+    let mut merger: KnownSizeMerger<Rgba<u8>, _> =
+        KnownSizeMerger::new((IMAGE_WIDTH, IMAGE_HEIGHT), 2, 2, None);
+
+    assert!(merger.try_bulk_push(&[&test_square, &test_square]).is_ok());
+    assert!(matches!(
+        merger.try_push(&test_square),
+        Err(MergeError::CanvasFull {
+            remaining: 0,
+            requested: 1
+        })
+    ));
+    assert_eq!(merger.get_num_images(), 2);
+}
+
+#[test]
+fn test_try_bulk_push_canvas_full() {
+    let test_square = generate_test_square();
+
+    let mut merger: KnownSizeMerger<Rgba<u8>, _> = KnownSizeMerger::new(
+        (IMAGE_WIDTH, IMAGE_HEIGHT),
+        IMAGES_PER_ROW,
+        TOTAL_IMAGES,
+        None,
+    );
+
+    let result = merger.try_bulk_push(&vec![&test_square; TOTAL_IMAGES as usize + 1]);
+    assert!(matches!(
+        result,
+        Err(MergeError::CanvasFull {
+            remaining: 100,
+            requested: 101
+        })
+    ));
+    assert_eq!(merger.get_num_images(), 0);
+}
+
+#[test]
+fn test_try_push_dimension_mismatch() {
+    let mut merger: KnownSizeMerger<Rgba<u8>, _> = KnownSizeMerger::new(
+        (IMAGE_WIDTH, IMAGE_HEIGHT),
+        IMAGES_PER_ROW,
+        TOTAL_IMAGES,
+        None,
+    );
+
+    let too_large = RgbaImageBuffer::new(IMAGE_WIDTH + 1, IMAGE_HEIGHT);
+    assert!(matches!(
+        merger.try_push(&too_large),
+        Err(MergeError::DimensionMismatch {
+            expected: (100, 100),
+            actual: (101, 100)
+        })
+    ));
+    assert_eq!(merger.get_num_images(), 0);
+}
+
+#[test]
+fn test_try_new_invalid_layout() {
+    let no_rows = KnownSizeMerger::<Rgba<u8>, _>::try_new((IMAGE_WIDTH, IMAGE_HEIGHT), 0, 10, None);
+    assert!(matches!(no_rows, Err(MergeError::InvalidLayout(_))));
+
+    let no_images =
+        KnownSizeMerger::<Rgba<u8>, _>::try_new((IMAGE_WIDTH, IMAGE_HEIGHT), 10, 0, None);
+    assert!(matches!(no_images, Err(MergeError::InvalidLayout(_))));
+
+    let too_large = KnownSizeMerger::<Rgba<u8>, _>::try_new((u32::MAX, IMAGE_HEIGHT), 2, 10, None);
+    assert!(matches!(too_large, Err(MergeError::CanvasTooLarge)));
+}
+
+#[test]
+fn test_try_new_from_raw_buffer_too_small() {
+    let merger: Result<KnownSizeMerger<Rgb<u8>, Vec<u8>>, _> = KnownSizeMerger::try_new_from_raw(
+        (IMAGE_WIDTH, IMAGE_HEIGHT),
+        IMAGES_PER_ROW,
+        TOTAL_IMAGES,
+        None,
+        vec![0; 10],
+    );
+
+    assert!(matches!(
+        merger,
+        Err(MergeError::BufferTooSmall { actual: 10, .. })
+    ));
+}
+
+#[test]
+fn test_size_policy_clip() {
+    let mut merger: KnownSizeMerger<Rgba<u8>, _> =
+        KnownSizeMerger::new((IMAGE_WIDTH, IMAGE_HEIGHT), 2, 2, None);
+    merger.set_size_policy(SizePolicy::Clip);
+
+    // An oversized image must not bleed into the neighbouring cell.
+    let oversized =
+        RgbaImageBuffer::new_from_pixel(IMAGE_WIDTH * 2, IMAGE_HEIGHT, Rgba([255, 0, 0, 255]));
+    merger.push(&oversized);
+
+    let canvas = merger.get_canvas();
+    assert_eq!(
+        canvas.get_pixel(IMAGE_WIDTH - 1, 0),
+        &Rgba([255, 0, 0, 255])
+    );
+    assert_eq!(canvas.get_pixel(IMAGE_WIDTH, 0), &Rgba([0, 0, 0, 0]));
+}
+
+#[test]
+fn test_size_policy_center_smaller() {
+    let mut merger: KnownSizeMerger<Rgba<u8>, _> =
+        KnownSizeMerger::new((IMAGE_WIDTH, IMAGE_HEIGHT), 2, 2, None);
+    merger.set_size_policy(SizePolicy::CenterSmaller);
+
+    let undersized =
+        RgbaImageBuffer::new_from_pixel(IMAGE_WIDTH / 2, IMAGE_HEIGHT / 2, Rgba([0, 255, 0, 255]));
+    merger.bulk_push(&[&undersized, &undersized]);
+
+    let canvas = merger.get_canvas();
+    let (quarter_x, quarter_y) = (IMAGE_WIDTH / 4, IMAGE_HEIGHT / 4);
+    assert_eq!(
+        canvas.get_pixel(quarter_x - 1, quarter_y),
+        &Rgba([0, 0, 0, 0])
+    );
+    assert_eq!(
+        canvas.get_pixel(quarter_x, quarter_y),
+        &Rgba([0, 255, 0, 255])
+    );
+    assert_eq!(
+        canvas.get_pixel(IMAGE_WIDTH + quarter_x * 3 - 1, quarter_y * 3 - 1),
+        &Rgba([0, 255, 0, 255])
+    );
+    assert_eq!(
+        canvas.get_pixel(IMAGE_WIDTH + quarter_x * 3, quarter_y * 3),
+        &Rgba([0, 0, 0, 0])
+    );
+}
+
+#[test]
+fn test_size_policy_resize_to_fit() {
+    let test_square = generate_test_square();
+    let slow_merge = merge_images_slow(IMAGES_PER_ROW, TOTAL_IMAGES, 0, 0);
+
+    let mut merger: KnownSizeMerger<Rgba<u8>, _> = KnownSizeMerger::new(
+        (IMAGE_WIDTH, IMAGE_HEIGHT),
+        IMAGES_PER_ROW,
+        TOTAL_IMAGES,
+        None,
+    );
+    merger.set_size_policy(SizePolicy::ResizeToFit);
+
+    let upscaled = Image::from(image::imageops::resize(
+        &*test_square,
+        IMAGE_WIDTH * 2,
+        IMAGE_HEIGHT * 2,
+        image::imageops::FilterType::Nearest,
+    ));
+    merger.bulk_push(&vec![&upscaled; TOTAL_IMAGES as usize]);
+
+    assert_eq!(merger.get_canvas(), &slow_merge);
+}
+
+#[test]
+fn test_source_over_composite() {
+    // Start from an opaque white canvas so the pushed sticker has something to blend with.
+    let container = vec![255u8; (IMAGE_WIDTH * IMAGE_HEIGHT * 4 * 2) as usize];
+    let mut merger: KnownSizeMerger<Rgba<u8>, _> =
+        KnownSizeMerger::new_from_raw((IMAGE_WIDTH, IMAGE_HEIGHT), 2, 2, None, container).unwrap();
+    merger.set_composite(Composite::new(CompositeOp::SourceOver));
+
+    let mut sticker = vec![0u8; (IMAGE_WIDTH * IMAGE_HEIGHT * 4) as usize];
+    sticker[..4].copy_from_slice(&[255, 0, 0, 255]);
+    let sticker: Image<Rgba<u8>, _> =
+        Image::new_from_raw(IMAGE_WIDTH, IMAGE_HEIGHT, sticker).unwrap();
+    merger.bulk_push(&[&sticker, &sticker]);
+
+    let canvas = merger.get_canvas();
+    assert_eq!(canvas.get_pixel(0, 0), &Rgba([255, 0, 0, 255]));
+    assert_eq!(canvas.get_pixel(1, 0), &Rgba([255, 255, 255, 255]));
+    assert_eq!(canvas.get_pixel(IMAGE_WIDTH, 0), &Rgba([255, 0, 0, 255]));
+    assert_eq!(
+        canvas.get_pixel(IMAGE_WIDTH + 1, 1),
+        &Rgba([255, 255, 255, 255])
+    );
+}
+
+#[test]
+fn test_multiply_blend_onto_filled_canvas() {
+    let canvas: BufferedImage<Rgb<u8>> =
+        Image::new_from_pixel(IMAGE_WIDTH, IMAGE_HEIGHT, Rgb([200, 100, 50]));
+    let canvas = raw::ImageCell::new(canvas);
+
+    let layer: BufferedImage<Rgb<u8>> =
+        Image::new_from_pixel(IMAGE_WIDTH / 2, IMAGE_HEIGHT, Rgb([255, 128, 0]));
+    // Safety: the canvas is only pasted onto once, on this thread.
+    unsafe {
+        raw::paste_composite(
+            &canvas,
+            &layer,
+            Point { x: 0, y: 0 },
+            &Composite::blend(BlendMode::Multiply),
+        )
+    };
+
+    let canvas = canvas.into_inner();
+    assert_eq!(canvas.get_pixel(0, 0), &Rgb([200, 50, 0]));
+    assert_eq!(canvas.get_pixel(IMAGE_WIDTH / 2, 0), &Rgb([200, 100, 50]));
+}
+
+#[test]
+fn test_resizable_known_size_merger_filter() {
+    let mut merger: KnownSizeMerger<Rgba<u8>, _> =
+        KnownSizeMerger::new((IMAGE_WIDTH, IMAGE_HEIGHT), 2, 2, None);
+    merger.set_resize_filter(ResizeFilter::Lanczos3);
+    assert_eq!(merger.get_resize_filter(), ResizeFilter::Lanczos3);
+
+    let large = generate_test_square();
+    let large = Image::from(image::imageops::resize(
+        &*large,
+        IMAGE_WIDTH * 10,
+        IMAGE_HEIGHT * 10,
+        image::imageops::FilterType::Triangle,
+    ));
+    merger.push_resized(&large);
+
+    let expected = image::imageops::resize(
+        &*large,
+        IMAGE_WIDTH,
+        IMAGE_HEIGHT,
+        image::imageops::FilterType::Lanczos3,
+    );
+    let canvas = merger.get_canvas();
+    for (x, y, pixel) in expected.enumerate_pixels() {
+        let actual = canvas.get_pixel(x, y);
+        for (a, b) in actual.0.iter().zip(pixel.0.iter()) {
+            assert!(a.abs_diff(*b) <= 3, "{:?} != {:?}", actual, pixel);
+        }
+    }
+}
+
+#[test]
+fn test_fit_mode_contain() {
+    let mut merger: KnownSizeMerger<Rgb<u8>, _> =
+        KnownSizeMerger::new((IMAGE_WIDTH, IMAGE_HEIGHT), 1, 1, None);
+    merger.set_fit_mode(FitMode::Contain {
+        fill: Rgb([255, 255, 255]),
+        alignment: Alignment::Center,
+    });
+
+    // A landscape image should be letterboxed above and below.
+    let landscape: BufferedImage<Rgb<u8>> =
+        Image::new_from_pixel(IMAGE_WIDTH * 4, IMAGE_HEIGHT * 2, Rgb([255, 0, 0]));
+    merger.push_resized(&landscape);
+
+    let canvas = merger.get_canvas();
+    let band = IMAGE_HEIGHT / 4;
+    assert_eq!(canvas.get_pixel(0, band - 1), &Rgb([255, 255, 255]));
+    assert_eq!(canvas.get_pixel(0, band), &Rgb([255, 0, 0]));
+    assert_eq!(
+        canvas.get_pixel(IMAGE_WIDTH - 1, IMAGE_HEIGHT - band - 1),
+        &Rgb([255, 0, 0])
+    );
+    assert_eq!(
+        canvas.get_pixel(IMAGE_WIDTH - 1, IMAGE_HEIGHT - band),
+        &Rgb([255, 255, 255])
+    );
+}
+
+#[test]
+fn test_fit_mode_cover() {
+    let mut merger: KnownSizeMerger<Rgb<u8>, _> =
+        KnownSizeMerger::new((IMAGE_WIDTH, IMAGE_HEIGHT), 1, 1, None);
+    merger.set_fit_mode(FitMode::Cover {
+        gravity: Alignment::Left,
+    });
+
+    // A landscape image whose left half is red and right half is blue; only the left half should remain.
+    let mut landscape: BufferedImage<Rgb<u8>> =
+        Image::new_from_pixel(IMAGE_WIDTH * 2, IMAGE_HEIGHT, Rgb([0, 0, 255]));
+    for x in 0..IMAGE_WIDTH {
+        for y in 0..IMAGE_HEIGHT {
+            landscape.put_pixel(x, y, Rgb([255, 0, 0]));
+        }
+    }
+    merger.push_resized(&landscape);
+
+    assert!(merger.get_canvas().pixels().all(|p| *p == Rgb([255, 0, 0])));
+}
@@ -0,0 +1,200 @@
+use image_merger::*;
+
+/// Generates a solid image of the given size, colored by its index so images can be told apart on the canvas.
+fn generate_test_image(width: u32, height: u32, index: u8) -> BufferedImage<Rgba<u8>> {
+    BufferedImage::new_from_pixel(width, height, Rgba([index, 255 - index, index / 2, 255]))
+}
+
+fn generate_test_images() -> Vec<BufferedImage<Rgba<u8>>> {
+    (0..40u32)
+        .map(|i| generate_test_image(8 + (i * 13) % 41, 5 + (i * 7) % 29, i as u8))
+        .collect()
+}
+
+fn overlaps(a: &Rect, b: &Rect) -> bool {
+    a.x < b.x + b.width && b.x < a.x + a.width && a.y < b.y + b.height && b.y < a.y + a.height
+}
+
+/// Checks that every placement is inside the canvas, does not overlap any other placement (including padding), and that
+/// the canvas holds each image at its placement.
+fn assert_packed(
+    merger: &PackingMerger<Rgba<u8>>,
+    images: &[&BufferedImage<Rgba<u8>>],
+    padding: Padding,
+) {
+    let canvas = merger.get_canvas();
+    let placements = merger.get_placements();
+    assert_eq!(placements.len(), images.len());
+
+    for (index, (placement, image)) in placements.iter().zip(images).enumerate() {
+        let rect = placement.rect;
+        assert!(rect.x + rect.width <= canvas.width());
+        assert!(rect.y + rect.height <= canvas.height());
+
+        if placement.rotated {
+            assert_eq!((rect.width, rect.height), (image.height(), image.width()));
+        } else {
+            assert_eq!((rect.width, rect.height), image.dimensions());
+        }
+
+        let padded = |rect: Rect| Rect {
+            width: rect.width + padding.x,
+            height: rect.height + padding.y,
+            ..rect
+        };
+        for other in &placements[index + 1..] {
+            assert!(
+                !overlaps(&padded(rect), &padded(other.rect)),
+                "{:?} overlaps {:?}",
+                placement,
+                other
+            );
+        }
+
+        let expected = image.get_pixel(0, 0);
+        for y in rect.y..rect.y + rect.height {
+            for x in rect.x..rect.x + rect.width {
+                assert_eq!(canvas.get_pixel(x, y), expected);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_pack_every_algorithm() {
+    let images = generate_test_images();
+    let refs: Vec<&BufferedImage<Rgba<u8>>> = images.iter().collect();
+    let area: u32 = images
+        .iter()
+        .map(|image| image.width() * image.height())
+        .sum();
+
+    for algorithm in [
+        PackingAlgorithm::MaxRects,
+        PackingAlgorithm::Skyline,
+        PackingAlgorithm::Guillotine,
+    ] {
+        for allow_rotation in [false, true] {
+            let options = PackingOptions {
+                algorithm,
+                padding: None,
+                allow_rotation,
+            };
+
+            let merger = PackingMerger::pack(&refs, options);
+            assert_packed(&merger, &refs, Point { x: 0, y: 0 });
+
+            // The canvas should be reasonably compact.
+            let canvas = merger.get_canvas();
+            assert!(canvas.width() * canvas.height() < area * 2);
+        }
+    }
+}
+
+#[test]
+fn test_pack_with_padding() {
+    let images = generate_test_images();
+    let refs: Vec<&BufferedImage<Rgba<u8>>> = images.iter().collect();
+    let padding = Point { x: 3, y: 5 };
+
+    let merger = PackingMerger::pack(
+        &refs,
+        PackingOptions {
+            padding: Some(padding),
+            allow_rotation: true,
+            ..Default::default()
+        },
+    );
+    assert_packed(&merger, &refs, padding);
+}
+
+#[test]
+fn test_pack_rotates_to_fit() {
+    let image = generate_test_image(30, 10, 1);
+    let mut merger: PackingMerger<Rgba<u8>> = PackingMerger::new(
+        (10, 30),
+        PackingOptions {
+            allow_rotation: true,
+            ..Default::default()
+        },
+    );
+
+    merger.push(&image);
+
+    let placement = merger.get_placements()[0];
+    assert!(placement.rotated);
+    assert_eq!(
+        placement.rect,
+        Rect {
+            x: 0,
+            y: 0,
+            width: 10,
+            height: 30
+        }
+    );
+}
+
+#[test]
+fn test_try_push_canvas_full() {
+    let image = generate_test_image(10, 10, 1);
+    let mut merger: PackingMerger<Rgba<u8>> =
+        PackingMerger::new((20, 10), PackingOptions::default());
+
+    merger.push(&image);
+    merger.push(&image);
+    assert!(matches!(
+        merger.try_push(&image),
+        Err(MergeError::CanvasFull { .. })
+    ));
+}
+
+#[test]
+fn test_try_bulk_push_canvas_full() {
+    let small = generate_test_image(10, 10, 1);
+    let large = generate_test_image(20, 20, 2);
+    let mut merger: PackingMerger<Rgba<u8>> =
+        PackingMerger::new((30, 20), PackingOptions::default());
+
+    // The large image and two small images fit, the other two small images do not.
+    assert!(matches!(
+        merger.try_bulk_push(&[&small, &large, &small, &small, &small]),
+        Err(MergeError::CanvasFull {
+            remaining: 3,
+            requested: 5
+        })
+    ));
+
+    // A failed bulk push leaves the merger untouched.
+    assert_eq!(merger.get_num_images(), 0);
+    merger.bulk_push(&[&small, &large, &small]);
+    assert_packed(&merger, &[&small, &large, &small], Point { x: 0, y: 0 });
+}
+
+#[test]
+fn test_push_after_pack() {
+    let images = [
+        generate_test_image(40, 40, 1),
+        generate_test_image(20, 20, 2),
+    ];
+    let refs: Vec<&BufferedImage<Rgba<u8>>> = images.iter().collect();
+
+    let mut merger = PackingMerger::pack(&refs, PackingOptions::default());
+    let (width, height) = merger.get_canvas().dimensions();
+
+    // Whatever is pushed after packing has to land in the leftover space of the packed canvas.
+    let small = generate_test_image(5, 5, 3);
+    while merger.try_push(&small).is_ok() {}
+
+    assert_eq!(merger.get_canvas().dimensions(), (width, height));
+    let mut expected = refs.clone();
+    expected.resize(merger.get_placements().len(), &small);
+    assert_packed(&merger, &expected, Point { x: 0, y: 0 });
+}
+
+#[test]
+fn test_try_pack_no_images() {
+    assert!(matches!(
+        PackingMerger::<Rgba<u8>>::try_pack(&[], PackingOptions::default()),
+        Err(MergeError::InvalidLayout(_))
+    ));
+}
@@ -0,0 +1,121 @@
+use image_merger::*;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+const IMAGE_SIZE: u32 = 16;
+
+/// Creates an empty directory for a test to write its images to.
+fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("image-merger-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn test_color(index: u8) -> Rgb<u8> {
+    Rgb([index * 20, 100, 255 - index * 20])
+}
+
+/// Writes a solid RGB image, colored by its index, in the given format.
+fn write_test_image(dir: &Path, name: &str, index: u8, format: image::ImageFormat) -> PathBuf {
+    let path = dir.join(name);
+    let image = image::RgbImage::from_pixel(IMAGE_SIZE, IMAGE_SIZE, test_color(index));
+    image.save_with_format(&path, format).unwrap();
+    path
+}
+
+fn assert_cell_color(canvas: &BufferedImage<Rgba<u8>>, cell: Rect, color: Rgb<u8>) {
+    let Rgb([r, g, b]) = color;
+    assert_eq!(*canvas.get_pixel(cell.x, cell.y), Rgba([r, g, b, 255]));
+    assert_eq!(
+        *canvas.get_pixel(cell.x + cell.width - 1, cell.y + cell.height - 1),
+        Rgba([r, g, b, 255])
+    );
+}
+
+#[test]
+fn test_bulk_push_paths_converts_and_sniffs() {
+    let dir = test_dir("bulk-push-paths");
+    let paths = [
+        write_test_image(&dir, "a.png", 0, image::ImageFormat::Png),
+        write_test_image(&dir, "b.bmp", 1, image::ImageFormat::Bmp),
+        // The format is sniffed from the contents, not the misleading extension.
+        write_test_image(&dir, "c.jpg", 2, image::ImageFormat::Png),
+    ];
+
+    let mut merger: KnownSizeMerger<Rgba<u8>, _> =
+        KnownSizeMerger::new((IMAGE_SIZE, IMAGE_SIZE), 2, 3, None);
+    merger.bulk_push_paths(&paths);
+
+    assert_eq!(merger.get_num_images(), 3);
+    for (index, cell) in merger.get_placements().iter().enumerate() {
+        assert_cell_color(merger.get_canvas(), cell.rect, test_color(index as u8));
+    }
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_from_dir_natural_order() {
+    let dir = test_dir("from-dir");
+    for index in [10u8, 2, 1] {
+        write_test_image(
+            &dir,
+            &format!("img{}.png", index),
+            index,
+            image::ImageFormat::Png,
+        );
+    }
+    fs::write(dir.join("notes.txt"), "not an image").unwrap();
+
+    assert_eq!(
+        image_paths(&dir, SortOrder::Name)
+            .unwrap()
+            .iter()
+            .map(|path| path.file_name().unwrap().to_str().unwrap())
+            .collect::<Vec<_>>(),
+        ["img1.png", "img10.png", "img2.png"]
+    );
+
+    let merger: UnknownSizeMerger<Rgba<u8>> =
+        UnknownSizeMerger::from_dir(&dir, 2, Some(Point { x: 1, y: 1 }), SortOrder::Natural)
+            .unwrap();
+    assert_eq!(merger.get_num_images(), 3);
+
+    let placements = merger.get_placements();
+    let canvas = merger.into_canvas();
+    for (placement, index) in placements.iter().zip([1u8, 2, 10]) {
+        assert_cell_color(&canvas, placement.rect, test_color(index));
+    }
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_try_push_path_errors() {
+    let dir = test_dir("push-path-errors");
+    let garbage = dir.join("garbage.png");
+    fs::write(&garbage, "not an image").unwrap();
+
+    let mut merger: KnownSizeMerger<Rgba<u8>, _> =
+        KnownSizeMerger::new((IMAGE_SIZE, IMAGE_SIZE), 2, 2, None);
+
+    for path in [garbage.clone(), dir.join("missing.png")] {
+        match merger.try_push_path(&path) {
+            Err(MergeError::LoadError {
+                path: error_path, ..
+            }) => assert_eq!(error_path, path),
+            other => panic!("expected a load error, got {:?}", other),
+        }
+    }
+
+    assert!(matches!(
+        KnownSizeMerger::<Rgba<u8>, _>::from_dir(&dir, 2, None, SortOrder::Name),
+        Err(MergeError::LoadError { .. })
+    ));
+    assert_eq!(merger.get_num_images(), 0);
+
+    fs::remove_dir_all(dir).unwrap();
+}
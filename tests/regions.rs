@@ -0,0 +1,97 @@
+use image_merger::{raw::ImageCell, *};
+use rayon::prelude::*;
+
+fn rect(x: u32, y: u32, width: u32, height: u32) -> Rect {
+    Rect {
+        x,
+        y,
+        width,
+        height,
+    }
+}
+
+#[test]
+fn test_regions_write_in_parallel() {
+    let mut cell = ImageCell::new(Image::<Rgba<u8>, _>::new(64, 48));
+    let rects: Vec<Rect> = (0..12)
+        .map(|index| rect((index % 4) * 16, (index / 4) * 16, 16, 16))
+        .collect();
+
+    let tile: BufferedImage<Rgba<u8>> = Image::new_from_pixel(8, 8, Rgba([9, 9, 9, 9]));
+    let mut regions = cell.regions_mut(&rects).unwrap();
+    regions
+        .par_iter_mut()
+        .enumerate()
+        .for_each(|(index, region)| {
+            region.fill(Rgba([index as u8, 0, 0, 255]));
+            region.put_pixel(15, 15, Rgba([255, 255, 255, 255]));
+            // Hangs off the bottom right corner of the region, and is clipped to it.
+            region.paste(&tile, Point { x: 12, y: 12 });
+        });
+    drop(regions);
+
+    let image = cell.into_inner();
+    for index in 0..12u32 {
+        let (x, y) = ((index % 4) * 16, (index / 4) * 16);
+        assert_eq!(image.get_pixel(x, y), &Rgba([index as u8, 0, 0, 255]));
+        assert_eq!(
+            image.get_pixel(x + 11, y + 15),
+            &Rgba([index as u8, 0, 0, 255])
+        );
+        assert_eq!(image.get_pixel(x + 12, y + 12), &Rgba([9, 9, 9, 9]));
+        assert_eq!(image.get_pixel(x + 15, y + 15), &Rgba([9, 9, 9, 9]));
+    }
+}
+
+#[test]
+fn test_region_rows() {
+    let mut cell = ImageCell::new(Image::<Rgb<u8>, _>::new(10, 10));
+    let mut region = cell.region_mut(rect(2, 3, 4, 2)).unwrap();
+    assert_eq!(region.dimensions(), (4, 2));
+
+    region.row_mut(1).copy_from_slice(&[1; 12]);
+    assert_eq!(region.row(1), &[1; 12]);
+    assert_eq!(region.get_pixel(3, 1), Rgb([1, 1, 1]));
+    assert_eq!(region.get_pixel(3, 0), Rgb([0, 0, 0]));
+
+    let image = cell.into_inner();
+    assert_eq!(image.get_pixel(2, 4), &Rgb([1, 1, 1]));
+    assert_eq!(image.get_pixel(6, 4), &Rgb([0, 0, 0]));
+}
+
+#[test]
+#[should_panic(expected = "row 2 lies outside of the region")]
+fn test_region_row_out_of_bounds() {
+    let mut cell = ImageCell::new(Image::<Rgb<u8>, _>::new(10, 10));
+    let region = cell.region_mut(rect(0, 0, 4, 2)).unwrap();
+    region.row(2);
+}
+
+#[test]
+fn test_regions_checked() {
+    let mut cell = ImageCell::new(Image::<Rgb<u8>, _>::new(10, 10));
+
+    assert!(matches!(
+        cell.regions_mut(&[rect(0, 0, 5, 5), rect(6, 6, 5, 4)]),
+        Err(MergeError::RegionOutOfBounds {
+            dimensions: (10, 10),
+            ..
+        })
+    ));
+
+    match cell.regions_mut(&[rect(0, 0, 5, 5), rect(5, 0, 5, 5), rect(4, 4, 2, 2)]) {
+        Err(MergeError::RegionOverlap { first, second }) => {
+            assert_eq!(first, rect(0, 0, 5, 5));
+            assert_eq!(second, rect(4, 4, 2, 2));
+        }
+        _ => panic!("expected the regions to overlap"),
+    }
+
+    // Regions that only touch, or are empty, do not overlap.
+    assert_eq!(
+        cell.regions_mut(&[rect(0, 0, 5, 10), rect(5, 0, 5, 10), rect(2, 2, 0, 3)])
+            .unwrap()
+            .len(),
+        3
+    );
+}
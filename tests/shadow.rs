@@ -0,0 +1,147 @@
+use image_merger::*;
+
+const RED: Rgb<u8> = Rgb([255, 0, 0]);
+const BLACK: Rgb<u8> = Rgb([0, 0, 0]);
+const WHITE: Rgb<u8> = Rgb([255, 255, 255]);
+
+fn merger(shadow: Shadow<Rgb<u8>>) -> KnownSizeMerger<Rgb<u8>, Vec<u8>> {
+    KnownSizeMerger::builder((10, 10))
+        .with_images_per_row(2)
+        .with_total_images(4)
+        .with_padding(Point { x: 10, y: 10 })
+        .with_margin(5)
+        .with_background(WHITE)
+        .with_decoration(CellDecoration::default().with_shadow(shadow))
+        .build()
+}
+
+fn red() -> BufferedImage<Rgb<u8>> {
+    BufferedImage::new_from_pixel(10, 10, RED)
+}
+
+#[test]
+fn test_drop_shadow_lies_beneath_image() {
+    let mut merger = merger(Shadow::new((3, 2), 0, BLACK, 0.5));
+    merger.push(&red());
+
+    let canvas = merger.get_canvas();
+    assert_eq!(*canvas.get_pixel(5, 5), RED);
+    assert_eq!(*canvas.get_pixel(14, 14), RED);
+    assert_eq!(*canvas.get_pixel(17, 16), Rgb([128, 128, 128]));
+    assert_eq!(*canvas.get_pixel(15, 7), Rgb([128, 128, 128]));
+    assert_eq!(*canvas.get_pixel(15, 6), WHITE);
+    assert_eq!(*canvas.get_pixel(18, 16), WHITE);
+    assert_eq!(*canvas.get_pixel(4, 10), WHITE);
+}
+
+#[test]
+fn test_glow_fades_out_evenly() {
+    let mut merger = merger(Shadow::glow(4, BLACK, 1.0));
+    merger.push(&red());
+
+    let canvas = merger.get_canvas();
+    let shade = |x, y| canvas.get_pixel(x, y).0[0];
+
+    // The glow fades out away from the image, the same way on every side, and ends at the blur radius.
+    assert!(shade(4, 10) < shade(3, 10));
+    assert!(shade(3, 10) < shade(2, 10));
+    assert_eq!(shade(4, 10), shade(15, 10));
+    assert_eq!(shade(10, 4), shade(10, 15));
+    assert_eq!(*canvas.get_pixel(0, 10), WHITE);
+    assert_eq!(*canvas.get_pixel(10, 10), RED);
+}
+
+#[test]
+fn test_shadow_must_fit_between_cells() {
+    let builder = KnownSizeMerger::<Rgb<u8>, _>::builder((10, 10))
+        .with_images_per_row(2)
+        .with_total_images(4)
+        .with_margin(5)
+        .with_decoration(CellDecoration::default().with_shadow(Shadow::new((2, 2), 3, BLACK, 0.5)));
+
+    // The shadow reaches 5 pixels right of and below every cell, and 1 pixel left of and above it.
+    assert!(matches!(
+        builder
+            .clone()
+            .with_padding(Point { x: 5, y: 6 })
+            .try_build(),
+        Err(MergeError::InvalidLayout(_))
+    ));
+    assert!(matches!(
+        builder
+            .clone()
+            .with_margin(4)
+            .with_padding(Point { x: 6, y: 6 })
+            .try_build(),
+        Err(MergeError::InvalidLayout(_))
+    ));
+    assert!(builder
+        .with_padding(Point { x: 6, y: 6 })
+        .try_build()
+        .is_ok());
+}
+
+#[test]
+fn test_bulk_push_matches_push() {
+    let shadow = Shadow::new((2, 2), 3, Rgb([0, 0, 80]), 0.7).with_filter(BlurFilter::Box);
+    let images: Vec<BufferedImage<Rgb<u8>>> = (0..4)
+        .map(|index| BufferedImage::new_from_pixel(10, 10, Rgb([index * 60, 100, 0])))
+        .collect();
+
+    let mut merger = merger(shadow);
+    merger.bulk_push(&images.iter().collect::<Vec<_>>());
+
+    let mut expected = self::merger(shadow);
+    for image in &images {
+        expected.push(image);
+    }
+
+    assert_eq!(**merger.get_canvas(), **expected.get_canvas());
+}
+
+#[test]
+fn test_remove_image_clears_shadow() {
+    let shadow = Shadow::new((-2, 1), 2, BLACK, 0.8);
+    let fresh = merger(shadow);
+
+    let mut merger = merger(shadow);
+    merger.bulk_push(&[&red(), &red(), &red(), &red()]);
+    for index in 0..4 {
+        merger.remove_image(index);
+    }
+
+    assert_eq!(**merger.get_canvas(), **fresh.get_canvas());
+}
+
+#[test]
+fn test_transparent_pixels_cast_no_shadow() {
+    let mut merger: KnownSizeMerger<Rgba<u8>, _> = KnownSizeMerger::builder((10, 10))
+        .with_images_per_row(1)
+        .with_total_images(1)
+        .with_margin(4)
+        .with_background(Rgba([255, 255, 255, 255]))
+        .with_composite(Composite::new(CompositeOp::SourceOver))
+        .with_decoration(CellDecoration::default().with_shadow(Shadow::new(
+            (2, 0),
+            0,
+            Rgba([0, 0, 0, 255]),
+            1.0,
+        )))
+        .build();
+
+    // Only the left half of the image is opaque, so only it casts a shadow, which the right half lets through.
+    let mut image = BufferedImage::new(10, 10);
+    for y in 0..10 {
+        for x in 0..5 {
+            image.put_pixel(x, y, Rgba([255, 0, 0, 255]));
+        }
+    }
+    merger.push(&image);
+
+    let canvas = merger.get_canvas();
+    assert_eq!(*canvas.get_pixel(8, 8), Rgba([255, 0, 0, 255]));
+    assert_eq!(*canvas.get_pixel(9, 8), Rgba([0, 0, 0, 255]));
+    assert_eq!(*canvas.get_pixel(10, 8), Rgba([0, 0, 0, 255]));
+    assert_eq!(*canvas.get_pixel(11, 8), Rgba([255, 255, 255, 255]));
+    assert_eq!(*canvas.get_pixel(15, 8), Rgba([255, 255, 255, 255]));
+}
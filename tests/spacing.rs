@@ -0,0 +1,149 @@
+use image_merger::*;
+
+type RgbImageBuffer = BufferedImage<Rgb<u8>>;
+
+fn spaced(
+    spacing: Spacing,
+    images_per_row: u32,
+    total_images: u32,
+) -> KnownSizeMerger<Rgb<u8>, Vec<u8>> {
+    KnownSizeMerger::builder((10, 10))
+        .with_images_per_row(images_per_row)
+        .with_total_images(total_images)
+        .with_spacing(spacing)
+        .with_background(Rgb([255, 255, 255]))
+        .build()
+}
+
+#[test]
+fn test_uneven_margins() {
+    let spacing = Spacing::default().with_margin(Margin {
+        top: 1,
+        right: 2,
+        bottom: 3,
+        left: 4,
+    });
+    let mut merger = spaced(spacing, 2, 2);
+    let image: RgbImageBuffer = Image::new_from_pixel(10, 10, Rgb([0, 0, 0]));
+    merger.bulk_push(&[&image, &image]);
+
+    let canvas = merger.get_canvas();
+    assert_eq!(canvas.dimensions(), (26, 14));
+    assert_eq!(*canvas.get_pixel(3, 1), Rgb([255, 255, 255]));
+    assert_eq!(*canvas.get_pixel(4, 1), Rgb([0, 0, 0]));
+    assert_eq!(*canvas.get_pixel(4, 0), Rgb([255, 255, 255]));
+    assert_eq!(*canvas.get_pixel(23, 10), Rgb([0, 0, 0]));
+    assert_eq!(*canvas.get_pixel(24, 10), Rgb([255, 255, 255]));
+    assert_eq!(*canvas.get_pixel(23, 11), Rgb([255, 255, 255]));
+}
+
+#[test]
+fn test_gutter_overrides() {
+    let spacing = Spacing::default()
+        .with_margin(5)
+        .with_gutter(Point { x: 1, y: 2 })
+        .with_column_gutter(0, 7)
+        .with_row_gutter(1, 20);
+    let merger = spaced(spacing, 3, 9);
+
+    let origins: Vec<(u32, u32)> = (0..9)
+        .map(|index| {
+            let cell = merger.get_cell(index).unwrap();
+            (cell.x, cell.y)
+        })
+        .collect();
+    assert_eq!(
+        origins,
+        vec![
+            (5, 5),
+            (22, 5),
+            (33, 5),
+            (5, 17),
+            (22, 17),
+            (33, 17),
+            (5, 47),
+            (22, 47),
+            (33, 47),
+        ]
+    );
+    assert_eq!(merger.get_canvas().dimensions(), (48, 62));
+}
+
+#[test]
+fn test_gutter_overrides_paste_and_split() {
+    let spacing = Spacing::default()
+        .with_margin(Margin {
+            top: 3,
+            right: 0,
+            bottom: 6,
+            left: 9,
+        })
+        .with_gutter(Point { x: 2, y: 2 })
+        .with_column_gutter(1, 12)
+        .with_row_gutter(0, 5);
+
+    let images: Vec<RgbImageBuffer> = (0..7)
+        .map(|index| Image::new_from_pixel(10, 10, Rgb([index as u8 * 30, 0, 0])))
+        .collect();
+    let mut merger = spaced(spacing, 4, 8);
+    merger.bulk_push(&images.iter().collect::<Vec<_>>());
+
+    let canvas = merger.get_canvas();
+    assert_eq!(canvas.dimensions(), (9 + 40 + 2 + 12 + 2, 3 + 20 + 5 + 6));
+    assert_eq!(*canvas.get_pixel(42, 8), Rgb([255, 255, 255]));
+    assert_eq!(*canvas.get_pixel(43, 8), Rgb([60, 0, 0]));
+
+    let split = Splitter::from_atlas(&merger.get_atlas()).split(canvas);
+    assert_eq!(split.len(), images.len());
+    for (split, image) in split.iter().zip(&images) {
+        assert_eq!(**split, **image);
+    }
+}
+
+#[test]
+fn test_padding_constructor_matches_spacing() {
+    let image: RgbImageBuffer = Image::new_from_pixel(10, 10, Rgb([1, 2, 3]));
+
+    let mut padded: KnownSizeMerger<Rgb<u8>, _> =
+        KnownSizeMerger::new((10, 10), 3, 6, Some(Point { x: 4, y: 1 }));
+    let mut spaced: KnownSizeMerger<Rgb<u8>, _> = KnownSizeMerger::builder((10, 10))
+        .with_images_per_row(3)
+        .with_total_images(6)
+        .with_spacing(Spacing::default().with_gutter(Point { x: 4, y: 1 }))
+        .build();
+
+    padded.bulk_push(&[&image, &image, &image, &image]);
+    spaced.bulk_push(&[&image, &image, &image, &image]);
+
+    assert_eq!(**padded.get_canvas(), **spaced.get_canvas());
+    assert_eq!(padded.get_spacing(), spaced.get_spacing());
+}
+
+#[test]
+fn test_column_gutter_outside_grid() {
+    let merger = KnownSizeMerger::<Rgb<u8>, _>::builder((10, 10))
+        .with_images_per_row(3)
+        .with_total_images(6)
+        .with_spacing(Spacing::default().with_column_gutter(2, 5))
+        .try_build();
+
+    assert!(matches!(merger, Err(MergeError::InvalidLayout(_))));
+}
+
+#[test]
+fn test_row_gutter_outside_grid() {
+    let builder = KnownSizeMerger::<Rgb<u8>, _>::builder((10, 10))
+        .with_images_per_row(3)
+        .with_total_images(6);
+
+    let merger = builder
+        .clone()
+        .with_spacing(Spacing::default().with_row_gutter(1, 5))
+        .try_build();
+    assert!(matches!(merger, Err(MergeError::InvalidLayout(_))));
+
+    let merger = builder
+        .with_spacing(Spacing::default().with_row_gutter(0, 5))
+        .try_build();
+    assert!(merger.is_ok());
+}
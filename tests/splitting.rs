@@ -0,0 +1,136 @@
+use image_merger::*;
+
+/// Generates a solid image, colored by its index so images can be told apart once split.
+fn generate_test_image(width: u32, height: u32, index: u8) -> BufferedImage<Rgb<u8>> {
+    BufferedImage::new_from_pixel(width, height, Rgb([index + 1, 255 - index, 50]))
+}
+
+#[test]
+fn test_split_grid() {
+    let images: Vec<BufferedImage<Rgb<u8>>> =
+        (0..7).map(|i| generate_test_image(20, 10, i)).collect();
+    let refs: Vec<&BufferedImage<Rgb<u8>>> = images.iter().collect();
+    let padding = Point { x: 3, y: 2 };
+
+    let mut merger: KnownSizeMerger<Rgb<u8>, _> =
+        KnownSizeMerger::new((20, 10), 3, 7, Some(padding));
+    merger.bulk_push(&refs);
+
+    let split = Splitter::new((20, 10), 3, 7, Some(padding)).split(merger.get_canvas());
+    assert_eq!(split.len(), images.len());
+    for (split, image) in split.iter().zip(&images) {
+        assert_eq!(**split, **image);
+    }
+}
+
+#[test]
+fn test_split_detected_grid() {
+    let images: Vec<BufferedImage<Rgb<u8>>> =
+        (0..7).map(|i| generate_test_image(20, 10, i)).collect();
+    let refs: Vec<&BufferedImage<Rgb<u8>>> = images.iter().collect();
+
+    let mut merger: KnownSizeMerger<Rgb<u8>, _> =
+        KnownSizeMerger::new((20, 10), 3, 9, Some(Point { x: 3, y: 2 }));
+    merger.bulk_push(&refs);
+
+    // The padding is left black, the color of the first uniform column.
+    let splitter = Splitter::detect(merger.get_canvas(), None).unwrap();
+    assert_eq!(
+        splitter,
+        Splitter::new((20, 10), 3, 7, Some(Point { x: 3, y: 2 }))
+    );
+
+    let split = splitter.split(merger.get_canvas());
+    for (split, image) in split.iter().zip(&images) {
+        assert_eq!(**split, **image);
+    }
+}
+
+#[test]
+fn test_detect_single_row() {
+    let images: Vec<BufferedImage<Rgb<u8>>> =
+        (0..4).map(|i| generate_test_image(10, 10, i)).collect();
+    let refs: Vec<&BufferedImage<Rgb<u8>>> = images.iter().collect();
+
+    let mut merger: KnownSizeMerger<Rgb<u8>, _> =
+        KnownSizeMerger::new((10, 10), 4, 4, Some(Point { x: 5, y: 0 }));
+    merger.bulk_push(&refs);
+
+    let splitter = Splitter::detect(merger.get_canvas(), Some(Rgb([0, 0, 0]))).unwrap();
+    assert_eq!(
+        splitter,
+        Splitter::new((10, 10), 4, 4, Some(Point { x: 5, y: 0 }))
+    );
+}
+
+#[test]
+fn test_detect_without_padding() {
+    let image = generate_test_image(10, 10, 1);
+    let mut merger: KnownSizeMerger<Rgb<u8>, _> = KnownSizeMerger::new((10, 10), 2, 2, None);
+    merger.bulk_push(&[&image, &image]);
+
+    assert!(matches!(
+        Splitter::detect(merger.get_canvas(), None),
+        Err(MergeError::InvalidLayout(_))
+    ));
+}
+
+#[test]
+fn test_split_packed_atlas() {
+    let images: Vec<BufferedImage<Rgba<u8>>> = (0..12u32)
+        .map(|i| {
+            let mut image = BufferedImage::new(5 + i * 3, 30 - i * 2);
+            for (x, y, pixel) in image.enumerate_pixels_mut() {
+                *pixel = Rgba([x as u8, y as u8, i as u8, 255]);
+            }
+            image
+        })
+        .collect();
+    let refs: Vec<&BufferedImage<Rgba<u8>>> = images.iter().collect();
+
+    let merger = PackingMerger::pack(
+        &refs,
+        PackingOptions {
+            allow_rotation: true,
+            padding: Some(Point { x: 1, y: 1 }),
+            ..Default::default()
+        },
+    );
+
+    for json in [
+        merger.get_atlas().to_json(),
+        merger.get_atlas().to_texture_packer_array("atlas.png"),
+    ] {
+        let atlas = Atlas::from_json(&json).unwrap();
+        assert_eq!(atlas, merger.get_atlas());
+
+        let split = Splitter::from_atlas(&atlas).split(merger.get_canvas());
+        for (split, image) in split.iter().zip(&images) {
+            assert_eq!(**split, **image);
+        }
+    }
+}
+
+#[test]
+fn test_try_split_out_of_bounds() {
+    let canvas: BufferedImage<Rgb<u8>> = BufferedImage::new(50, 50);
+    assert!(matches!(
+        Splitter::new((20, 20), 3, 3, None).try_split(&canvas),
+        Err(MergeError::DimensionMismatch {
+            expected: (60, 20),
+            actual: (50, 50)
+        })
+    ));
+}
+
+#[test]
+fn test_invalid_atlas() {
+    assert!(matches!(
+        Atlas::from_json("{\"frames\": 3, \"meta\": {\"size\": {\"w\": 1, \"h\": 1}}}"),
+        Err(MergeError::InvalidAtlas(_))
+    ));
+    assert!(matches!(
+        Atlas::from_json("not json"),
+        Err(MergeError::InvalidAtlas(_))
+    ));
+}
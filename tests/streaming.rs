@@ -0,0 +1,198 @@
+use image_merger::*;
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+    time::Duration,
+};
+
+const IMAGE_SIZE: u32 = 32;
+
+type RgbImageBuffer = BufferedImage<Rgb<u8>>;
+
+fn test_image(index: u32, width: u32, height: u32) -> RgbImageBuffer {
+    RgbImageBuffer::new_from_pixel(width, height, Rgb([index as u8 * 10, 100, 200]))
+}
+
+/// A source that records how many sources are being loaded at once.
+struct Tracked<'a> {
+    index: u32,
+    live: &'a AtomicUsize,
+    peak: &'a AtomicUsize,
+}
+
+impl ImageSource<Rgb<u8>> for Tracked<'_> {
+    fn dimensions_hint(&self) -> Option<(u32, u32)> {
+        Some((IMAGE_SIZE, IMAGE_SIZE))
+    }
+
+    fn load(self) -> Result<RgbImageBuffer, MergeError> {
+        let live = self.live.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak.fetch_max(live, Ordering::SeqCst);
+        thread::sleep(Duration::from_millis(5));
+        self.live.fetch_sub(1, Ordering::SeqCst);
+
+        Ok(test_image(self.index, IMAGE_SIZE, IMAGE_SIZE))
+    }
+}
+
+#[test]
+fn test_push_stream_matches_bulk_push_resized() {
+    let images: Vec<RgbImageBuffer> = (0..12)
+        .map(|index| test_image(index, 20 + index * 7, 50 - index * 2))
+        .collect();
+
+    let mut expected: KnownSizeMerger<Rgb<u8>, _> =
+        KnownSizeMerger::new((IMAGE_SIZE, IMAGE_SIZE), 5, 12, Some(Point { x: 2, y: 3 }));
+    expected.set_resize_filter(ResizeFilter::Bilinear);
+    expected.bulk_push_resized(&images.iter().collect::<Vec<_>>());
+
+    let mut merger: KnownSizeMerger<Rgb<u8>, _> =
+        KnownSizeMerger::new((IMAGE_SIZE, IMAGE_SIZE), 5, 12, Some(Point { x: 2, y: 3 }));
+    merger.set_resize_filter(ResizeFilter::Bilinear);
+    merger.push_stream(
+        images,
+        &StreamOptions {
+            max_in_flight: 3,
+            memory_budget: None,
+        },
+    );
+
+    assert_eq!(merger.get_num_images(), 12);
+    assert_eq!(**merger.get_canvas(), **expected.get_canvas());
+}
+
+#[test]
+fn test_push_stream_bounds_in_flight() {
+    let cell_bytes = (IMAGE_SIZE * IMAGE_SIZE * 3) as usize;
+    let cases = [
+        (
+            StreamOptions {
+                max_in_flight: 2,
+                memory_budget: None,
+            },
+            2,
+        ),
+        // Every image reserves its decoded and resized bytes, so three images fit in the budget.
+        (
+            StreamOptions {
+                max_in_flight: 64,
+                memory_budget: Some(cell_bytes * 6),
+            },
+            3,
+        ),
+    ];
+
+    for (options, limit) in cases {
+        let (live, peak) = (AtomicUsize::new(0), AtomicUsize::new(0));
+        let sources = (0..24).map(|index| Tracked {
+            index,
+            live: &live,
+            peak: &peak,
+        });
+
+        let mut merger: KnownSizeMerger<Rgb<u8>, _> =
+            KnownSizeMerger::new((IMAGE_SIZE, IMAGE_SIZE), 6, 24, None);
+        merger.push_stream(sources, &options);
+
+        assert_eq!(merger.get_num_images(), 24);
+        assert!(peak.load(Ordering::SeqCst) <= limit);
+        for (index, placement) in merger.get_placements().iter().enumerate() {
+            let rect = placement.rect;
+            assert_eq!(
+                *merger.get_canvas().get_pixel(rect.x, rect.y),
+                Rgb([index as u8 * 10, 100, 200])
+            );
+        }
+    }
+}
+
+#[test]
+fn test_try_push_stream_stops_at_first_error() {
+    let sources = (0..8u32).map(|index| {
+        move || {
+            if index == 3 {
+                Err(MergeError::InvalidLayout("broken source"))
+            } else {
+                Ok(test_image(index, IMAGE_SIZE, IMAGE_SIZE))
+            }
+        }
+    });
+
+    let mut merger: KnownSizeMerger<Rgb<u8>, _> =
+        KnownSizeMerger::new((IMAGE_SIZE, IMAGE_SIZE), 4, 8, None);
+    assert!(matches!(
+        merger.try_push_stream(sources, &StreamOptions::default()),
+        Err(MergeError::InvalidLayout("broken source"))
+    ));
+
+    // Only the images before the failing one are kept, the slots after it are free again.
+    assert_eq!(merger.get_num_images(), 3);
+    for index in 3..8 {
+        let cell = merger.get_cell(index).unwrap();
+        assert_eq!(
+            *merger.get_canvas().get_pixel(cell.x, cell.y),
+            Rgb([0, 0, 0])
+        );
+    }
+
+    merger.push(&test_image(9, IMAGE_SIZE, IMAGE_SIZE));
+    let cell = merger.get_cell(3).unwrap();
+    assert_eq!(
+        *merger.get_canvas().get_pixel(cell.x, cell.y),
+        Rgb([90, 100, 200])
+    );
+}
+
+#[test]
+fn test_try_push_stream_canvas_full() {
+    let images = || (0..5).map(|index| test_image(index, IMAGE_SIZE, IMAGE_SIZE));
+
+    let mut merger: KnownSizeMerger<Rgb<u8>, _> =
+        KnownSizeMerger::new((IMAGE_SIZE, IMAGE_SIZE), 2, 4, None);
+    assert!(matches!(
+        merger.try_push_stream(images(), &StreamOptions::default()),
+        Err(MergeError::CanvasFull {
+            remaining: 4,
+            requested: 5
+        })
+    ));
+    assert_eq!(merger.get_num_images(), 0);
+
+    // Without a size hint, the images that fit are pushed before the canvas runs out.
+    let unsized_images = images().filter(|_| true);
+    assert!(matches!(
+        merger.try_push_stream(unsized_images, &StreamOptions::default()),
+        Err(MergeError::CanvasFull {
+            remaining: 0,
+            requested: 1
+        })
+    ));
+    assert_eq!(merger.get_num_images(), 4);
+}
+
+#[test]
+fn test_unknown_size_push_stream_grows_canvas() {
+    // Filtering hides the length of the stream, so the canvas has to grow while streaming.
+    let sources = (0..23)
+        .map(|index| test_image(index, IMAGE_SIZE * 2, IMAGE_SIZE))
+        .filter(|_| true);
+
+    let mut merger: UnknownSizeMerger<Rgb<u8>> =
+        UnknownSizeMerger::new((IMAGE_SIZE, IMAGE_SIZE), 5, None);
+    merger.set_fit_mode(FitMode::Cover {
+        gravity: Alignment::Center,
+    });
+    merger.push_stream(sources, &StreamOptions::default());
+
+    assert_eq!(merger.get_num_images(), 23);
+    let placements = merger.get_placements();
+    let canvas = merger.into_canvas();
+    assert_eq!(canvas.dimensions(), (IMAGE_SIZE * 5, IMAGE_SIZE * 5));
+    for (index, placement) in placements.iter().enumerate() {
+        let rect = placement.rect;
+        assert_eq!(
+            *canvas.get_pixel(rect.x + rect.width - 1, rect.y),
+            Rgb([index as u8 * 10, 100, 200])
+        );
+    }
+}
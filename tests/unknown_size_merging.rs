@@ -0,0 +1,115 @@
+use image_merger::*;
+
+static IMAGES_PER_ROW: u32 = 10;
+static TOTAL_IMAGES: u32 = 95;
+static PADDING_X: u32 = 10;
+static PADDING_Y: u32 = 10;
+static IMAGE_WIDTH: u32 = 50;
+static IMAGE_HEIGHT: u32 = 50;
+
+type RgbaImageBuffer = BufferedImage<Rgba<u8>>;
+
+fn generate_test_square() -> RgbaImageBuffer {
+    let mut image = RgbaImageBuffer::new(IMAGE_WIDTH, IMAGE_HEIGHT);
+    for x in 0..IMAGE_WIDTH {
+        for y in 0..IMAGE_HEIGHT {
+            image.put_pixel(x, y, Rgba([x as u8, y as u8, (x + y) as u8, 255]));
+        }
+    }
+
+    image
+}
+
+fn merge_known(total_images: u32, padding: Option<Padding>) -> RgbaImageBuffer {
+    let test_square = generate_test_square();
+
+    let mut merger: KnownSizeMerger<Rgba<u8>, _> = KnownSizeMerger::new(
+        (IMAGE_WIDTH, IMAGE_HEIGHT),
+        IMAGES_PER_ROW,
+        total_images,
+        padding,
+    );
+    merger.bulk_push(&vec![&test_square; total_images as usize]);
+
+    merger.into_canvas()
+}
+
+#[test]
+fn test_push_grows_canvas() {
+    let test_square = generate_test_square();
+    let padding = Some(Padding {
+        x: PADDING_X,
+        y: PADDING_Y,
+    });
+
+    let mut merger: UnknownSizeMerger<Rgba<u8>> =
+        UnknownSizeMerger::new((IMAGE_WIDTH, IMAGE_HEIGHT), IMAGES_PER_ROW, padding);
+    assert_eq!(merger.get_capacity(), IMAGES_PER_ROW);
+
+    for _ in 0..TOTAL_IMAGES {
+        merger.push(&test_square);
+    }
+
+    assert_eq!(merger.get_num_images(), TOTAL_IMAGES);
+    assert!(merger.get_capacity() >= TOTAL_IMAGES);
+    assert_eq!(merger.into_canvas(), merge_known(TOTAL_IMAGES, padding));
+}
+
+#[test]
+fn test_bulk_push_grows_canvas() {
+    let test_square = generate_test_square();
+
+    let mut merger: UnknownSizeMerger<Rgba<u8>> =
+        UnknownSizeMerger::new((IMAGE_WIDTH, IMAGE_HEIGHT), IMAGES_PER_ROW, None);
+    merger.bulk_push(&[&test_square; 15]);
+    merger.bulk_push(&vec![&test_square; (TOTAL_IMAGES - 15) as usize]);
+
+    assert_eq!(merger.get_num_images(), TOTAL_IMAGES);
+    assert_eq!(merger.into_canvas(), merge_known(TOTAL_IMAGES, None));
+}
+
+#[test]
+fn test_failed_bulk_push_does_not_grow_canvas() {
+    let test_square = generate_test_square();
+    let wrong_size = RgbaImageBuffer::new(IMAGE_WIDTH + 1, IMAGE_HEIGHT);
+
+    let mut merger: UnknownSizeMerger<Rgba<u8>> =
+        UnknownSizeMerger::new((IMAGE_WIDTH, IMAGE_HEIGHT), IMAGES_PER_ROW, None);
+    let result = merger.try_bulk_push(&[&test_square, &wrong_size]);
+    assert!(matches!(result, Err(MergeError::DimensionMismatch { .. })));
+    assert!(merger.try_push(&wrong_size).is_err());
+
+    assert_eq!(merger.get_capacity(), IMAGES_PER_ROW);
+    assert_eq!(merger.get_canvas().height(), IMAGE_HEIGHT);
+}
+
+#[test]
+fn test_shrink_to_fit() {
+    let test_square = generate_test_square();
+
+    let mut merger: UnknownSizeMerger<Rgba<u8>> =
+        UnknownSizeMerger::with_capacity((IMAGE_WIDTH, IMAGE_HEIGHT), IMAGES_PER_ROW, 100, None);
+    merger.bulk_push(&[&test_square; 25]);
+    assert_eq!(merger.get_canvas().height(), IMAGE_HEIGHT * 10);
+
+    merger.shrink_to_fit();
+    assert_eq!(merger.get_capacity(), IMAGES_PER_ROW * 3);
+    assert_eq!(merger.get_canvas().height(), IMAGE_HEIGHT * 3);
+    assert_eq!(merger.get_canvas(), &merge_known(25, None));
+}
+
+#[test]
+fn test_resizable_unknown_size_merger() {
+    let mut merger: UnknownSizeMerger<Rgba<u8>> =
+        UnknownSizeMerger::new((IMAGE_WIDTH, IMAGE_HEIGHT), IMAGES_PER_ROW, None);
+
+    let test_square = Image::from(image::imageops::resize(
+        &generate_test_square().into_buffer(),
+        IMAGE_WIDTH * 2,
+        IMAGE_HEIGHT * 2,
+        image::imageops::FilterType::Nearest,
+    ));
+
+    merger.bulk_push_resized(&vec![&test_square; TOTAL_IMAGES as usize]);
+    assert_eq!(merger.get_num_images(), TOTAL_IMAGES);
+}
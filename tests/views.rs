@@ -0,0 +1,162 @@
+use image::{buffer::ConvertBuffer, DynamicImage, GenericImageView};
+use image_merger::*;
+
+type RgbaImageBuffer = BufferedImage<Rgba<u8>>;
+
+fn gradient<Q: Pixel>(width: u32, height: u32, pixel: impl Fn(u32, u32) -> Q) -> BufferedImage<Q> {
+    let mut image = BufferedImage::<Q>::new(width, height);
+    for y in 0..height {
+        for x in 0..width {
+            image.put_pixel(x, y, pixel(x, y));
+        }
+    }
+
+    image
+}
+
+/// Copies a region of an image into its own buffer, to compare against pushing a view of the region.
+fn cropped(image: &RgbaImageBuffer, x: u32, y: u32, width: u32, height: u32) -> RgbaImageBuffer {
+    let view = image.view(x, y, width, height);
+    gradient(width, height, |x, y| view.get_pixel(x, y))
+}
+
+fn sheet() -> RgbaImageBuffer {
+    gradient(40, 30, |x, y| {
+        Rgba([x as u8 * 6, y as u8 * 8, (x + y) as u8, 255])
+    })
+}
+
+#[test]
+fn test_push_sub_image_matches_cropped_copy() {
+    let sheet = sheet();
+
+    let mut merger: KnownSizeMerger<Rgba<u8>, _> =
+        KnownSizeMerger::new((10, 10), 2, 4, Some(Point { x: 1, y: 2 }));
+    merger.push(&sheet.view(0, 0, 10, 10));
+    merger.bulk_push(&[&sheet.view(30, 20, 10, 10), &sheet.view(13, 7, 10, 10)]);
+
+    let mut expected: KnownSizeMerger<Rgba<u8>, _> =
+        KnownSizeMerger::new((10, 10), 2, 4, Some(Point { x: 1, y: 2 }));
+    expected.push(&cropped(&sheet, 0, 0, 10, 10));
+    expected.bulk_push(&[
+        &cropped(&sheet, 30, 20, 10, 10),
+        &cropped(&sheet, 13, 7, 10, 10),
+    ]);
+
+    assert_eq!(**merger.get_canvas(), **expected.get_canvas());
+}
+
+#[test]
+fn test_push_sub_image_follows_policy() {
+    let sheet = sheet();
+
+    for policy in [SizePolicy::CenterSmaller, SizePolicy::ResizeToFit] {
+        let mut merger: KnownSizeMerger<Rgba<u8>, _> = KnownSizeMerger::new((12, 12), 2, 2, None);
+        merger.set_size_policy(policy);
+        merger.push(&sheet.view(5, 5, 6, 4));
+        merger.push(&sheet.view(0, 0, 30, 25));
+
+        let mut expected: KnownSizeMerger<Rgba<u8>, _> = KnownSizeMerger::new((12, 12), 2, 2, None);
+        expected.set_size_policy(policy);
+        expected.push(&cropped(&sheet, 5, 5, 6, 4));
+        expected.push(&cropped(&sheet, 0, 0, 30, 25));
+
+        assert_eq!(**merger.get_canvas(), **expected.get_canvas());
+    }
+}
+
+#[test]
+fn test_push_borrowed_buffer() {
+    let bytes: Vec<u8> = (0..8 * 8 * 4).map(|index| index as u8).collect();
+    let borrowed: ImageBuffer<Rgba<u8>, &[u8]> = ImageBuffer::from_raw(8, 8, &bytes[..]).unwrap();
+
+    let mut merger: UnknownSizeMerger<Rgba<u8>> = UnknownSizeMerger::new((8, 8), 2, None);
+    merger.push(&borrowed);
+    merger.push(&borrowed);
+    merger.push(&borrowed);
+
+    let canvas = merger.into_canvas();
+    assert_eq!(canvas.dimensions(), (16, 16));
+    assert_eq!(*canvas.get_pixel(1, 0), Rgba([4, 5, 6, 7]));
+    assert_eq!(*canvas.get_pixel(9, 1), Rgba([36, 37, 38, 39]));
+    assert_eq!(*canvas.get_pixel(7, 15), Rgba([252, 253, 254, 255]));
+}
+
+#[test]
+fn test_push_generic_view() {
+    let image = DynamicImage::ImageRgba8(
+        gradient(6, 6, |x, y| Rgba([x as u8, y as u8, 3, 255])).into_buffer(),
+    );
+
+    let mut merger: KnownSizeMerger<Rgba<u8>, _> = KnownSizeMerger::new((6, 6), 1, 2, None);
+    merger.push(&GenericView(&image));
+
+    assert_eq!(*merger.get_canvas().get_pixel(4, 5), Rgba([4, 5, 3, 255]));
+    assert_eq!(*merger.get_canvas().get_pixel(4, 11), Rgba([0, 0, 0, 0]));
+}
+
+#[test]
+fn test_push_dynamic_converts_every_variant() {
+    let rgb16 = gradient(6, 6, |x, y| {
+        Rgb([x as u16 * 10_000, y as u16 * 10_000, 65_535])
+    });
+    let luma8 = gradient(6, 6, |x, y| Luma([(x * 40 + y) as u8]));
+    let rgba32f = gradient(6, 6, |x, y| {
+        Rgba([x as f32 / 5.0, y as f32 / 5.0, 0.5, 1.0])
+    });
+
+    let mut merger: KnownSizeMerger<Rgba<u8>, _> = KnownSizeMerger::new((6, 6), 3, 3, None);
+    merger.push_dynamic(&DynamicImage::ImageRgb16((*rgb16).clone()));
+    merger.push_dynamic(&DynamicImage::ImageLuma8((*luma8).clone()));
+    merger.push_dynamic(&DynamicImage::ImageRgba32F((*rgba32f).clone()));
+
+    let mut expected: KnownSizeMerger<Rgba<u8>, _> = KnownSizeMerger::new((6, 6), 3, 3, None);
+    let rgb16: ImageBuffer<Rgba<u8>, Vec<u8>> = (*rgb16).convert();
+    let luma8: ImageBuffer<Rgba<u8>, Vec<u8>> = (*luma8).convert();
+    let rgba32f: ImageBuffer<Rgba<u8>, Vec<u8>> = (*rgba32f).convert();
+    expected.bulk_push(&[&rgb16, &luma8, &rgba32f]);
+
+    assert_eq!(**merger.get_canvas(), **expected.get_canvas());
+    assert_eq!(merger.get_num_images(), 3);
+}
+
+#[test]
+fn test_try_push_dynamic_canvas_full() {
+    let image = DynamicImage::new_luma16(4, 4);
+
+    let mut merger: KnownSizeMerger<Rgb<u8>, _> = KnownSizeMerger::new((4, 4), 1, 1, None);
+    assert!(merger.try_push_dynamic(&image).is_ok());
+    assert!(matches!(
+        merger.try_push_dynamic(&image),
+        Err(MergeError::CanvasFull { .. })
+    ));
+}
+
+#[test]
+fn test_packing_bulk_push_sub_images_matches_cropped_copies() {
+    let sheet = sheet();
+    let regions = [(0, 0, 12, 5), (3, 4, 4, 20), (20, 10, 15, 15), (8, 2, 9, 9)];
+    let views: Vec<_> = regions
+        .iter()
+        .map(|&(x, y, width, height)| sheet.view(x, y, width, height))
+        .collect();
+    let copies: Vec<RgbaImageBuffer> = regions
+        .iter()
+        .map(|&(x, y, width, height)| cropped(&sheet, x, y, width, height))
+        .collect();
+
+    let options = PackingOptions {
+        allow_rotation: true,
+        padding: Some(Point { x: 1, y: 1 }),
+        ..PackingOptions::default()
+    };
+
+    let mut merger: PackingMerger<Rgba<u8>> = PackingMerger::new((40, 40), options);
+    merger.bulk_push(&views.iter().collect::<Vec<_>>());
+
+    let mut expected: PackingMerger<Rgba<u8>> = PackingMerger::new((40, 40), options);
+    expected.bulk_push(&copies.iter().collect::<Vec<_>>());
+
+    assert_eq!(merger.get_placements(), expected.get_placements());
+    assert_eq!(**merger.get_canvas(), **expected.get_canvas());
+}